@@ -0,0 +1,331 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// 默认端口，`AUTOMATION_PORT` 可改
+const DEFAULT_PORT: u16 = 47_815;
+/// 请求体上限：命令都很短，超过的直接拒绝
+const MAX_BODY_BYTES: usize = 4096;
+/// 单个连接读写的超时，避免卡住的客户端堵住后面的请求
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 本地自动化接口的设置：只有配了 `AUTOMATION_TOKEN` 才会开
+#[derive(Debug, Clone)]
+pub struct AutomationConfig {
+    pub port: u16,
+    pub token: String,
+}
+
+impl AutomationConfig {
+    /// 读取 `AUTOMATION_TOKEN` / `AUTOMATION_PORT`；没有 token 返回 None
+    pub fn from_env() -> Option<Self> {
+        let token = std::env::var("AUTOMATION_TOKEN").ok()?.trim().to_string();
+        if token.is_empty() {
+            return None;
+        }
+        let port = std::env::var("AUTOMATION_PORT")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_PORT);
+        Some(Self { port, token })
+    }
+}
+
+/// 外部脚本能触发的命令，对应已有的 Tauri 命令
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `POST /feed {"food": "fish"}` → `feed_sophie`
+    Feed { food: String },
+    /// `POST /play {"toy": "ball"}` → `show_toy`
+    Play { toy: String },
+    /// `POST /notify {"event": "番茄钟结束"}`：告诉她外面发生了什么
+    Notify { event: String },
+    /// `GET /state` → `get_sophie_state`
+    GetState,
+}
+
+/// 请求被拒绝的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    Unauthorized,
+    NotFound,
+    BadRequest(String),
+}
+
+impl Rejection {
+    fn status(&self) -> (u16, &'static str) {
+        match self {
+            Rejection::Unauthorized => (401, "Unauthorized"),
+            Rejection::NotFound => (404, "Not Found"),
+            Rejection::BadRequest(_) => (400, "Bad Request"),
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Rejection::Unauthorized => "missing or wrong token".to_string(),
+            Rejection::NotFound => "unknown command".to_string(),
+            Rejection::BadRequest(reason) => reason.clone(),
+        }
+    }
+}
+
+/// 解析好的 HTTP 请求（只保留用得到的部分）
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// `Authorization: Bearer <token>` 里的 token
+    pub token: Option<String>,
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+struct FeedBody {
+    food: String,
+}
+
+#[derive(Deserialize)]
+struct PlayBody {
+    toy: String,
+}
+
+#[derive(Deserialize)]
+struct NotifyBody {
+    event: String,
+}
+
+/// 先验 token 再分派：token 不对时不透露路径是否存在
+pub fn route(req: &Request, token: &str) -> Result<Command, Rejection> {
+    if !req.token.as_deref().is_some_and(|given| token_matches(given, token)) {
+        return Err(Rejection::Unauthorized);
+    }
+    match (req.method.as_str(), req.path.as_str()) {
+        ("GET", "/state") => Ok(Command::GetState),
+        ("POST", "/feed") => parse_body::<FeedBody>(&req.body).map(|b| Command::Feed { food: b.food }),
+        ("POST", "/play") => parse_body::<PlayBody>(&req.body).map(|b| Command::Play { toy: b.toy }),
+        ("POST", "/notify") => {
+            let event = parse_body::<NotifyBody>(&req.body)?.event.trim().to_string();
+            if event.is_empty() {
+                return Err(Rejection::BadRequest("event is empty".to_string()));
+            }
+            Ok(Command::Notify { event })
+        }
+        _ => Err(Rejection::NotFound),
+    }
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, Rejection> {
+    serde_json::from_str(body).map_err(|e| Rejection::BadRequest(format!("invalid body: {}", e)))
+}
+
+/// 逐字节比较完整个 token，耗时不随第一个不同字节的位置变化
+fn token_matches(given: &str, expected: &str) -> bool {
+    let (a, b) = (given.as_bytes(), expected.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 从连接里读一个请求：请求行、头（只认 Authorization 和 Content-Length）、请求体
+pub fn read_request(stream: impl Read) -> Result<Request, Rejection> {
+    let bad = |reason: &str| Rejection::BadRequest(reason.to_string());
+    let mut reader = BufReader::new(stream.take((MAX_BODY_BYTES * 2) as u64));
+
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|_| bad("unreadable request"))?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(bad("malformed request line")),
+    };
+
+    let mut token = None;
+    let mut content_length = 0usize;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).map_err(|_| bad("unreadable headers"))? == 0 {
+            break;
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else { continue };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            token = value.strip_prefix("Bearer ").map(|t| t.trim().to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| bad("bad content-length"))?;
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(bad("body too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|_| bad("truncated body"))?;
+    let body = String::from_utf8(body).map_err(|_| bad("body is not utf-8"))?;
+    Ok(Request { method, path, token, body })
+}
+
+fn write_response(mut stream: impl Write, status: (u16, &str), body: &str) {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status.0,
+        status.1,
+        body.len(),
+        body
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()) {
+        log::warn!("Automation: failed to write response: {}", e);
+    }
+}
+
+fn handle_connection<F>(stream: TcpStream, token: &str, dispatch: &F)
+where
+    F: Fn(Command) -> Result<String, String>,
+{
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    let result = read_request(&stream).and_then(|req| route(&req, token));
+    match result {
+        Ok(command) => match dispatch(command) {
+            Ok(json) => write_response(&stream, (200, "OK"), &json),
+            Err(e) => {
+                let body = serde_json::json!({ "error": e }).to_string();
+                write_response(&stream, (500, "Internal Server Error"), &body);
+            }
+        },
+        Err(rejection) => {
+            if rejection == Rejection::Unauthorized {
+                log::warn!("Automation: rejected a request with a missing or wrong token");
+            }
+            let body = serde_json::json!({ "error": rejection.message() }).to_string();
+            write_response(&stream, rejection.status(), &body);
+        }
+    }
+}
+
+/// 只绑定 127.0.0.1
+pub fn bind(port: u16) -> Result<TcpListener, String> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|e| format!("Failed to bind automation port {}: {}", port, e))
+}
+
+/// 逐个处理连接（命令都很快，不需要并发），`dispatch` 返回 JSON 响应体
+pub fn serve<F>(listener: TcpListener, token: String, dispatch: F)
+where
+    F: Fn(Command) -> Result<String, String>,
+{
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => handle_connection(stream, &token, &dispatch),
+            Err(e) => log::warn!("Automation: failed to accept connection: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "s3cret-token";
+
+    fn request(method: &str, path: &str, token: Option<&str>, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            token: token.map(str::to_string),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn routes_requests_to_commands() {
+        let feed = request("POST", "/feed", Some(TOKEN), r#"{"food": "fish"}"#);
+        assert_eq!(route(&feed, TOKEN), Ok(Command::Feed { food: "fish".to_string() }));
+
+        let play = request("POST", "/play", Some(TOKEN), r#"{"toy": "ball"}"#);
+        assert_eq!(route(&play, TOKEN), Ok(Command::Play { toy: "ball".to_string() }));
+
+        let notify = request("POST", "/notify", Some(TOKEN), r#"{"event": " 番茄钟结束 "}"#);
+        assert_eq!(route(&notify, TOKEN), Ok(Command::Notify { event: "番茄钟结束".to_string() }));
+
+        let state = request("GET", "/state", Some(TOKEN), "");
+        assert_eq!(route(&state, TOKEN), Ok(Command::GetState));
+    }
+
+    #[test]
+    fn rejects_missing_or_wrong_token_before_routing() {
+        let missing = request("GET", "/state", None, "");
+        assert_eq!(route(&missing, TOKEN), Err(Rejection::Unauthorized));
+
+        let wrong = request("GET", "/state", Some("s3cret-tokem"), "");
+        assert_eq!(route(&wrong, TOKEN), Err(Rejection::Unauthorized));
+
+        let prefix = request("GET", "/state", Some("s3cret"), "");
+        assert_eq!(route(&prefix, TOKEN), Err(Rejection::Unauthorized));
+
+        // 不带 token 也探不出哪些路径存在
+        let unknown = request("GET", "/nope", None, "");
+        assert_eq!(route(&unknown, TOKEN), Err(Rejection::Unauthorized));
+    }
+
+    #[test]
+    fn rejects_unknown_paths_and_bad_bodies() {
+        let unknown = request("GET", "/nope", Some(TOKEN), "");
+        assert_eq!(route(&unknown, TOKEN), Err(Rejection::NotFound));
+
+        let wrong_method = request("GET", "/feed", Some(TOKEN), "");
+        assert_eq!(route(&wrong_method, TOKEN), Err(Rejection::NotFound));
+
+        let bad_json = request("POST", "/feed", Some(TOKEN), "fish");
+        assert!(matches!(route(&bad_json, TOKEN), Err(Rejection::BadRequest(_))));
+
+        let empty_event = request("POST", "/notify", Some(TOKEN), r#"{"event": "  "}"#);
+        assert!(matches!(route(&empty_event, TOKEN), Err(Rejection::BadRequest(_))));
+    }
+
+    #[test]
+    fn reads_token_and_body_from_raw_request() {
+        let raw = "POST /feed HTTP/1.1\r\nHost: localhost\r\nauthorization: Bearer s3cret-token\r\nContent-Length: 16\r\n\r\n{\"food\": \"fish\"}";
+        let req = read_request(raw.as_bytes()).unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/feed");
+        assert_eq!(req.token.as_deref(), Some(TOKEN));
+        assert_eq!(req.body, r#"{"food": "fish"}"#);
+
+        let huge = format!("POST /feed HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1);
+        assert!(matches!(read_request(huge.as_bytes()), Err(Rejection::BadRequest(_))));
+    }
+
+    #[test]
+    fn serves_over_localhost_and_rejects_bad_tokens() {
+        let listener = bind(0).unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert!(addr.ip().is_loopback());
+        std::thread::spawn(move || {
+            serve(listener, TOKEN.to_string(), |command| match command {
+                Command::GetState => Ok(r#"{"emotion":"calm"}"#.to_string()),
+                other => Err(format!("unexpected {:?}", other)),
+            })
+        });
+
+        let send = |auth: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET /state HTTP/1.1\r\n{}\r\n", auth).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let ok = send("Authorization: Bearer s3cret-token\r\n");
+        assert!(ok.starts_with("HTTP/1.1 200"), "{}", ok);
+        assert!(ok.ends_with(r#"{"emotion":"calm"}"#));
+
+        let denied = send("Authorization: Bearer nope\r\n");
+        assert!(denied.starts_with("HTTP/1.1 401"), "{}", denied);
+        assert!(send("").starts_with("HTTP/1.1 401"));
+    }
+}
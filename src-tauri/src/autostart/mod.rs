@@ -0,0 +1,147 @@
+use tauri::AppHandle;
+use tauri_plugin_autostart::ManagerExt;
+
+use crate::memory::MemoryStore;
+
+/// 开机启动偏好在 sophie_state 表里的 key
+const PREF_KEY: &str = "autostart";
+
+/// 系统开机启动登记的最小接口（测试里换成假的）
+pub trait Launcher {
+    fn is_enabled(&self) -> Result<bool, String>;
+    fn enable(&self) -> Result<(), String>;
+    fn disable(&self) -> Result<(), String>;
+}
+
+/// 真正的系统登记：Windows 注册表、macOS LaunchAgent、Linux XDG autostart
+struct SystemLauncher<'a>(&'a AppHandle);
+
+impl Launcher for SystemLauncher<'_> {
+    fn is_enabled(&self) -> Result<bool, String> {
+        self.0.autolaunch().is_enabled().map_err(|e| e.to_string())
+    }
+
+    fn enable(&self) -> Result<(), String> {
+        self.0.autolaunch().enable().map_err(|e| e.to_string())
+    }
+
+    fn disable(&self) -> Result<(), String> {
+        self.0.autolaunch().disable().map_err(|e| e.to_string())
+    }
+}
+
+/// 系统里现在是否已登记开机启动
+pub fn is_enabled(app: &AppHandle) -> Result<bool, String> {
+    query(&SystemLauncher(app))
+}
+
+/// 登记/取消开机启动，成功后保存偏好，返回系统里实际生效的状态
+pub fn set(app: &AppHandle, mem: &MemoryStore, enabled: bool) -> Result<bool, String> {
+    set_with(&SystemLauncher(app), mem, enabled)
+}
+
+/// 启动时对齐系统状态和保存的偏好（比如重装后登记丢了）；没设置过就不动
+pub fn restore(app: &AppHandle, mem: &MemoryStore) {
+    restore_with(&SystemLauncher(app), mem)
+}
+
+fn query(launcher: &impl Launcher) -> Result<bool, String> {
+    launcher
+        .is_enabled()
+        .map_err(|e| format!("Failed to query autostart: {}", e))
+}
+
+fn set_with(launcher: &impl Launcher, mem: &MemoryStore, enabled: bool) -> Result<bool, String> {
+    let result = if enabled { launcher.enable() } else { launcher.disable() };
+    result.map_err(|e| {
+        let action = if enabled { "enable" } else { "disable" };
+        format!("System refused to {} autostart: {}", action, e)
+    })?;
+    mem.save_state(PREF_KEY, if enabled { "1" } else { "0" })?;
+    query(launcher)
+}
+
+fn restore_with(launcher: &impl Launcher, mem: &MemoryStore) {
+    let Some(wanted) = mem.load_state(PREF_KEY).map(|v| v == "1") else {
+        return;
+    };
+    match query(launcher) {
+        Ok(actual) if actual == wanted => {}
+        Ok(_) => {
+            if let Err(e) = set_with(launcher, mem, wanted) {
+                log::warn!("{}", e);
+            }
+        }
+        Err(e) => log::warn!("{}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// 记下收到的调用；`refuse` 时像系统拒绝那样报错
+    #[derive(Default)]
+    struct FakeLauncher {
+        enabled: Cell<bool>,
+        refuse: bool,
+        calls: RefCell<Vec<&'static str>>,
+    }
+
+    impl Launcher for FakeLauncher {
+        fn is_enabled(&self) -> Result<bool, String> {
+            Ok(self.enabled.get())
+        }
+
+        fn enable(&self) -> Result<(), String> {
+            self.calls.borrow_mut().push("enable");
+            if self.refuse {
+                return Err("access denied".to_string());
+            }
+            self.enabled.set(true);
+            Ok(())
+        }
+
+        fn disable(&self) -> Result<(), String> {
+            self.calls.borrow_mut().push("disable");
+            self.enabled.set(false);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn toggling_calls_the_system_and_saves_the_preference() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        let launcher = FakeLauncher::default();
+        assert_eq!(set_with(&launcher, &mem, true), Ok(true));
+        assert_eq!(mem.load_state(PREF_KEY).as_deref(), Some("1"));
+        assert_eq!(set_with(&launcher, &mem, false), Ok(false));
+        assert_eq!(mem.load_state(PREF_KEY).as_deref(), Some("0"));
+        assert_eq!(*launcher.calls.borrow(), vec!["enable", "disable"]);
+    }
+
+    #[test]
+    fn refusals_are_reported_and_not_saved() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        let launcher = FakeLauncher { refuse: true, ..FakeLauncher::default() };
+        let err = set_with(&launcher, &mem, true).unwrap_err();
+        assert!(err.contains("refused to enable"), "{}", err);
+        assert_eq!(mem.load_state(PREF_KEY), None);
+    }
+
+    #[test]
+    fn restore_only_fixes_a_drifted_registration() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        let launcher = FakeLauncher::default();
+        // 没设置过偏好：不动系统
+        restore_with(&launcher, &mem);
+        assert!(launcher.calls.borrow().is_empty());
+
+        mem.save_state(PREF_KEY, "1").unwrap();
+        restore_with(&launcher, &mem);
+        restore_with(&launcher, &mem);
+        assert_eq!(*launcher.calls.borrow(), vec!["enable"]);
+        assert!(launcher.enabled.get());
+    }
+}
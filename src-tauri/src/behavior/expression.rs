@@ -0,0 +1,120 @@
+use serde::Serialize;
+
+use crate::state::SophieState;
+use crate::state::emotion::Emotion;
+
+/// 信任到这个值，心情好时尾巴竖起来
+const TAIL_UP_TRUST: f32 = 60.0;
+/// 信任低于这个值还很戒备，尾巴不会竖
+const WARY_TRUST: f32 = 30.0;
+/// 情绪强度超过这个值，无聊就变成甩尾巴
+const RESTLESS_INTENSITY: f32 = 0.6;
+
+/// 耳朵朝向
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Ears {
+    /// 竖起朝前：感兴趣、开心
+    Forward,
+    Neutral,
+    /// 往后压：烦躁、低落
+    Back,
+}
+
+/// 尾巴姿态
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Tail {
+    /// 竖起：开心又信任主人
+    Up,
+    Neutral,
+    /// 左右甩：烦躁、不耐烦
+    Swishing,
+    /// 尾尖抖动：好奇、想扑
+    Twitching,
+    /// 夹着/圈起来：低落、睡觉
+    Tucked,
+}
+
+/// 行为之外的细节表情，前端用来驱动耳朵和尾巴的小动画
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Expression {
+    pub ears: Ears,
+    pub tail: Tail,
+}
+
+/// 由情绪、强度和信任推出耳朵和尾巴
+pub fn expression(state: &SophieState) -> Expression {
+    if state.is_sleeping {
+        return Expression { ears: Ears::Neutral, tail: Tail::Tucked };
+    }
+    if state.is_groggy() {
+        return Expression { ears: Ears::Neutral, tail: Tail::Neutral };
+    }
+    let trust = state.relationship.trust;
+    let content_tail = if trust >= TAIL_UP_TRUST { Tail::Up } else { Tail::Neutral };
+    let (ears, tail) = match state.emotion {
+        Emotion::Irritated => (Ears::Back, Tail::Swishing),
+        Emotion::Down => (Ears::Back, Tail::Tucked),
+        Emotion::Happy => (Ears::Forward, content_tail),
+        Emotion::Needy => (Ears::Forward, content_tail),
+        Emotion::Calm => (Ears::Neutral, content_tail),
+        Emotion::Curious | Emotion::Playful => (Ears::Forward, Tail::Twitching),
+        Emotion::Bored if state.emotion_intensity > RESTLESS_INTENSITY => (Ears::Neutral, Tail::Swishing),
+        Emotion::Bored => (Ears::Neutral, Tail::Neutral),
+    };
+    // 还不信任主人时尾巴不会竖起来
+    let tail = if tail == Tail::Up && trust < WARY_TRUST { Tail::Neutral } else { tail };
+    Expression { ears, tail }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cat(emotion: Emotion, trust: f32) -> SophieState {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion;
+        sophie.relationship.trust = trust;
+        sophie
+    }
+
+    fn hint(ears: Ears, tail: Tail) -> Expression {
+        Expression { ears, tail }
+    }
+
+    #[test]
+    fn emotions_map_to_ears_and_tail() {
+        assert_eq!(expression(&cat(Emotion::Irritated, 80.0)), hint(Ears::Back, Tail::Swishing));
+        assert_eq!(expression(&cat(Emotion::Down, 50.0)), hint(Ears::Back, Tail::Tucked));
+        assert_eq!(expression(&cat(Emotion::Curious, 50.0)), hint(Ears::Forward, Tail::Twitching));
+        assert_eq!(expression(&cat(Emotion::Happy, 80.0)), hint(Ears::Forward, Tail::Up));
+
+        let mut sleeping = cat(Emotion::Happy, 80.0);
+        sleeping.is_sleeping = true;
+        assert_eq!(expression(&sleeping), hint(Ears::Neutral, Tail::Tucked));
+    }
+
+    #[test]
+    fn tail_goes_up_only_with_enough_trust() {
+        assert_eq!(expression(&cat(Emotion::Happy, 45.0)).tail, Tail::Neutral);
+        assert_eq!(expression(&cat(Emotion::Calm, TAIL_UP_TRUST)).tail, Tail::Up);
+        assert_eq!(expression(&cat(Emotion::Calm, 10.0)).tail, Tail::Neutral);
+    }
+
+    #[test]
+    fn intense_boredom_swishes_the_tail() {
+        let mut sophie = cat(Emotion::Bored, 50.0);
+        sophie.emotion_intensity = 0.3;
+        assert_eq!(expression(&sophie).tail, Tail::Neutral);
+        sophie.emotion_intensity = 0.9;
+        assert_eq!(expression(&sophie), hint(Ears::Neutral, Tail::Swishing));
+    }
+
+    #[test]
+    fn hints_serialize_as_lowercase_words() {
+        let json = serde_json::to_string(&hint(Ears::Back, Tail::Swishing)).unwrap();
+        assert_eq!(json, r#"{"ears":"back","tail":"swishing"}"#);
+    }
+}
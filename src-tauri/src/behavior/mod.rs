@@ -1,9 +1,15 @@
+pub mod expression;
+pub mod weights;
+
 use serde::{Deserialize, Serialize};
 use crate::state::SophieState;
+use crate::state::chase::ChaseSession;
 use crate::state::emotion::Emotion;
+use crate::state::environment::Weather;
+use crate::rng::rand_f32;
 
 /// Sophie 的行为——直接映射到前端动画状态
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Behavior {
     /// 待机（站立微动） → 前端 "idle"
@@ -21,102 +27,590 @@ pub enum Behavior {
 }
 
 impl Behavior {
+    /// 稳定的 id（与前端动画名一致）
+    pub fn id(&self) -> &'static str {
+        match self {
+            Behavior::Idle => "idle",
+            Behavior::Sleep => "sleep",
+            Behavior::Walk => "walk",
+            Behavior::Alert => "alert",
+            Behavior::Sit => "sit",
+            Behavior::Run => "run",
+        }
+    }
+
     /// 该行为是否需要翻转方向（随机朝左或朝右）
     pub fn may_change_direction(&self) -> bool {
         matches!(self, Behavior::Walk | Behavior::Run)
     }
+
+    /// 每轮广播移动的逻辑距离（屏幕宽度 = 1.0）
+    pub fn move_distance(&self) -> f32 {
+        match self {
+            Behavior::Walk => 0.03,
+            Behavior::Run => 0.08,
+            _ => 0.0,
+        }
+    }
+}
+
+/// 行为的决定因素（调试 / 透明度用）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BehaviorReason {
+    /// 体力耗尽累倒了
+    Collapsed,
+    /// 正在睡觉
+    Asleep,
+    /// 睡意 > 70
+    Sleepy,
+    /// 能量 < 20
+    Exhausted,
+    /// 饥饿 > 85
+    Hungry,
+    /// 平静但离最喜欢的位置太远
+    FarFromFavorite,
+    /// 晨昏活跃时段
+    Crepuscular,
+    /// 晴天晒太阳
+    Sunbathing,
+    /// 下雨天犯困
+    RainyDay,
+    /// 天冷找暖和的地方窝着
+    Cold,
+    /// 刚睡醒，还迷糊
+    Groggy,
+    /// 信任主人，主动走过去
+    Approach,
+    /// 非常信任，坐下露肚皮
+    ShowBelly,
+    /// 饿了，缠着主人要饭
+    Begging,
+    /// 饿了但不信任主人，自己去找吃的
+    Foraging,
+    /// 到了平时的饭点，盯着主人等开饭
+    MealTime,
+    /// 在追主人的光标
+    Chasing,
+    /// 由当前情绪决定
+    Emotion(Emotion),
+}
+
+impl BehaviorReason {
+    /// 稳定的原因代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            BehaviorReason::Collapsed => "collapsed",
+            BehaviorReason::Asleep => "asleep",
+            BehaviorReason::Sleepy => "sleepy",
+            BehaviorReason::Exhausted => "exhausted",
+            BehaviorReason::Hungry => "hungry",
+            BehaviorReason::FarFromFavorite => "far_from_favorite",
+            BehaviorReason::Crepuscular => "crepuscular",
+            BehaviorReason::Sunbathing => "sunbathing",
+            BehaviorReason::RainyDay => "rainy_day",
+            BehaviorReason::Cold => "cold",
+            BehaviorReason::Groggy => "groggy",
+            BehaviorReason::Approach => "approach",
+            BehaviorReason::ShowBelly => "show_belly",
+            BehaviorReason::Begging => "begging",
+            BehaviorReason::Foraging => "foraging",
+            BehaviorReason::MealTime => "meal_time",
+            BehaviorReason::Chasing => "chasing",
+            BehaviorReason::Emotion(_) => "emotion",
+        }
+    }
+
+    /// 人能看懂的解释
+    pub fn explain(&self, behavior: Behavior) -> String {
+        match self {
+            BehaviorReason::Collapsed => "体力耗尽累倒了，睡到恢复体力".to_string(),
+            BehaviorReason::Asleep => "正在睡觉".to_string(),
+            BehaviorReason::Sleepy => "睡意>70 所以睡觉".to_string(),
+            BehaviorReason::Exhausted => "能量<20 所以坐下休息".to_string(),
+            BehaviorReason::Hungry => "饥饿>85 所以走来走去引起注意".to_string(),
+            BehaviorReason::FarFromFavorite => "离最喜欢的位置太远，慢慢走回去".to_string(),
+            BehaviorReason::Crepuscular => "晨昏时段精神好，出来走走".to_string(),
+            BehaviorReason::Sunbathing => "晴天，坐着晒太阳".to_string(),
+            BehaviorReason::RainyDay => "下雨天，懒洋洋想睡".to_string(),
+            BehaviorReason::Cold => "天冷，窝起来取暖".to_string(),
+            BehaviorReason::Groggy => "刚睡醒，迷迷糊糊".to_string(),
+            BehaviorReason::Approach => "信任度>30，主动靠近主人".to_string(),
+            BehaviorReason::ShowBelly => "信任度>70，放心地露肚皮".to_string(),
+            BehaviorReason::Begging => "饿了，盯着主人要饭".to_string(),
+            BehaviorReason::Foraging => "饿了但不信任主人，自己去找吃的".to_string(),
+            BehaviorReason::MealTime => "到饭点了，等着开饭".to_string(),
+            BehaviorReason::Chasing => "在追光标".to_string(),
+            BehaviorReason::Emotion(emotion) => format!("{} 所以 {:?}", emotion_label(*emotion), behavior),
+        }
+    }
+}
+
+/// 走/跑时的意图，决定往哪边走
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Heading {
+    /// 朝主人（最喜欢的位置）走
+    Approach,
+    /// 躲开主人
+    Flee,
+    /// 随便走走
+    Wander,
+}
+
+/// 有明确意图时朝对应方向走的概率
+const HEADING_BIAS: f32 = 0.85;
+/// 还没有最喜欢的位置时，当作主人在屏幕中间
+const DEFAULT_TARGET_X: f32 = 0.5;
+/// 离目标这么近就不算"朝哪边"了
+const TARGET_REACHED: f32 = 0.05;
+
+/// 按行为原因和情绪判断走动的意图：饿了不信任主人、烦躁时躲开，想亲近时靠过去
+pub fn heading(state: &SophieState, reason: BehaviorReason) -> Heading {
+    match reason {
+        BehaviorReason::Foraging => Heading::Flee,
+        BehaviorReason::Approach
+        | BehaviorReason::FarFromFavorite
+        | BehaviorReason::Hungry
+        | BehaviorReason::Begging
+        | BehaviorReason::MealTime
+        | BehaviorReason::Chasing => Heading::Approach,
+        _ => match state.emotion {
+            Emotion::Irritated => Heading::Flee,
+            Emotion::Calm | Emotion::Happy | Emotion::Needy => Heading::Approach,
+            _ => Heading::Wander,
+        },
+    }
+}
+
+/// 这一步是否朝左走（`r` 是 [0, 1) 的随机数）：靠近时偏向目标，躲开时偏离目标
+///
+/// 目标一般是最喜欢的位置（主人），追光标时是光标
+pub fn choose_flip(state: &SophieState, reason: BehaviorReason, r: f32) -> bool {
+    let x = state.position.x;
+    let target = match (reason, state.chase_target()) {
+        (BehaviorReason::Chasing, Some((cursor_x, _))) => cursor_x,
+        _ => state.position.favorite_x.unwrap_or(DEFAULT_TARGET_X),
+    };
+    let target_is_left = target < x;
+    let left_chance = match heading(state, reason) {
+        Heading::Approach if (target - x).abs() < TARGET_REACHED => 0.5,
+        Heading::Approach if target_is_left => HEADING_BIAS,
+        Heading::Approach => 1.0 - HEADING_BIAS,
+        Heading::Flee if target_is_left => 1.0 - HEADING_BIAS,
+        Heading::Flee => HEADING_BIAS,
+        Heading::Wander => 0.5,
+    };
+    r < left_chance
+}
+
+/// 额外原因对应加进候选表的行为
+fn bonus_behavior(reason: BehaviorReason) -> Behavior {
+    match reason {
+        BehaviorReason::Sunbathing | BehaviorReason::ShowBelly => Behavior::Sit,
+        BehaviorReason::RainyDay | BehaviorReason::Cold => Behavior::Sleep,
+        _ => Behavior::Walk,
+    }
+}
+
+fn emotion_label(emotion: Emotion) -> &'static str {
+    match emotion {
+        Emotion::Happy => "开心",
+        Emotion::Calm => "平静",
+        Emotion::Curious => "好奇",
+        Emotion::Playful => "想玩",
+        Emotion::Bored => "无聊",
+        Emotion::Irritated => "烦躁",
+        Emotion::Down => "低落",
+        Emotion::Needy => "黏人",
+    }
+}
+
+/// 解析 LLM / 规则返回的 action：容忍大小写、引号、"action: xxx" 前缀和常见同义词
+pub fn parse_action(action: &str) -> Option<Behavior> {
+    let action = action.trim().to_lowercase();
+    // "action: approach" / "行为：approach" 只取冒号后面
+    let action = action.rsplit([':', '：']).next().unwrap_or("");
+    let word: String = action
+        .trim()
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '`' || c == '.' || c == '。')
+        .chars()
+        .map(|c| if c == '-' || c == ' ' { '_' } else { c })
+        .collect();
+    match word.as_str() {
+        "ignore" | "idle" | "stay" | "none" | "nothing" | "不理" => Some(Behavior::Idle),
+        "glance" | "alert" | "look" | "watch" | "stare" | "瞥" | "看" => Some(Behavior::Alert),
+        "approach" | "walk" | "come" | "follow" | "walk_to" | "靠近" | "走" => Some(Behavior::Walk),
+        "walk_away" | "run" | "run_away" | "leave" | "flee" | "走开" | "跑" => Some(Behavior::Run),
+        "sit" | "rest" | "lie_down" | "坐" => Some(Behavior::Sit),
+        "sleep" | "nap" | "doze" | "睡" | "睡觉" => Some(Behavior::Sleep),
+        _ => None,
+    }
 }
 
 /// 根据当前状态决策下一个行为
 pub fn decide_behavior(state: &SophieState, hour: u32) -> Behavior {
+    decide_behavior_with_reason(state, hour).0
+}
+
+/// 决策下一个行为，同时给出决定因素
+pub fn decide_behavior_with_reason(state: &SophieState, hour: u32) -> (Behavior, BehaviorReason) {
+    // 累倒了：压过饥饿等一切需求，睡到体力恢复
+    if state.collapsed || state.physiological.energy <= 0.0 {
+        return (Behavior::Sleep, BehaviorReason::Collapsed);
+    }
     // 睡眠状态优先
     if state.is_sleeping {
-        return Behavior::Sleep;
+        return (Behavior::Sleep, BehaviorReason::Asleep);
+    }
+    // 刚醒：只会坐着或发呆，过一会儿才恢复正常
+    if state.is_groggy() {
+        let behavior = if rand_f32() < 0.5 { Behavior::Sit } else { Behavior::Idle };
+        return (behavior, BehaviorReason::Groggy);
     }
 
     let phys = &state.physiological;
 
     // 生理需求：困了要睡
     if phys.sleepiness > 70.0 {
-        return Behavior::Sleep;
+        return (Behavior::Sleep, BehaviorReason::Sleepy);
     }
     // 能量太低：坐下休息
     if phys.energy < 20.0 {
-        return Behavior::Sit;
+        return (Behavior::Sit, BehaviorReason::Exhausted);
+    }
+    // 追光标：够得着就扑过去，够不着或已经到了就盯着
+    if let Some((x, y)) = state.chase_target() {
+        let pounce = ChaseSession::is_reachable(y) && (x - state.position.x).abs() >= TARGET_REACHED;
+        let behavior = if pounce { Behavior::Run } else { Behavior::Alert };
+        return (behavior, BehaviorReason::Chasing);
+    }
+    // 饿了：亲密的猫盯着主人要饭，不信任的自己去找，其他的走来走去引起注意
+    if state.is_begging() {
+        return (Behavior::Alert, BehaviorReason::Begging);
     }
-    // 饿了：走来走去引起注意
     if phys.hunger > 85.0 {
-        return Behavior::Walk;
+        if !state.relationship.will_approach() {
+            return (Behavior::Walk, BehaviorReason::Foraging);
+        }
+        return (Behavior::Walk, BehaviorReason::Hungry);
+    }
+    // 平时这个点该吃饭了：时不时盯着主人
+    if state.expects_meal() && phys.hunger > 40.0 && rand_f32() < 0.5 {
+        return (Behavior::Alert, BehaviorReason::MealTime);
     }
 
-    // 情绪驱动
-    match state.emotion {
-        Emotion::Bored => {
-            let r = rand_f32();
-            if r < 0.3 {
-                Behavior::Walk // 蹭屏幕 → 走来走去
-            } else if r < 0.5 {
-                Behavior::Run // 疯跑
-            } else {
-                Behavior::Alert // 盯着用户看
-            }
-        }
+    // 情绪驱动；额外加进来的候选行为记下原因
+    let mut bonuses: Vec<BehaviorReason> = Vec::new();
+    let table: Vec<(Behavior, f32)> = match state.emotion {
+        Emotion::Bored => weights::BORED.to_vec(),
+        Emotion::Needy => weights::NEEDY.to_vec(),
         Emotion::Happy => {
-            let r = rand_f32();
-            if state.relationship.intimacy > 50.0 && r < 0.3 {
-                Behavior::Walk // 靠近用户
-            } else if r < 0.5 {
-                Behavior::Idle // 放松待机
+            if state.position.distance_to_favorite() > 0.15 {
+                weights::HAPPY_FAR_FROM_FAVORITE.to_vec()
+            } else if state.relationship.intimacy > 50.0 && state.relationship.will_approach() {
+                weights::HAPPY_INTIMATE.to_vec()
             } else {
-                Behavior::Sit // 舒服地坐着
+                weights::HAPPY.to_vec()
             }
         }
-        Emotion::Irritated => Behavior::Sit, // 躲到角落坐着不理人
-        Emotion::Down => Behavior::Sleep,     // 蜷缩休息
-        Emotion::Curious => Behavior::Alert,  // 警觉观察
+        Emotion::Irritated => vec![(Behavior::Sit, 1.0)], // 躲到角落坐着不理人
+        Emotion::Down => vec![(Behavior::Sleep, 1.0)],     // 蜷缩休息
+        Emotion::Curious => vec![(Behavior::Alert, 1.0)],  // 警觉观察
         Emotion::Playful => {
-            if rand_f32() < 0.5 {
-                Behavior::Run // 疯跑
-            } else {
-                Behavior::Walk
-            }
+            // 越兴奋越可能疯跑
+            let run = state.emotion_intensity.clamp(0.0, 1.0);
+            vec![(Behavior::Run, run), (Behavior::Walk, 1.0 - run)]
         }
         Emotion::Calm => {
+            let mut table = weights::DAILY.to_vec();
+            // 离最喜欢的位置太远：慢慢走回去
+            if state.position.distance_to_favorite() > 0.15 {
+                table = weights::with_chance(&table, Behavior::Walk, 0.3);
+                bonuses.push(BehaviorReason::FarFromFavorite);
+            }
             // 晨昏活跃
-            if (5..8).contains(&hour) || (17..20).contains(&hour) {
-                if phys.energy > 60.0 && rand_f32() < 0.4 {
-                    return Behavior::Walk;
+            let crepuscular = (5..8).contains(&hour) || (17..20).contains(&hour);
+            if crepuscular && phys.energy > 60.0 {
+                table = weights::with_chance(&table, Behavior::Walk, 0.4);
+                bonuses.push(BehaviorReason::Crepuscular);
+            }
+            // 天气：晴天晒太阳，下雨犯困，冷了窝着
+            let env = &state.environment;
+            if env.weather == Weather::Sunny && (9..17).contains(&hour) {
+                table = weights::with_chance(&table, Behavior::Sit, 0.3);
+                bonuses.push(BehaviorReason::Sunbathing);
+            } else if env.weather == Weather::Rainy {
+                table = weights::with_chance(&table, Behavior::Sleep, 0.3);
+                bonuses.push(BehaviorReason::RainyDay);
+            } else if env.is_cold() {
+                table = weights::with_chance(&table, Behavior::Sleep, 0.25);
+                bonuses.push(BehaviorReason::Cold);
+            }
+            table
+        }
+    };
+
+    // 信任决定肢体语言：信任就靠近、露肚皮；不信任时再开心也保持距离
+    let rel = &state.relationship;
+    let table = if matches!(state.emotion, Emotion::Happy | Emotion::Calm) {
+        let mut table = table;
+        if rel.will_approach() {
+            table = weights::with_chance(&table, Behavior::Walk, 0.15);
+            bonuses.push(BehaviorReason::Approach);
+        }
+        if rel.will_show_belly() {
+            table = weights::with_chance(&table, Behavior::Sit, 0.15);
+            bonuses.push(BehaviorReason::ShowBelly);
+        }
+        table
+    } else {
+        table
+    };
+
+    // 性格：爱玩的多跑，怕生的多观察
+    let traits = &state.personality;
+    let table = weights::scale(&table, Behavior::Run, traits.run_bias());
+    let table = weights::scale(&table, Behavior::Alert, traits.alert_bias());
+
+    let behavior = weights::sample(&table, rand_f32());
+    let reason = bonuses
+        .into_iter()
+        .rev()
+        .find(|reason| bonus_behavior(*reason) == behavior)
+        .unwrap_or(BehaviorReason::Emotion(state.emotion));
+    (behavior, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calm() -> SophieState {
+        let mut sophie = SophieState::new();
+        sophie.emotion = Emotion::Calm;
+        sophie.physiological.energy = 80.0;
+        sophie.physiological.hunger = 20.0;
+        sophie.physiological.sleepiness = 10.0;
+        sophie
+    }
+
+    #[test]
+    fn needs_explain_themselves() {
+        let mut sophie = calm();
+        sophie.is_sleeping = true;
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Sleep, BehaviorReason::Asleep));
+
+        let mut sophie = calm();
+        sophie.physiological.sleepiness = 80.0;
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Sleep, BehaviorReason::Sleepy));
+
+        let mut sophie = calm();
+        sophie.physiological.energy = 10.0;
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Sit, BehaviorReason::Exhausted));
+
+        let mut sophie = calm();
+        sophie.physiological.hunger = 90.0;
+        // 信任主人但还不够亲密：不会要饭，也不会自己跑去找吃的
+        sophie.relationship.trust = 40.0;
+        let (behavior, reason) = decide_behavior_with_reason(&sophie, 12);
+        assert_eq!((behavior, reason), (Behavior::Walk, BehaviorReason::Hungry));
+        assert_eq!(reason.code(), "hungry");
+        assert_eq!(reason.explain(behavior), "饥饿>85 所以走来走去引起注意");
+    }
+
+    #[test]
+    fn mood_driven_behavior_names_the_emotion() {
+        let mut sophie = calm();
+        sophie.emotion = Emotion::Irritated;
+        let (behavior, reason) = decide_behavior_with_reason(&sophie, 12);
+        assert_eq!((behavior, reason), (Behavior::Sit, BehaviorReason::Emotion(Emotion::Irritated)));
+        assert_eq!(reason.code(), "emotion");
+        assert_eq!(reason.explain(behavior), "烦躁 所以 Sit");
+    }
+
+    #[test]
+    fn extra_walks_are_credited_to_their_cause() {
+        let mut far = calm();
+        far.position.x = 0.9;
+        far.position.favorite_x = Some(0.1);
+        let dusk = calm();
+
+        for (sophie, hour, cause) in [
+            (&far, 12, BehaviorReason::FarFromFavorite),
+            (&dusk, 18, BehaviorReason::Crepuscular),
+        ] {
+            let mut walked = false;
+            for _ in 0..200 {
+                let (behavior, reason) = decide_behavior_with_reason(sophie, hour);
+                if behavior == Behavior::Walk {
+                    walked = true;
+                    assert_eq!(reason, cause);
+                } else {
+                    assert_eq!(reason, BehaviorReason::Emotion(Emotion::Calm));
                 }
             }
-            // 默认日常行为
-            random_daily_behavior()
+            assert!(walked, "{:?} never walked", cause);
         }
     }
-}
 
-fn random_daily_behavior() -> Behavior {
-    let r = rand_f32();
-    if r < 0.35 {
-        Behavior::Idle
-    } else if r < 0.55 {
-        Behavior::Sit
-    } else if r < 0.7 {
-        Behavior::Walk
-    } else if r < 0.85 {
-        Behavior::Alert
-    } else {
-        Behavior::Idle
+    #[test]
+    fn weather_bonuses_are_credited_to_the_weather() {
+        use crate::state::environment::{Season, Weather};
+
+        let mut sunny = calm();
+        sunny.environment.weather = Weather::Sunny;
+        let mut rainy = calm();
+        rainy.environment.weather = Weather::Rainy;
+        let mut wintry = calm();
+        wintry.environment.season = Season::Winter;
+
+        for (sophie, behavior, cause) in [
+            (&sunny, Behavior::Sit, BehaviorReason::Sunbathing),
+            (&rainy, Behavior::Sleep, BehaviorReason::RainyDay),
+            (&wintry, Behavior::Sleep, BehaviorReason::Cold),
+        ] {
+            let mut seen = false;
+            for _ in 0..200 {
+                let (picked, reason) = decide_behavior_with_reason(sophie, 12);
+                if picked == behavior {
+                    seen = true;
+                    assert_eq!(reason, cause);
+                }
+            }
+            assert!(seen, "{:?} never happened", cause);
+        }
     }
-}
 
-/// 简单伪随机
-fn rand_f32() -> f32 {
-    use std::time::SystemTime;
-    let t = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap_or_default()
-        .subsec_nanos();
-    // 加入更多变化
-    let mixed = t.wrapping_mul(1103515245).wrapping_add(12345);
-    (mixed % 10000) as f32 / 10000.0
+    #[test]
+    fn freshly_woken_cat_only_sits_or_idles() {
+        let mut sophie = calm();
+        sophie.is_sleeping = true;
+        sophie.wake_up();
+        assert!(sophie.is_groggy());
+        for _ in 0..20 {
+            let (behavior, reason) = decide_behavior_with_reason(&sophie, 12);
+            assert!(matches!(behavior, Behavior::Sit | Behavior::Idle));
+            assert_eq!(reason, BehaviorReason::Groggy);
+        }
+
+        // 迷糊期过了就恢复正常
+        sophie.woke_at_ts = sophie.woke_at_ts.map(|ts| ts - crate::state::GROGGY_SECS);
+        assert!(!sophie.is_groggy());
+        assert_ne!(decide_behavior_with_reason(&sophie, 12).1, BehaviorReason::Groggy);
+    }
+
+    #[test]
+    fn trust_unlocks_approach_and_belly_up() {
+        let mut wary = calm();
+        wary.emotion = Emotion::Happy;
+        wary.relationship.trust = 10.0;
+        let mut trusting = wary.clone();
+        trusting.relationship.trust = 90.0;
+
+        let reasons = |sophie: &SophieState| {
+            (0..400).map(|_| decide_behavior_with_reason(sophie, 12).1).collect::<Vec<_>>()
+        };
+        let wary_reasons = reasons(&wary);
+        assert!(!wary_reasons.contains(&BehaviorReason::Approach));
+        assert!(!wary_reasons.contains(&BehaviorReason::ShowBelly));
+
+        let trusting_reasons = reasons(&trusting);
+        assert!(trusting_reasons.contains(&BehaviorReason::Approach));
+        assert!(trusting_reasons.contains(&BehaviorReason::ShowBelly));
+    }
+
+    #[test]
+    fn hungry_bonded_cats_beg_and_wary_ones_forage() {
+        let mut bonded = calm();
+        bonded.physiological.hunger = 80.0;
+        bonded.relationship.intimacy = 90.0;
+        bonded.relationship.trust = 60.0;
+        assert_eq!(decide_behavior_with_reason(&bonded, 12), (Behavior::Alert, BehaviorReason::Begging));
+
+        let mut wary = bonded.clone();
+        wary.relationship.intimacy = 10.0;
+        wary.relationship.trust = 10.0;
+        // 还没饿到要走来走去的程度
+        assert_ne!(decide_behavior_with_reason(&wary, 12).1, BehaviorReason::Foraging);
+        wary.physiological.hunger = 90.0;
+        assert_eq!(decide_behavior_with_reason(&wary, 12), (Behavior::Walk, BehaviorReason::Foraging));
+    }
+
+    #[test]
+    fn actions_are_parsed_leniently() {
+        assert_eq!(parse_action("action: approach"), Some(Behavior::Walk));
+        assert_eq!(parse_action("  \"Walk-Away\". "), Some(Behavior::Run));
+        assert_eq!(parse_action("行为：sleep"), Some(Behavior::Sleep));
+        assert_eq!(parse_action("`stare`"), Some(Behavior::Alert));
+        assert_eq!(parse_action("lie down"), Some(Behavior::Sit));
+        assert_eq!(parse_action("Nothing"), Some(Behavior::Idle));
+        assert_eq!(parse_action("睡觉。"), Some(Behavior::Sleep));
+        assert_eq!(parse_action(""), None);
+        assert_eq!(parse_action("do a backflip"), None);
+    }
+
+    /// 1000 次里朝左走的次数
+    fn left_turns(sophie: &SophieState, reason: BehaviorReason) -> usize {
+        (0..1000).filter(|i| choose_flip(sophie, reason, *i as f32 / 1000.0)).count()
+    }
+
+    #[test]
+    fn approaching_heads_for_her_spot_and_fleeing_turns_away() {
+        let mut sophie = calm();
+        sophie.position.x = 0.8;
+        sophie.position.favorite_x = Some(0.2);
+        assert_eq!(heading(&sophie, BehaviorReason::Approach), Heading::Approach);
+        assert_eq!(left_turns(&sophie, BehaviorReason::Approach), 850);
+        assert_eq!(heading(&sophie, BehaviorReason::Foraging), Heading::Flee);
+        assert_eq!(left_turns(&sophie, BehaviorReason::Foraging), 150);
+
+        // 最喜欢的位置在右边时反过来
+        sophie.position.x = 0.1;
+        assert_eq!(left_turns(&sophie, BehaviorReason::Approach), 150);
+        // 已经到了就随便走
+        sophie.position.x = 0.22;
+        assert_eq!(left_turns(&sophie, BehaviorReason::Approach), 500);
+    }
+
+    #[test]
+    fn mood_sets_the_heading_when_the_reason_does_not() {
+        let mut sophie = calm();
+        sophie.position.x = 0.9;
+        // 没有最喜欢的位置：当主人在屏幕中间
+        assert_eq!(left_turns(&sophie, BehaviorReason::Emotion(Emotion::Calm)), 850);
+        sophie.emotion = Emotion::Irritated;
+        assert_eq!(heading(&sophie, BehaviorReason::Emotion(Emotion::Irritated)), Heading::Flee);
+        assert_eq!(left_turns(&sophie, BehaviorReason::Emotion(Emotion::Irritated)), 150);
+        sophie.emotion = Emotion::Curious;
+        assert_eq!(heading(&sophie, BehaviorReason::Emotion(Emotion::Curious)), Heading::Wander);
+        assert_eq!(left_turns(&sophie, BehaviorReason::Emotion(Emotion::Curious)), 500);
+    }
+
+    #[test]
+    fn a_low_cursor_gets_pounced_and_a_high_one_watched() {
+        let mut sophie = calm();
+        sophie.emotion = Emotion::Playful;
+        sophie.position.x = 0.8;
+        assert!(sophie.chase_cursor(0.2, 0.9));
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Run, BehaviorReason::Chasing));
+        // 朝光标跑，而不是朝最喜欢的位置
+        sophie.position.favorite_x = Some(0.95);
+        assert!(choose_flip(&sophie, BehaviorReason::Chasing, 0.5));
+
+        assert!(sophie.chase_cursor(0.2, 0.1));
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Alert, BehaviorReason::Chasing));
+    }
+
+    #[test]
+    fn running_out_of_energy_beats_hunger() {
+        let mut sophie = calm();
+        sophie.physiological.energy = 0.0;
+        sophie.physiological.hunger = 95.0;
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Sleep, BehaviorReason::Collapsed));
+
+        // 体力回了一些但还没缓过来，照样睡
+        sophie.physiological.energy = 15.0;
+        sophie.collapsed = true;
+        sophie.is_sleeping = true;
+        assert_eq!(decide_behavior_with_reason(&sophie, 12), (Behavior::Sleep, BehaviorReason::Collapsed));
+    }
 }
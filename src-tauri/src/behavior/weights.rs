@@ -0,0 +1,147 @@
+use super::Behavior;
+
+/// 带权重的候选行为（权重不必归一化）
+pub type WeightTable = [(Behavior, f32)];
+
+/// 无聊：蹭屏幕走来走去 / 疯跑 / 盯着用户看
+pub const BORED: &WeightTable = &[
+    (Behavior::Walk, 0.3),
+    (Behavior::Run, 0.2),
+    (Behavior::Alert, 0.5),
+];
+
+/// 黏人：绕着用户走来走去 / 盯着用户看
+pub const NEEDY: &WeightTable = &[
+    (Behavior::Walk, 0.5),
+    (Behavior::Alert, 0.4),
+    (Behavior::Sit, 0.1),
+];
+
+/// 开心：放松待机 / 舒服地坐着
+pub const HAPPY: &WeightTable = &[
+    (Behavior::Idle, 0.5),
+    (Behavior::Sit, 0.5),
+];
+
+/// 开心且亲密：会主动靠近用户
+pub const HAPPY_INTIMATE: &WeightTable = &[
+    (Behavior::Walk, 0.3),
+    (Behavior::Idle, 0.2),
+    (Behavior::Sit, 0.5),
+];
+
+/// 开心但离最喜欢的位置太远：走回去
+pub const HAPPY_FAR_FROM_FAVORITE: &WeightTable = &[
+    (Behavior::Walk, 0.4),
+    (Behavior::Idle, 0.1),
+    (Behavior::Sit, 0.5),
+];
+
+/// 平静时的日常行为
+pub const DAILY: &WeightTable = &[
+    (Behavior::Idle, 0.5),
+    (Behavior::Sit, 0.2),
+    (Behavior::Walk, 0.15),
+    (Behavior::Alert, 0.15),
+];
+
+/// 以 `chance` 的概率直接选 `behavior`，否则按 `table` 抽
+pub fn with_chance(table: &WeightTable, behavior: Behavior, chance: f32) -> Vec<(Behavior, f32)> {
+    let total: f32 = table.iter().map(|(_, w)| w).sum();
+    let rest = if total > 0.0 { (1.0 - chance) / total } else { 0.0 };
+    let mut out: Vec<(Behavior, f32)> = table.iter().map(|&(b, w)| (b, w * rest)).collect();
+    match out.iter_mut().find(|(b, _)| *b == behavior) {
+        Some((_, w)) => *w += chance,
+        None => out.push((behavior, chance)),
+    }
+    out
+}
+
+/// 把 `behavior` 的权重乘以 `factor`（不在表里则不变）
+pub fn scale(table: &WeightTable, behavior: Behavior, factor: f32) -> Vec<(Behavior, f32)> {
+    table
+        .iter()
+        .map(|&(b, w)| if b == behavior { (b, w * factor) } else { (b, w) })
+        .collect()
+}
+
+/// 按权重抽取一个行为；`r` 是 [0, 1) 的随机数
+pub fn sample(table: &WeightTable, r: f32) -> Behavior {
+    let total: f32 = table.iter().map(|(_, w)| w.max(0.0)).sum();
+    let mut target = r * total;
+    for &(behavior, weight) in table {
+        let weight = weight.max(0.0);
+        if target < weight {
+            return behavior;
+        }
+        target -= weight;
+    }
+    table.last().map(|&(b, _)| b).unwrap_or(Behavior::Idle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// 用固定种子抽很多次，每个行为出现的比例
+    fn frequencies(table: &WeightTable, draws: usize) -> Vec<(Behavior, f32)> {
+        let mut rng = Rng::new(7);
+        let mut counts = vec![0usize; table.len()];
+        for _ in 0..draws {
+            let picked = sample(table, rng.next_f32());
+            let i = table.iter().position(|&(b, _)| b == picked).unwrap();
+            counts[i] += 1;
+        }
+        table
+            .iter()
+            .zip(counts)
+            .map(|(&(b, _), n)| (b, n as f32 / draws as f32))
+            .collect()
+    }
+
+    #[test]
+    fn sampling_converges_to_table_weights() {
+        for table in [BORED, NEEDY, HAPPY, HAPPY_INTIMATE, HAPPY_FAR_FROM_FAVORITE, DAILY] {
+            let total: f32 = table.iter().map(|(_, w)| w).sum();
+            for ((behavior, weight), (_, freq)) in table.iter().zip(frequencies(table, 100_000)) {
+                let expected = weight / total;
+                assert!(
+                    (freq - expected).abs() < 0.01,
+                    "{:?}: drew {:.3}, table says {:.3}",
+                    behavior,
+                    freq,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sample_ends_of_range() {
+        assert_eq!(sample(DAILY, 0.0), Behavior::Idle);
+        assert_eq!(sample(DAILY, 0.999_999), Behavior::Alert);
+        // 权重为 0 或负的永远抽不到
+        let table = [(Behavior::Run, 0.0), (Behavior::Sit, -1.0), (Behavior::Walk, 1.0)];
+        assert_eq!(sample(&table, 0.0), Behavior::Walk);
+    }
+
+    #[test]
+    fn with_chance_keeps_total_weight_at_one() {
+        let table = with_chance(DAILY, Behavior::Walk, 0.3);
+        let total: f32 = table.iter().map(|(_, w)| w).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+        let walk = table.iter().find(|(b, _)| *b == Behavior::Walk).unwrap().1;
+        assert!((walk - (0.3 + 0.7 * 0.15)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn scale_only_touches_the_named_behavior() {
+        let scaled = scale(BORED, Behavior::Run, 2.0);
+        for (&(behavior, weight), &(_, new_weight)) in BORED.iter().zip(scaled.iter()) {
+            let expected = if behavior == Behavior::Run { weight * 2.0 } else { weight };
+            assert_eq!(new_weight, expected);
+        }
+        assert_eq!(scale(&[(Behavior::Sit, 1.0)], Behavior::Run, 3.0), vec![(Behavior::Sit, 1.0)]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::memory::MemoryStore;
+use crate::state::emotion::Emotion;
+
+/// 各纪念日上次触发的日期在 sophie_state 表里的 key
+const FIRED_KEY: &str = "special_days_fired";
+
+/// 哪天算纪念日
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateRule {
+    /// 她的生日：出生那天的月日（满一年以后）；2 月 29 日出生的平年在 2 月 28 日过
+    Birthday,
+    /// 每年固定的月日（本地日期）
+    Annual { month: u32, day: u32 },
+}
+
+impl DateRule {
+    /// `today` / `born_day` 是本地日期编号（见 `state::local_day`）
+    pub fn matches(&self, today: i64, born_day: i64) -> bool {
+        let (year, month, day) = civil_from_days(today);
+        match *self {
+            DateRule::Birthday => {
+                if today <= born_day {
+                    return false;
+                }
+                let (_, born_month, born_day_of_month) = civil_from_days(born_day);
+                if (born_month, born_day_of_month) == (2, 29) && !is_leap_year(year) {
+                    return (month, day) == (2, 28);
+                }
+                (month, day) == (born_month, born_day_of_month)
+            }
+            DateRule::Annual { month: m, day: d } => (month, day) == (m, d),
+        }
+    }
+}
+
+/// 纪念日：哪天，以及那天她会怎样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecialDay {
+    /// 唯一 id，和内置的重名时覆盖内置的
+    pub id: String,
+    pub rule: DateRule,
+    /// 那天变成的情绪
+    #[serde(default)]
+    pub emotion: Option<Emotion>,
+    /// 那天说的话（生日没写时用内置的短语）
+    #[serde(default)]
+    pub thought: Option<String>,
+}
+
+/// 内置的纪念日：只有生日
+pub fn builtin() -> Vec<SpecialDay> {
+    vec![SpecialDay {
+        id: "birthday".to_string(),
+        rule: DateRule::Birthday,
+        emotion: Some(Emotion::Happy),
+        thought: None,
+    }]
+}
+
+/// 纪念日表和各自上次触发的日期：每次（每个日期）最多触发一次
+pub struct Calendar {
+    days: Vec<SpecialDay>,
+    /// id → 上次触发的本地日期编号
+    fired: HashMap<String, i64>,
+}
+
+impl Calendar {
+    /// 内置纪念日 + `path` 里主人自己加的，格式如
+    /// `[{"id": "new_year", "rule": {"annual": {"month": 1, "day": 1}}, "emotion": "Happy", "thought": "新年！"}]`；
+    /// 文件不存在或格式不对就只用内置的。触发记录从数据库读
+    pub fn load(path: &Path, mem: &MemoryStore) -> Self {
+        let mut days = builtin();
+        if let Ok(text) = std::fs::read_to_string(path) {
+            match serde_json::from_str::<Vec<SpecialDay>>(&text) {
+                Ok(custom) => {
+                    for day in custom {
+                        days.retain(|d| d.id != day.id);
+                        days.push(day);
+                    }
+                }
+                Err(e) => log::warn!("Invalid special days file {:?}: {}, using built-in ones", path, e),
+            }
+        }
+        let fired = mem
+            .load_state(FIRED_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { days, fired }
+    }
+
+    pub fn save(&self, mem: &MemoryStore) -> Result<(), String> {
+        let json = serde_json::to_string(&self.fired).map_err(|e| format!("Serialize error: {}", e))?;
+        mem.save_state(FIRED_KEY, &json)
+    }
+
+    /// 今天该触发而还没触发过的纪念日，取出后就记为已触发
+    pub fn take_due(&mut self, today: i64, born_day: i64) -> Vec<SpecialDay> {
+        let due: Vec<SpecialDay> = self
+            .days
+            .iter()
+            .filter(|d| self.fired.get(&d.id) != Some(&today) && d.rule.matches(today, born_day))
+            .cloned()
+            .collect();
+        for day in &due {
+            self.fired.insert(day.id.clone(), today);
+        }
+        due
+    }
+}
+
+/// 日期编号（1970-01-01 起的天数）转成公历年月日
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 公历年月日转日期编号（测试里好写日期）
+    fn day(year: i64, month: u32, day: u32) -> i64 {
+        (0..=(year - 1970 + 1) * 366)
+            .find(|&d| civil_from_days(d) == (year, month, day))
+            .expect("valid date")
+    }
+
+    #[test]
+    fn dates_convert_back_and_forth() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+    }
+
+    #[test]
+    fn birthday_needs_a_full_year_and_matches_month_and_day() {
+        let born = day(2023, 6, 15);
+        assert!(!DateRule::Birthday.matches(born, born));
+        assert!(DateRule::Birthday.matches(day(2024, 6, 15), born));
+        assert!(!DateRule::Birthday.matches(day(2024, 6, 16), born));
+    }
+
+    #[test]
+    fn leap_day_birthday_falls_back_to_feb_28_in_common_years() {
+        let born = day(2024, 2, 29);
+        assert!(DateRule::Birthday.matches(day(2025, 2, 28), born));
+        assert!(!DateRule::Birthday.matches(day(2025, 3, 1), born));
+        // 闰年还是在 2 月 29 日过
+        assert!(DateRule::Birthday.matches(day(2028, 2, 29), born));
+        assert!(!DateRule::Birthday.matches(day(2028, 2, 28), born));
+    }
+
+    #[test]
+    fn each_special_day_fires_once_per_day_and_survives_restarts() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        let path = std::env::temp_dir().join(format!("sophie-special-days-{}.json", std::process::id()));
+        std::fs::write(&path, r#"[{"id": "new_year", "rule": {"annual": {"month": 1, "day": 1}}, "thought": "新年！"}]"#).unwrap();
+        let born = day(2020, 1, 1);
+        let today = day(2025, 1, 1);
+
+        let mut calendar = Calendar::load(&path, &mem);
+        let ids: Vec<String> = calendar.take_due(today, born).into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["birthday", "new_year"]);
+        assert!(calendar.take_due(today, born).is_empty());
+        calendar.save(&mem).unwrap();
+
+        // 重启后同一天不再触发，第二年照常
+        let mut calendar = Calendar::load(&path, &mem);
+        assert!(calendar.take_due(today, born).is_empty());
+        assert_eq!(calendar.take_due(day(2026, 1, 1), born).len(), 2);
+        let _ = std::fs::remove_file(&path);
+    }
+}
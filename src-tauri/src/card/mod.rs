@@ -0,0 +1,127 @@
+use crate::i18n::Lang;
+use crate::state::SophieState;
+use crate::state::emotion::Emotion;
+
+/// 卡片上的名字
+const NAME: &str = "Sophie";
+/// 签名想法最多几个字，太长就截断
+const MAX_THOUGHT_CHARS: usize = 30;
+
+/// 可以分享的状态卡片（纯文本）：名字、年龄、心情、最突出的关系值、一句最近的想法
+pub fn render(sophie: &SophieState, now: u64, thought: Option<&str>, lang: Lang) -> String {
+    let days = sophie.stats.days_alive(now);
+    let mood = mood_label(sophie, lang);
+    let (stat, value) = top_relationship(sophie, lang);
+    let thought = thought
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| t.chars().take(MAX_THOUGHT_CHARS).collect::<String>())
+        .unwrap_or_else(|| "...".to_string());
+
+    let lines = match lang {
+        Lang::Zh => [
+            format!("🐾 {} · 第 {} 天", NAME, days),
+            format!("心情：{}", mood),
+            format!("{}：{:.0}/100", stat, value),
+            format!("「{}」", thought),
+        ],
+        Lang::En => [
+            format!("🐾 {} · day {}", NAME, days),
+            format!("Mood: {}", mood),
+            format!("{}: {:.0}/100", stat, value),
+            format!("\"{}\"", thought),
+        ],
+    };
+    lines.join("\n")
+}
+
+/// 心情：睡着时就写在睡觉
+fn mood_label(sophie: &SophieState, lang: Lang) -> &'static str {
+    if sophie.is_sleeping {
+        return match lang {
+            Lang::Zh => "睡着了",
+            Lang::En => "asleep",
+        };
+    }
+    match (sophie.emotion, lang) {
+        (Emotion::Happy, Lang::Zh) => "开心",
+        (Emotion::Calm, Lang::Zh) => "平静",
+        (Emotion::Curious, Lang::Zh) => "好奇",
+        (Emotion::Playful, Lang::Zh) => "想玩",
+        (Emotion::Bored, Lang::Zh) => "无聊",
+        (Emotion::Irritated, Lang::Zh) => "烦躁",
+        (Emotion::Down, Lang::Zh) => "低落",
+        (Emotion::Needy, Lang::Zh) => "黏人",
+        (Emotion::Happy, Lang::En) => "happy",
+        (Emotion::Calm, Lang::En) => "calm",
+        (Emotion::Curious, Lang::En) => "curious",
+        (Emotion::Playful, Lang::En) => "playful",
+        (Emotion::Bored, Lang::En) => "bored",
+        (Emotion::Irritated, Lang::En) => "irritated",
+        (Emotion::Down, Lang::En) => "down",
+        (Emotion::Needy, Lang::En) => "needy",
+    }
+}
+
+/// 信任 / 亲密 / 了解里最高的一项（相同时按这个顺序取前面的）
+fn top_relationship(sophie: &SophieState, lang: Lang) -> (&'static str, f32) {
+    let rel = &sophie.relationship;
+    let names = match lang {
+        Lang::Zh => ["信任", "亲密", "了解"],
+        Lang::En => ["Trust", "Intimacy", "Understanding"],
+    };
+    let stats = [(names[0], rel.trust), (names[1], rel.intimacy), (names[2], rel.understanding)];
+    stats
+        .into_iter()
+        .fold(stats[0], |best, next| if next.1 > best.1 { next } else { best })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sophie() -> SophieState {
+        let mut sophie = SophieState::new();
+        sophie.stats.born_ts = 1_000_000;
+        sophie.emotion = Emotion::Happy;
+        sophie.relationship.trust = 40.0;
+        sophie.relationship.intimacy = 72.4;
+        sophie.relationship.understanding = 10.0;
+        sophie
+    }
+
+    #[test]
+    fn cards_show_age_mood_top_stat_and_thought() {
+        let now = 1_000_000 + 2 * 86400;
+        assert_eq!(
+            render(&sophie(), now, Some(" 想晒太阳 "), Lang::Zh),
+            "🐾 Sophie · 第 3 天\n心情：开心\n亲密：72/100\n「想晒太阳」"
+        );
+        assert_eq!(
+            render(&sophie(), now, None, Lang::En),
+            "🐾 Sophie · day 3\nMood: happy\nIntimacy: 72/100\n\"...\""
+        );
+    }
+
+    #[test]
+    fn long_thoughts_are_cut_and_sleep_overrides_mood() {
+        let mut sophie = sophie();
+        sophie.is_sleeping = true;
+        let long = "喵".repeat(MAX_THOUGHT_CHARS + 10);
+        let card = render(&sophie, 1_000_000, Some(&long), Lang::Zh);
+        assert!(card.contains("心情：睡着了"));
+        assert!(card.ends_with(&format!("「{}」", "喵".repeat(MAX_THOUGHT_CHARS))));
+        assert!(render(&sophie, 1_000_000, Some("  "), Lang::Zh).ends_with("「...」"));
+    }
+
+    #[test]
+    fn ties_go_to_trust_first() {
+        let mut sophie = sophie();
+        sophie.relationship.trust = 50.0;
+        sophie.relationship.intimacy = 50.0;
+        sophie.relationship.understanding = 50.0;
+        assert_eq!(top_relationship(&sophie, Lang::En), ("Trust", 50.0));
+        sophie.relationship.understanding = 60.0;
+        assert_eq!(top_relationship(&sophie, Lang::Zh), ("了解", 60.0));
+    }
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 时间来源：正式运行读系统时间，模拟/调试时换成可以手动拨的时钟
+pub trait Clock {
+    /// Unix 时间戳（秒）
+    fn now_unix(&self) -> u64;
+    /// 当前这一秒内的纳秒数（0-999_999_999）
+    fn now_nanos(&self) -> u32;
+}
+
+/// 系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> u64 {
+        since_epoch().as_secs()
+    }
+
+    fn now_nanos(&self) -> u32 {
+        since_epoch().subsec_nanos()
+    }
+}
+
+fn since_epoch() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// 手动拨的时钟：只有调用 `advance`/`set` 时才走
+#[cfg(any(test, feature = "sim"))]
+pub struct MockClock {
+    now: std::cell::Cell<u64>,
+}
+
+#[cfg(any(test, feature = "sim"))]
+impl MockClock {
+    pub fn new(start: u64) -> Self {
+        Self { now: std::cell::Cell::new(start) }
+    }
+
+    /// 往前拨 `secs` 秒
+    pub fn advance(&self, secs: u64) {
+        self.now.set(self.now.get().saturating_add(secs));
+    }
+
+    /// 直接拨到 `ts`（可以往回拨，模拟系统时间被改）
+    #[cfg(test)]
+    pub fn set(&self, ts: u64) {
+        self.now.set(ts);
+    }
+}
+
+#[cfg(any(test, feature = "sim"))]
+impl Clock for MockClock {
+    fn now_unix(&self) -> u64 {
+        self.now.get()
+    }
+
+    fn now_nanos(&self) -> u32 {
+        0
+    }
+}
+
+thread_local! {
+    /// 当前线程临时换上的时钟（None = 系统时钟），见 `with_clock`
+    static CURRENT: RefCell<Option<Rc<dyn Clock>>> = const { RefCell::new(None) };
+}
+
+/// 当前线程的时钟读到的 Unix 时间戳（秒）
+pub fn now_unix() -> u64 {
+    CURRENT
+        .with(|current| current.borrow().as_ref().map(|clock| clock.now_unix()))
+        .unwrap_or_else(|| SystemClock.now_unix())
+}
+
+/// 当前线程的时钟读到的亚秒纳秒数
+pub fn now_nanos() -> u32 {
+    CURRENT
+        .with(|current| current.borrow().as_ref().map(|clock| clock.now_nanos()))
+        .unwrap_or_else(|| SystemClock.now_nanos())
+}
+
+/// 在当前线程上换用 `clock` 跑 `f`，结束后（包括 panic）恢复原来的时钟
+///
+/// 只影响当前线程，后台循环和其他命令照常读系统时间
+#[cfg(any(test, feature = "sim"))]
+pub fn with_clock<R>(clock: Rc<dyn Clock>, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(clock))));
+    f()
+}
+
+#[cfg(any(test, feature = "sim"))]
+struct Restore(Option<Rc<dyn Clock>>);
+
+#[cfg(any(test, feature = "sim"))]
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
@@ -0,0 +1,494 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use crate::i18n::Lang;
+use crate::state::emotion::Emotion;
+
+/// 没有配置、也读不到系统时区时的偏移：UTC+8
+const DEFAULT_UTC_OFFSET_SECS: i64 = 8 * 3600;
+/// 合法的时区偏移范围（±14 小时）
+const MAX_UTC_OFFSET_SECS: i64 = 14 * 3600;
+
+/// 当前使用的时区偏移，启动时由 `set_utc_offset` 设定
+static UTC_OFFSET_SECS: AtomicI64 = AtomicI64::new(DEFAULT_UTC_OFFSET_SECS);
+
+/// 本地时区相对 UTC 的偏移（秒）：本地日期、钟点都按它算
+pub fn utc_offset_secs() -> i64 {
+    UTC_OFFSET_SECS.load(Ordering::Relaxed)
+}
+
+/// 启动时设定时区偏移（来自 `AppConfig::utc_offset_secs`）
+pub fn set_utc_offset(secs: i64) {
+    UTC_OFFSET_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// 后台循环每轮的默认秒数（也是广播快照的间隔，可以用 `set_update_interval` 调）
+pub const LOOP_INTERVAL_SECS: u64 = 10;
+/// 循环间隔的可调范围（秒）：太快费电，太慢她看起来像卡住了
+pub const MIN_UPDATE_INTERVAL_SECS: u64 = 2;
+pub const MAX_UPDATE_INTERVAL_SECS: u64 = 60;
+/// 每隔多少秒 tick 一次生理/情绪/关系（与循环间隔无关）
+pub const STATE_TICK_SECS: u64 = 30;
+
+/// 自主思考的默认间隔：30 分钟
+const DEFAULT_THINK_INTERVAL_SECS: u64 = 1800;
+/// 自主思考的最小间隔，避免频繁调用 API
+const MIN_THINK_INTERVAL_SECS: u64 = 300;
+
+/// 按情绪调整后的 temperature 范围
+const MIN_MOOD_TEMPERATURE: f32 = 0.3;
+const MAX_MOOD_TEMPERATURE: f32 = 1.3;
+
+/// 单次 LLM 调用的参数
+#[derive(Debug, Clone, Copy)]
+pub struct CallParams {
+    pub max_tokens: u32,
+    /// 越低回答越稳定简短，越高越多变（基础值，调用时按情绪偏移）
+    pub temperature: f32,
+}
+
+impl CallParams {
+    /// 读取 `<PREFIX>_MAX_TOKENS` / `<PREFIX>_TEMPERATURE`，缺省用给定值
+    fn from_env(prefix: &str, max_tokens: u32, temperature: f32) -> Self {
+        Self {
+            max_tokens: env_parse(&format!("{}_MAX_TOKENS", prefix))
+                .unwrap_or(max_tokens)
+                .max(1),
+            temperature: env_parse::<f32>(&format!("{}_TEMPERATURE", prefix))
+                .filter(|t| t.is_finite())
+                .unwrap_or(temperature)
+                .clamp(0.0, 2.0),
+        }
+    }
+
+    /// 在配置的 temperature 上叠加情绪偏移：情绪越强烈偏得越多，结果限制在合理范围
+    pub fn temperature_for(&self, emotion: Emotion, intensity: f32) -> f32 {
+        let scale = 0.5 + intensity.clamp(0.0, 1.0);
+        (self.temperature + emotion.temperature_shift() * scale)
+            .clamp(MIN_MOOD_TEMPERATURE, MAX_MOOD_TEMPERATURE)
+    }
+}
+
+/// 数值调校参数：被冷落时关系怎么衰减、累倒后要睡多久
+#[derive(Debug, Clone, Copy)]
+pub struct TuningConfig {
+    /// 冷落多少分钟内关系完全不掉，`NEGLECT_GRACE_MINUTES`
+    pub neglect_grace_minutes: u32,
+    /// 过了宽限期后衰减加速到基础速率所需的分钟数，`NEGLECT_RAMP_MINUTES`
+    pub neglect_ramp_minutes: u32,
+    /// 衰减加速的上限（基础速率的倍数）
+    pub neglect_max_ramp: f32,
+    /// 每次 tick 的基础信任衰减，`NEGLECT_TRUST_RATE`
+    pub neglect_trust_rate: f32,
+    /// 每次 tick 的基础亲密度衰减，`NEGLECT_INTIMACY_RATE`
+    pub neglect_intimacy_rate: f32,
+    /// 体力耗尽累倒后，体力恢复到这么多才能醒，`COLLAPSE_RECOVER_ENERGY`
+    pub collapse_recover_energy: f32,
+    /// 情绪转移的阈值
+    pub transition: TransitionConfig,
+}
+
+impl TuningConfig {
+    pub fn new() -> Self {
+        Self {
+            neglect_grace_minutes: 120,
+            neglect_ramp_minutes: 120,
+            neglect_max_ramp: 3.0,
+            neglect_trust_rate: 0.1,
+            neglect_intimacy_rate: 0.2,
+            collapse_recover_energy: 30.0,
+            transition: TransitionConfig::new(),
+        }
+    }
+
+    fn from_env() -> Self {
+        let defaults = Self::new();
+        let rate = |key: &str, default: f32| {
+            env_parse::<f32>(key)
+                .filter(|r| r.is_finite() && *r >= 0.0)
+                .unwrap_or(default)
+        };
+        Self {
+            neglect_grace_minutes: env_parse("NEGLECT_GRACE_MINUTES")
+                .unwrap_or(defaults.neglect_grace_minutes),
+            neglect_ramp_minutes: env_parse("NEGLECT_RAMP_MINUTES")
+                .unwrap_or(defaults.neglect_ramp_minutes)
+                .max(1),
+            neglect_max_ramp: defaults.neglect_max_ramp,
+            neglect_trust_rate: rate("NEGLECT_TRUST_RATE", defaults.neglect_trust_rate),
+            neglect_intimacy_rate: rate("NEGLECT_INTIMACY_RATE", defaults.neglect_intimacy_rate),
+            collapse_recover_energy: rate("COLLAPSE_RECOVER_ENERGY", defaults.collapse_recover_energy)
+                .clamp(1.0, 100.0),
+            transition: TransitionConfig::from_env(),
+        }
+    }
+
+    /// 冷落了这么久时的衰减倍率：宽限期内为 0，之后线性加速，封顶 `neglect_max_ramp`
+    pub fn neglect_ramp(&self, minutes_since_interaction: u32) -> f32 {
+        let over = minutes_since_interaction.saturating_sub(self.neglect_grace_minutes);
+        (over as f32 / self.neglect_ramp_minutes as f32).min(self.neglect_max_ramp)
+    }
+}
+
+/// 情绪转移的阈值（见 `Emotion::transition`）：分钟数是基线强度、中等黏人程度下的值
+#[derive(Debug, Clone, Copy)]
+pub struct TransitionConfig {
+    /// 平静时被冷落多久（分钟）变无聊，`EMOTION_BORED_MINUTES`
+    pub bored_minutes: f32,
+    /// 人不在时平静能维持多久，`EMOTION_BORED_AWAY_MINUTES`
+    pub bored_away_minutes: f32,
+    /// 有互动时体力高于这个值才会开心，`EMOTION_HAPPY_MIN_ENERGY`
+    pub happy_min_energy: f32,
+    /// 开心维持多久，`EMOTION_HAPPY_MINUTES`
+    pub happy_minutes: f32,
+    /// 人走了，无聊多久后平静下来，`EMOTION_AWAY_CALM_MINUTES`
+    pub away_calm_minutes: f32,
+    /// 人在电脑前却不理她，无聊多久后烦躁，`EMOTION_IGNORED_MINUTES`
+    pub ignored_minutes: f32,
+    /// 无聊多久后变低落（或烦躁），`EMOTION_NEGLECT_MINUTES`
+    pub neglect_minutes: f32,
+    /// 冷落到头时亲密度高于这个值是烦躁，否则是低落，`EMOTION_NEGLECT_IRRITATED_INTIMACY`
+    pub neglect_irritated_intimacy: f32,
+    /// 亲密度高于这个值，无聊时变黏人，`EMOTION_NEEDY_INTIMACY`
+    pub needy_intimacy: f32,
+    /// 黏人没人理多久后烦躁，`EMOTION_NEEDY_MINUTES`
+    pub needy_minutes: f32,
+    /// 烦躁维持多久，`EMOTION_IRRITATED_MINUTES`
+    pub irritated_minutes: f32,
+    /// 低落时亲密度高于这个值，互动才能让她平静，`EMOTION_DOWN_RECOVER_INTIMACY`
+    pub down_recover_intimacy: f32,
+    /// 好奇维持多久，`EMOTION_CURIOUS_MINUTES`
+    pub curious_minutes: f32,
+    /// 想玩时体力低于这个值就平静下来，`EMOTION_PLAYFUL_MIN_ENERGY`
+    pub playful_min_energy: f32,
+    /// 想玩维持多久，`EMOTION_PLAYFUL_MINUTES`
+    pub playful_minutes: f32,
+}
+
+impl TransitionConfig {
+    pub fn new() -> Self {
+        Self {
+            bored_minutes: 120.0,
+            bored_away_minutes: 240.0,
+            happy_min_energy: 50.0,
+            happy_minutes: 60.0,
+            away_calm_minutes: 240.0,
+            ignored_minutes: 120.0,
+            neglect_minutes: 240.0,
+            neglect_irritated_intimacy: 40.0,
+            needy_intimacy: 60.0,
+            needy_minutes: 300.0,
+            irritated_minutes: 30.0,
+            down_recover_intimacy: 30.0,
+            curious_minutes: 10.0,
+            playful_min_energy: 40.0,
+            playful_minutes: 30.0,
+        }
+    }
+
+    fn from_env() -> Self {
+        let d = Self::new();
+        let minutes = |key: &str, default: f32| {
+            env_parse::<f32>(key)
+                .filter(|m| m.is_finite() && *m > 0.0)
+                .unwrap_or(default)
+        };
+        let level = |key: &str, default: f32| {
+            env_parse::<f32>(key)
+                .filter(|v| v.is_finite())
+                .unwrap_or(default)
+                .clamp(0.0, 100.0)
+        };
+        Self {
+            bored_minutes: minutes("EMOTION_BORED_MINUTES", d.bored_minutes),
+            bored_away_minutes: minutes("EMOTION_BORED_AWAY_MINUTES", d.bored_away_minutes),
+            happy_min_energy: level("EMOTION_HAPPY_MIN_ENERGY", d.happy_min_energy),
+            happy_minutes: minutes("EMOTION_HAPPY_MINUTES", d.happy_minutes),
+            away_calm_minutes: minutes("EMOTION_AWAY_CALM_MINUTES", d.away_calm_minutes),
+            ignored_minutes: minutes("EMOTION_IGNORED_MINUTES", d.ignored_minutes),
+            neglect_minutes: minutes("EMOTION_NEGLECT_MINUTES", d.neglect_minutes),
+            neglect_irritated_intimacy: level("EMOTION_NEGLECT_IRRITATED_INTIMACY", d.neglect_irritated_intimacy),
+            needy_intimacy: level("EMOTION_NEEDY_INTIMACY", d.needy_intimacy),
+            needy_minutes: minutes("EMOTION_NEEDY_MINUTES", d.needy_minutes),
+            irritated_minutes: minutes("EMOTION_IRRITATED_MINUTES", d.irritated_minutes),
+            down_recover_intimacy: level("EMOTION_DOWN_RECOVER_INTIMACY", d.down_recover_intimacy),
+            curious_minutes: minutes("EMOTION_CURIOUS_MINUTES", d.curious_minutes),
+            playful_min_energy: level("EMOTION_PLAYFUL_MIN_ENERGY", d.playful_min_energy),
+            playful_minutes: minutes("EMOTION_PLAYFUL_MINUTES", d.playful_minutes),
+        }
+    }
+}
+
+/// 默认最多同时 2 个 LLM 请求
+const DEFAULT_LLM_CONCURRENCY: usize = 2;
+/// 言语响应排队等待 LLM 名额的最长时间
+pub const SPEECH_QUEUE_WAIT_SECS: u64 = 5;
+
+/// 两次互动之间默认至少间隔 300 毫秒
+const DEFAULT_INTERACTION_INTERVAL_MS: u64 = 300;
+
+/// 运行时配置（从环境变量 / .env 读取）
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    /// 自主思考的间隔（秒），`THINK_INTERVAL_SECS`
+    pub think_interval_secs: u64,
+    /// 情绪变得无聊/低落或长时间被忽视时额外触发思考，`THINK_ON_EVENTS=1`
+    pub think_on_events: bool,
+    /// 离线模式：不发任何 LLM 请求，`OFFLINE=1`
+    pub offline: bool,
+    /// 自主思考的调用参数，`THINK_MAX_TOKENS` / `THINK_TEMPERATURE`
+    pub thinking: CallParams,
+    /// 言语响应的调用参数，`SPEECH_MAX_TOKENS` / `SPEECH_TEMPERATURE`
+    pub speech: CallParams,
+    /// 同时进行的 LLM 请求上限，`LLM_MAX_CONCURRENCY`
+    pub llm_max_concurrency: usize,
+    /// 请求时带 `response_format: json_object`（服务端支持时更稳），`LLM_JSON_MODE=1`
+    pub llm_json_mode: bool,
+    /// 两次互动之间的最小间隔（毫秒），更快的点击直接忽略，`INTERACTION_MIN_INTERVAL_MS`
+    pub interaction_min_interval_ms: u64,
+    /// 想法和 prompt 的语言，`SOPHIE_LANG`（zh / en），缺省跟随系统 locale
+    pub lang: Lang,
+    /// 本地时区相对 UTC 的偏移（秒），`UTC_OFFSET`（如 +08:00 / -5），缺省读系统时区
+    pub utc_offset_secs: i64,
+    /// 数值调校参数
+    pub tuning: TuningConfig,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Self {
+        let think_interval_secs = env_parse("THINK_INTERVAL_SECS")
+            .unwrap_or(DEFAULT_THINK_INTERVAL_SECS)
+            .max(MIN_THINK_INTERVAL_SECS);
+        let think_on_events = env_flag("THINK_ON_EVENTS");
+        let offline = env_flag("OFFLINE");
+
+        Self {
+            think_interval_secs,
+            think_on_events,
+            offline,
+            thinking: CallParams::from_env("THINK", 300, 0.9),
+            speech: CallParams::from_env("SPEECH", 200, 0.9),
+            llm_max_concurrency: env_parse("LLM_MAX_CONCURRENCY")
+                .unwrap_or(DEFAULT_LLM_CONCURRENCY)
+                .max(1),
+            llm_json_mode: env_flag("LLM_JSON_MODE"),
+            interaction_min_interval_ms: env_parse("INTERACTION_MIN_INTERVAL_MS")
+                .unwrap_or(DEFAULT_INTERACTION_INTERVAL_MS),
+            lang: Lang::from_env(),
+            utc_offset_secs: env_utc_offset()
+                .or_else(system_utc_offset)
+                .unwrap_or(DEFAULT_UTC_OFFSET_SECS),
+            tuning: TuningConfig::from_env(),
+        }
+    }
+
+    /// 两次思考之间至少间隔的秒数（事件触发也要遵守）
+    pub fn min_think_gap_secs(&self) -> u64 {
+        MIN_THINK_INTERVAL_SECS
+    }
+}
+
+/// 把循环间隔限制在可调范围内
+pub fn clamp_update_interval(secs: u64) -> u64 {
+    secs.clamp(MIN_UPDATE_INTERVAL_SECS, MAX_UPDATE_INTERVAL_SECS)
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    let value = std::env::var(key).ok()?;
+    match value.trim().parse() {
+        Ok(v) => Some(v),
+        Err(_) => {
+            log::warn!("Invalid value for {}: {:?}, using default", key, value);
+            None
+        }
+    }
+}
+
+/// `UTC_OFFSET` 环境变量，格式不对时警告并忽略
+fn env_utc_offset() -> Option<i64> {
+    let value = std::env::var("UTC_OFFSET").ok()?;
+    let offset = parse_utc_offset(&value);
+    if offset.is_none() {
+        log::warn!("Invalid value for UTC_OFFSET: {:?}, using the system timezone", value);
+    }
+    offset
+}
+
+/// 解析时区偏移："+08:00" / "-0530" / "8" / "UTC+8"，返回秒
+pub fn parse_utc_offset(text: &str) -> Option<i64> {
+    let upper = text.trim().to_ascii_uppercase();
+    let rest = upper
+        .strip_prefix("UTC")
+        .or_else(|| upper.strip_prefix("GMT"))
+        .unwrap_or(&upper);
+    if rest.is_empty() {
+        return (upper != rest).then_some(0);
+    }
+    parse_hms(rest).filter(|secs| secs.abs() <= MAX_UTC_OFFSET_SECS)
+}
+
+/// 系统时区的标准时偏移（不考虑夏令时）：`TZ` 环境变量，或 /etc/localtime 末尾的 POSIX TZ 串
+fn system_utc_offset() -> Option<i64> {
+    let path = match std::env::var("TZ") {
+        Ok(tz) if !tz.is_empty() => {
+            let tz = tz.trim_start_matches(':');
+            if let Some(offset) = posix_tz_offset(tz) {
+                return Some(offset);
+            }
+            PathBuf::from("/usr/share/zoneinfo").join(tz)
+        }
+        _ => PathBuf::from("/etc/localtime"),
+    };
+    // TZif 文件最后一行是 POSIX TZ 串，比如 "CST-8"、"EST5EDT,M3.2.0,M11.1.0"
+    let data = std::fs::read(path).ok()?;
+    let text = String::from_utf8_lossy(&data);
+    let footer = text.trim_end_matches('\n').rsplit('\n').next()?;
+    posix_tz_offset(footer).filter(|secs| secs.abs() <= MAX_UTC_OFFSET_SECS)
+}
+
+/// POSIX TZ 串里的标准时偏移："CST-8" → UTC+8，"<+0530>-5:30" → UTC+5:30（POSIX 的符号和 UTC 偏移相反）
+fn posix_tz_offset(tz: &str) -> Option<i64> {
+    let rest = match tz.strip_prefix('<') {
+        Some(quoted) => &quoted[quoted.find('>')? + 1..],
+        None => {
+            let name_len = tz.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(tz.len());
+            if name_len < 3 {
+                return None;
+            }
+            &tz[name_len..]
+        }
+    };
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | ':')))
+        .unwrap_or(rest.len());
+    parse_hms(&rest[..end]).map(|secs| -secs)
+}
+
+/// "+8" / "-05:30" / "0800" / "5:30:15" → 秒
+fn parse_hms(text: &str) -> Option<i64> {
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let mut parts = digits.split(':');
+    let first = parts.next()?;
+    let (hours, minutes): (i64, i64) = if first.len() == 4 && first.bytes().all(|b| b.is_ascii_digit()) {
+        (first[..2].parse().ok()?, first[2..].parse().ok()?)
+    } else {
+        (first.parse().ok()?, parts.next().map_or(Some(0), |m| m.parse().ok())?)
+    };
+    let seconds: i64 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() || hours > 24 || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+fn env_flag(key: &str) -> bool {
+    std::env::var(key)
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_interval_is_clamped_to_a_safe_range() {
+        assert_eq!(clamp_update_interval(0), MIN_UPDATE_INTERVAL_SECS);
+        assert_eq!(clamp_update_interval(LOOP_INTERVAL_SECS), LOOP_INTERVAL_SECS);
+        assert_eq!(clamp_update_interval(3600), MAX_UPDATE_INTERVAL_SECS);
+        assert_eq!(AppConfig::from_env().min_think_gap_secs(), MIN_THINK_INTERVAL_SECS);
+    }
+
+    #[test]
+    fn env_values_parse_or_fall_back() {
+        std::env::set_var("SOPHIE_TEST_PARSE_OK", " 600 ");
+        std::env::set_var("SOPHIE_TEST_PARSE_BAD", "半小时");
+        assert_eq!(env_parse::<u64>("SOPHIE_TEST_PARSE_OK"), Some(600));
+        assert_eq!(env_parse::<u64>("SOPHIE_TEST_PARSE_BAD"), None);
+        assert_eq!(env_parse::<u64>("SOPHIE_TEST_PARSE_MISSING"), None);
+
+        std::env::set_var("SOPHIE_TEST_FLAG_ON", "yes");
+        std::env::set_var("SOPHIE_TEST_FLAG_OFF", "0");
+        assert!(env_flag("SOPHIE_TEST_FLAG_ON"));
+        assert!(!env_flag("SOPHIE_TEST_FLAG_OFF"));
+        assert!(!env_flag("SOPHIE_TEST_FLAG_MISSING"));
+    }
+
+    #[test]
+    fn call_params_default_and_clamp() {
+        let params = CallParams::from_env("SOPHIE_TEST_UNSET", 300, 0.9);
+        assert_eq!(params.max_tokens, 300);
+        assert_eq!(params.temperature, 0.9);
+
+        std::env::set_var("SOPHIE_TEST_CALL_MAX_TOKENS", "0");
+        std::env::set_var("SOPHIE_TEST_CALL_TEMPERATURE", "7.5");
+        let params = CallParams::from_env("SOPHIE_TEST_CALL", 300, 0.9);
+        assert_eq!(params.max_tokens, 1);
+        assert_eq!(params.temperature, 2.0);
+
+        std::env::set_var("SOPHIE_TEST_NAN_TEMPERATURE", "NaN");
+        assert_eq!(CallParams::from_env("SOPHIE_TEST_NAN", 200, 0.4).temperature, 0.4);
+    }
+
+    #[test]
+    fn utc_offsets_parse_in_common_forms() {
+        assert_eq!(parse_utc_offset("+08:00"), Some(8 * 3600));
+        assert_eq!(parse_utc_offset("8"), Some(8 * 3600));
+        assert_eq!(parse_utc_offset("UTC-5"), Some(-5 * 3600));
+        assert_eq!(parse_utc_offset(" gmt+0530 "), Some(5 * 3600 + 30 * 60));
+        assert_eq!(parse_utc_offset("-09:30"), Some(-(9 * 3600 + 30 * 60)));
+        assert_eq!(parse_utc_offset("UTC"), Some(0));
+        assert_eq!(parse_utc_offset(""), None);
+        assert_eq!(parse_utc_offset("+20"), None);
+        assert_eq!(parse_utc_offset("8:75"), None);
+        assert_eq!(parse_utc_offset("北京"), None);
+    }
+
+    #[test]
+    fn posix_tz_strings_give_the_standard_offset() {
+        assert_eq!(posix_tz_offset("CST-8"), Some(8 * 3600));
+        assert_eq!(posix_tz_offset("EST5EDT,M3.2.0,M11.1.0"), Some(-5 * 3600));
+        assert_eq!(posix_tz_offset("<+0530>-5:30"), Some(5 * 3600 + 30 * 60));
+        assert_eq!(posix_tz_offset("UTC0"), Some(0));
+        assert_eq!(posix_tz_offset("Asia/Shanghai"), None);
+    }
+
+    #[test]
+    fn neglect_ramp_is_flat_then_linear_then_capped() {
+        let tuning = TuningConfig::new();
+        assert_eq!(tuning.neglect_ramp(0), 0.0);
+        assert_eq!(tuning.neglect_ramp(120), 0.0);
+        assert_eq!(tuning.neglect_ramp(180), 0.5);
+        assert_eq!(tuning.neglect_ramp(240), 1.0);
+        assert_eq!(tuning.neglect_ramp(10_000), tuning.neglect_max_ramp);
+    }
+
+    #[test]
+    fn each_emotion_lands_in_its_temperature_band() {
+        let params = CallParams { max_tokens: 100, temperature: 0.9 };
+        for emotion in Emotion::ALL {
+            let t = params.temperature_for(emotion, 0.5);
+            match emotion {
+                Emotion::Playful | Emotion::Curious | Emotion::Happy | Emotion::Needy => {
+                    assert!(t > 0.9 && t <= 1.2, "{:?}: {}", emotion, t)
+                }
+                Emotion::Calm | Emotion::Bored => assert_eq!(t, 0.9, "{:?}", emotion),
+                Emotion::Down | Emotion::Irritated => {
+                    assert!((0.45..0.9).contains(&t), "{:?}: {}", emotion, t)
+                }
+            }
+        }
+        // 情绪越强偏得越多，但不出范围
+        let mild = params.temperature_for(Emotion::Irritated, 0.0);
+        let strong = params.temperature_for(Emotion::Irritated, 1.0);
+        assert!(strong < mild);
+        let hot = CallParams { max_tokens: 100, temperature: 1.2 };
+        assert_eq!(hot.temperature_for(Emotion::Playful, 1.0), MAX_MOOD_TEMPERATURE);
+        let cold = CallParams { max_tokens: 100, temperature: 0.4 };
+        assert_eq!(cold.temperature_for(Emotion::Irritated, 1.0), MIN_MOOD_TEMPERATURE);
+    }
+}
+
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+use crate::memory::MemoryStore;
+use crate::state::emotion::Emotion;
+
+/// 免打扰设置在 sophie_state 表里的 key
+const PREFS_KEY: &str = "dnd";
+
+/// 定时免打扰的时段：本地钟点 [start_hour, end_hour)，可以跨零点（如 22-7）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DndWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl DndWindow {
+    pub fn new(start_hour: u32, end_hour: u32) -> Self {
+        Self { start_hour: start_hour % 24, end_hour: end_hour % 24 }
+    }
+
+    /// 这个钟点在不在时段里（开始等于结束视为空时段）
+    pub fn contains(&self, hour: u32) -> bool {
+        let hour = hour % 24;
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// 免打扰偏好（持久化）：主人手动开关，或者按时段自动开
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DndPrefs {
+    /// 手动开启
+    pub manual: bool,
+    /// 定时时段（None = 不定时）
+    pub schedule: Option<DndWindow>,
+    /// 免打扰时让她也安静下来：闹腾的情绪直接平静
+    pub calm_down: bool,
+}
+
+impl DndPrefs {
+    pub fn new() -> Self {
+        Self { manual: false, schedule: None, calm_down: false }
+    }
+
+    /// 从数据库读取，没有或损坏时用默认值
+    pub fn load(mem: &MemoryStore) -> Self {
+        mem.load_state(PREFS_KEY)
+            .and_then(|json| serde_json::from_str::<DndPrefs>(&json).ok())
+            .unwrap_or_else(DndPrefs::new)
+    }
+
+    pub fn save(&self, mem: &MemoryStore) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| format!("Serialize error: {}", e))?;
+        mem.save_state(PREFS_KEY, &json)
+    }
+
+    /// 现在是否免打扰；`hour` 是本地钟点
+    pub fn is_active(&self, hour: u32) -> bool {
+        self.manual || self.schedule.is_some_and(|window| window.contains(hour))
+    }
+}
+
+/// 免打扰的运行状态：偏好 + 这段免打扰期间憋着没说的事
+pub struct Dnd {
+    pub prefs: DndPrefs,
+    /// 上一轮循环时是否在免打扰
+    was_active: bool,
+    /// 免打扰期间被压下去的想法数
+    suppressed: u32,
+    /// 免打扰开始时的情绪（结束时对比有没有变化）
+    emotion_at_start: Option<Emotion>,
+}
+
+impl Dnd {
+    pub fn new(prefs: DndPrefs) -> Self {
+        Self { prefs, was_active: false, suppressed: 0, emotion_at_start: None }
+    }
+
+    pub fn is_active(&self, hour: u32) -> bool {
+        self.prefs.is_active(hour)
+    }
+
+    /// 记一次被压下的想法
+    pub fn suppress(&mut self) {
+        self.suppressed += 1;
+    }
+
+    /// 每轮循环调用：免打扰刚结束、而且期间有想法被压下或情绪变了，就返回 true（该补一句）
+    pub fn poll(&mut self, hour: u32, emotion: Emotion) -> bool {
+        let active = self.prefs.is_active(hour);
+        if active && !self.was_active {
+            self.suppressed = 0;
+            self.emotion_at_start = Some(emotion);
+        }
+        let ended = self.was_active && !active;
+        self.was_active = active;
+        if !ended {
+            return false;
+        }
+        let mood_changed = self.emotion_at_start.take().is_some_and(|start| start != emotion);
+        let notable = self.suppressed > 0 || mood_changed;
+        self.suppressed = 0;
+        notable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(start: u32, end: u32) -> Dnd {
+        Dnd::new(DndPrefs { schedule: Some(DndWindow::new(start, end)), ..DndPrefs::new() })
+    }
+
+    #[test]
+    fn window_wraps_past_midnight() {
+        let night = DndWindow::new(22, 7);
+        assert!(night.contains(23) && night.contains(0) && night.contains(6));
+        assert!(!night.contains(7) && !night.contains(12) && !night.contains(21));
+        assert!(!DndWindow::new(9, 9).contains(9));
+    }
+
+    #[test]
+    fn suppression_applies_only_while_active() {
+        let mut dnd = scheduled(22, 7);
+        assert!(!dnd.poll(21, Emotion::Calm));
+        assert!(!dnd.is_active(21));
+
+        // 进入免打扰：想法被压下，期间不补话
+        assert!(!dnd.poll(22, Emotion::Calm));
+        assert!(dnd.is_active(23));
+        dnd.suppress();
+        assert!(!dnd.poll(3, Emotion::Calm));
+
+        // 结束时补一句，之后恢复正常
+        assert!(dnd.poll(7, Emotion::Calm));
+        assert!(!dnd.is_active(8));
+        assert!(!dnd.poll(8, Emotion::Calm));
+    }
+
+    #[test]
+    fn quiet_stretch_ends_silently_unless_mood_changed() {
+        let mut dnd = scheduled(1, 3);
+        dnd.poll(1, Emotion::Calm);
+        assert!(!dnd.poll(3, Emotion::Calm));
+
+        dnd.poll(1, Emotion::Calm);
+        assert!(dnd.poll(3, Emotion::Bored));
+    }
+
+    #[test]
+    fn manual_switch_overrides_schedule() {
+        let mut dnd = scheduled(22, 7);
+        dnd.prefs.manual = true;
+        assert!(dnd.is_active(12));
+        dnd.poll(12, Emotion::Calm);
+        dnd.suppress();
+        dnd.prefs.manual = false;
+        assert!(dnd.poll(12, Emotion::Calm));
+    }
+
+    #[test]
+    fn schedule_survives_a_restart() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        assert!(!DndPrefs::load(&mem).is_active(23));
+        let prefs = DndPrefs { schedule: Some(DndWindow::new(22, 7)), calm_down: true, ..DndPrefs::new() };
+        prefs.save(&mem).unwrap();
+        let loaded = DndPrefs::load(&mem);
+        assert!(loaded.is_active(23) && !loaded.is_active(12));
+        assert!(loaded.calm_down);
+    }
+}
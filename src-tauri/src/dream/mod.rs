@@ -0,0 +1,95 @@
+use crate::rng;
+use crate::memory::{Memory, MemoryKind};
+
+/// 睡着时每次检查做梦的概率（百分比）
+const DREAM_CHANCE_PERCENT: u32 = 5;
+
+/// 一个梦
+#[derive(Debug, Clone)]
+pub struct Dream {
+    pub text: String,
+    /// 好梦醒来心情更好
+    pub pleasant: bool,
+}
+
+/// 这次检查要不要做梦（只有睡着时才会）
+pub fn should_dream(is_sleeping: bool) -> bool {
+    is_sleeping && ((rng::rand_f32() * 100.0) as u32) < DREAM_CHANCE_PERCENT
+}
+
+/// 从难忘的记忆里挑一条编成梦
+pub fn dream_from_memories(memories: &[Memory]) -> Option<Dream> {
+    if memories.is_empty() {
+        return None;
+    }
+    let memory = &memories[rng::rand_index(memories.len())];
+    Some(dream_from_memory(memory))
+}
+
+/// 把一条记忆变成梦话（不依赖 LLM）
+pub fn dream_from_memory(memory: &Memory) -> Dream {
+    let content = memory.content.as_str();
+    let unpleasant = ["叫醒", "烦", "讨厌", "走开"].iter().any(|w| content.contains(w));
+
+    let text = if content.contains("喂食") || content.contains("吃") {
+        "梦到...鱼".to_string()
+    } else if content.contains("点了我") || content.contains("摸") {
+        "梦到...主人的手".to_string()
+    } else if memory.kind == MemoryKind::UserSpeech {
+        "梦到...主人在说话".to_string()
+    } else if unpleasant {
+        "梦到...不好的事".to_string()
+    } else {
+        let fragment: String = content.chars().take(4).collect();
+        format!("梦到...{}", fragment)
+    };
+
+    Dream {
+        text,
+        pleasant: !unpleasant && memory.emotional_weight >= 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(kind: MemoryKind, content: &str, emotional_weight: f32) -> Memory {
+        Memory {
+            id: 1,
+            kind,
+            content: content.to_string(),
+            emotional_weight,
+            timestamp: 0,
+            emotion: None,
+            behavior: None,
+        }
+    }
+
+    #[test]
+    fn never_dreams_while_awake() {
+        assert!((0..1000).all(|_| !should_dream(false)));
+    }
+
+    #[test]
+    fn dreams_come_from_existing_memories() {
+        assert!(dream_from_memories(&[]).is_none());
+
+        let memories = [memory(MemoryKind::Interaction, "主人给我喂食了", 0.6)];
+        let d = dream_from_memories(&memories).unwrap();
+        assert_eq!(d.text, "梦到...鱼");
+        assert!(d.pleasant);
+
+        let d = dream_from_memory(&memory(MemoryKind::Thought, "窗外的鸟飞走了", 0.7));
+        assert_eq!(d.text, "梦到...窗外的鸟");
+    }
+
+    #[test]
+    fn unpleasant_memories_make_bad_dreams() {
+        let d = dream_from_memory(&memory(MemoryKind::Interaction, "正困的时候被主人叫醒了", 0.9));
+        assert_eq!(d.text, "梦到...不好的事");
+        assert!(!d.pleasant);
+        // 淡淡的记忆也算不上好梦
+        assert!(!dream_from_memory(&memory(MemoryKind::Interaction, "主人点了我", 0.3)).pleasant);
+    }
+}
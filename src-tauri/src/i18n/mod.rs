@@ -0,0 +1,214 @@
+/// 界面/想法/prompt 使用的语言（默认中文）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lang {
+    Zh,
+    En,
+}
+
+impl Lang {
+    /// 解析语言代码或系统 locale（"en" / "en_US.UTF-8" / "zh-CN"），不认识的返回 None
+    pub fn parse(code: &str) -> Option<Lang> {
+        let code = code.trim().to_ascii_lowercase();
+        if code.starts_with("zh") {
+            Some(Lang::Zh)
+        } else if code.starts_with("en") {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+
+    /// `SOPHIE_LANG` 优先，其次系统 locale（LC_ALL / LANG），都没有就用中文
+    pub fn from_env() -> Lang {
+        ["SOPHIE_LANG", "LC_ALL", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find_map(|value| Lang::parse(&value))
+            .unwrap_or(Lang::Zh)
+    }
+}
+
+/// 规则想法的短语池
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phrase {
+    Starving,
+    Begging,
+    BeggingInsistent,
+    /// 带 `{food}` 占位符：点名要最喜欢的食物
+    BeggingFor,
+    Sleepy,
+    Snoring,
+    Groggy,
+    Tired,
+    /// 体力耗尽累倒
+    Collapsed,
+    Peckish,
+    MealTime,
+    /// 免打扰结束，补一句憋着的话
+    DndCatchUp,
+    /// 窗口重新显示时的招呼：亲近且刚走不久 / 亲近但走了很久 / 一般 / 冷淡
+    GreetWarm,
+    GreetMissed,
+    GreetPlain,
+    GreetCool,
+    /// 她的生日
+    Birthday,
+    HappyTrusting,
+    Happy,
+    Content,
+    BoredSeeking,
+    Bored,
+    Silence,
+    Needy,
+    Irritated,
+    Down,
+    Curious,
+    Playful,
+    Calm,
+    Warm,
+    Safe,
+    LateNight,
+    Morning,
+    NewDayLonely,
+    Dusk,
+    Sunny,
+    Rainy,
+    Cold,
+    CheckInShort,
+    CheckInLong,
+}
+
+/// 按语言取短语池
+pub fn phrases(lang: Lang, phrase: Phrase) -> &'static [&'static str] {
+    match lang {
+        Lang::Zh => zh(phrase),
+        Lang::En => en(phrase),
+    }
+}
+
+fn zh(phrase: Phrase) -> &'static [&'static str] {
+    match phrase {
+        Phrase::Starving => &["饿...", "饭。", "肚子空了"],
+        Phrase::Begging => &["饭。", "饭？", "碗空了"],
+        Phrase::BeggingInsistent => &["饭！", "饭！！", "喵！饭！"],
+        Phrase::BeggingFor => &["{food}。", "想吃{food}", "{food}呢？"],
+        Phrase::Sleepy => &["困...", "眼睛睁不开", "想睡"],
+        Phrase::Snoring => &["zzz", "zzz...", "呼..."],
+        Phrase::Groggy => &["...嗯", "嗯...？", "..."],
+        Phrase::Tired => &["累", "不想动"],
+        Phrase::Collapsed => &["累...", "累...走不动了"],
+        Phrase::Peckish => &["有点饿", "饭呢"],
+        Phrase::MealTime => &["该吃了", "饭点到了", "...饭呢？"],
+        Phrase::DndCatchUp => &["忙完了？", "刚才想跟你说来着...", "终于..."],
+        Phrase::GreetWarm => &["嗯~", "回来啦", "在这里。"],
+        Phrase::GreetMissed => &["去哪了...", "终于回来了", "...等你好久"],
+        Phrase::GreetPlain => &["嗯。", "哦，是你"],
+        Phrase::GreetCool => &["...回来了", "哼。", "..."],
+        Phrase::Birthday => &["今天...是我的日子", "生日！", "有小鱼干吗？今天可是生日"],
+        Phrase::HappyTrusting => &["嗯~", "在这里。", "暖暖的"],
+        Phrase::Happy => &["嗯~"],
+        Phrase::Content => &["舒服", "不错"],
+        Phrase::BoredSeeking => &["无聊", "理我", "看这边"],
+        Phrase::Bored => &["无聊"],
+        Phrase::Silence => &["..."],
+        Phrase::Needy => &["...还不理我？", "喵！", "看我", "人呢"],
+        Phrase::Irritated => &["烦", "别碰", "哼。"],
+        Phrase::Down => &["...", "没人理"],
+        Phrase::Curious => &["嗯？", "那是什么", "外面有鸟"],
+        Phrase::Playful => &["来玩！", "抓！"],
+        Phrase::Calm => &["嗯。"],
+        Phrase::Warm => &["暖和"],
+        Phrase::Safe => &["安心。"],
+        Phrase::LateNight => &["这么晚...", "夜里好安静", "...还不睡？"],
+        Phrase::Morning => &["早", "早。", "天亮了"],
+        Phrase::NewDayLonely => &["又一天...", "昨天没人理", "...今天呢？"],
+        Phrase::Dusk => &["外面...鸟", "有什么在动", "想抓"],
+        Phrase::Sunny => &["晒太阳", "暖洋洋", "阳光好"],
+        Phrase::Rainy => &["下雨了...", "滴答滴答", "想窝着"],
+        Phrase::Cold => &["冷", "想钻被窝", "暖气呢"],
+        Phrase::CheckInShort => &["...？", "人呢", "嗯？"],
+        Phrase::CheckInLong => &["好久没来了", "...还回来吗", "等你"],
+    }
+}
+
+fn en(phrase: Phrase) -> &'static [&'static str] {
+    match phrase {
+        Phrase::Starving => &["Hungry...", "Food.", "Empty belly"],
+        Phrase::Begging => &["Food.", "Food?", "Bowl's empty"],
+        Phrase::BeggingInsistent => &["Food!", "FOOD!", "Meow! Food!"],
+        Phrase::BeggingFor => &["{food}.", "Want {food}", "{food}?"],
+        Phrase::Sleepy => &["Sleepy...", "Eyes closing", "Nap time"],
+        Phrase::Snoring => &["zzz", "zzz...", "purr..."],
+        Phrase::Groggy => &["...mm", "Mm...?", "..."],
+        Phrase::Tired => &["Tired", "Not moving"],
+        Phrase::Collapsed => &["So tired...", "Tired... can't move"],
+        Phrase::Peckish => &["Bit hungry", "Food?"],
+        Phrase::MealTime => &["Dinner time", "It's time", "...food?"],
+        Phrase::DndCatchUp => &["Done?", "Was going to tell you...", "Finally..."],
+        Phrase::GreetWarm => &["Mm~", "You're back", "Right here."],
+        Phrase::GreetMissed => &["Where'd you go...", "Finally back", "...waited so long"],
+        Phrase::GreetPlain => &["Mm.", "Oh, you"],
+        Phrase::GreetCool => &["...back, huh", "Hmph.", "..."],
+        Phrase::Birthday => &["Today is... my day", "Birthday!", "Treats? It's my birthday"],
+        Phrase::HappyTrusting => &["Mm~", "Right here.", "Warm"],
+        Phrase::Happy => &["Mm~"],
+        Phrase::Content => &["Comfy", "Nice"],
+        Phrase::BoredSeeking => &["Bored", "Notice me", "Over here"],
+        Phrase::Bored => &["Bored"],
+        Phrase::Silence => &["..."],
+        Phrase::Needy => &["...still ignoring me?", "Meow!", "Look at me", "Hello?"],
+        Phrase::Irritated => &["Ugh", "Don't touch", "Hmph."],
+        Phrase::Down => &["...", "Nobody cares"],
+        Phrase::Curious => &["Hm?", "What's that", "Bird outside"],
+        Phrase::Playful => &["Play!", "Pounce!"],
+        Phrase::Calm => &["Mm."],
+        Phrase::Warm => &["Cozy"],
+        Phrase::Safe => &["Safe."],
+        Phrase::LateNight => &["So late...", "Quiet night", "...not sleeping?"],
+        Phrase::Morning => &["Morning", "Morning.", "It's light"],
+        Phrase::NewDayLonely => &["Another day...", "Nobody came yesterday", "...today?"],
+        Phrase::Dusk => &["Outside...bird", "Something moved", "Want to hunt"],
+        Phrase::Sunny => &["Sunbeam", "So warm", "Nice sun"],
+        Phrase::Rainy => &["Raining...", "Drip drip", "Stay in"],
+        Phrase::Cold => &["Cold", "Blanket?", "Heater?"],
+        Phrase::CheckInShort => &["...?", "Where'd you go", "Hm?"],
+        Phrase::CheckInLong => &["It's been a while", "...coming back?", "Waiting"],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: &[Phrase] = &[
+        Phrase::Starving, Phrase::Sleepy, Phrase::Snoring, Phrase::Groggy, Phrase::Tired,
+        Phrase::Peckish, Phrase::HappyTrusting, Phrase::Happy, Phrase::Content,
+        Phrase::BoredSeeking, Phrase::Bored, Phrase::Silence, Phrase::Needy, Phrase::Irritated,
+        Phrase::Down, Phrase::Curious, Phrase::Playful, Phrase::Calm, Phrase::Warm, Phrase::Safe,
+        Phrase::LateNight, Phrase::Morning, Phrase::Dusk, Phrase::Sunny, Phrase::Rainy,
+        Phrase::Cold, Phrase::CheckInShort, Phrase::CheckInLong, Phrase::Begging,
+        Phrase::BeggingInsistent, Phrase::BeggingFor, Phrase::NewDayLonely, Phrase::MealTime,
+        Phrase::DndCatchUp, Phrase::Collapsed, Phrase::GreetWarm, Phrase::GreetMissed,
+        Phrase::GreetPlain, Phrase::GreetCool, Phrase::Birthday,
+    ];
+
+    #[test]
+    fn locale_codes_pick_the_language() {
+        assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::parse(" EN "), Some(Lang::En));
+        assert_eq!(Lang::parse("zh-CN"), Some(Lang::Zh));
+        assert_eq!(Lang::parse("fr_FR"), None);
+        assert_eq!(Lang::parse(""), None);
+    }
+
+    #[test]
+    fn every_phrase_has_lines_in_both_languages() {
+        for &phrase in ALL {
+            assert!(!phrases(Lang::Zh, phrase).is_empty(), "{:?}", phrase);
+            let en = phrases(Lang::En, phrase);
+            assert!(!en.is_empty(), "{:?}", phrase);
+            // 英文短语里不该混进中文
+            assert!(en.iter().all(|line| line.is_ascii()), "{:?}", phrase);
+        }
+    }
+}
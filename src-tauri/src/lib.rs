@@ -2,8 +2,28 @@ mod state;
 mod behavior;
 mod memory;
 mod llm;
+mod config;
+mod offline;
+mod dream;
+mod rng;
+mod window;
+mod sound;
+mod i18n;
+mod autostart;
+mod card;
+mod clock;
+mod dnd;
+mod calendar;
+#[cfg(feature = "automation")]
+mod automation;
+/// 整段模拟（测试 / 调试用）
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
 
-use std::sync::Mutex;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Semaphore;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
 use std::path::PathBuf;
 
@@ -14,18 +34,65 @@ use tauri::{
     tray::TrayIconBuilder,
 };
 
-use state::SophieState;
-use behavior::{Behavior, decide_behavior};
-use memory::MemoryStore;
+use state::{ComfortOutcome, SophieState};
+use state::emotion::Emotion;
+use state::food::FoodType;
+use state::petting::PetOutcome;
+use state::environment::{Season, Weather};
+use behavior::{Behavior, BehaviorReason, decide_behavior, decide_behavior_with_reason};
+use memory::{MemoryKind, MemoryStore, PendingMemory};
 use llm::LlmClient;
+use config::AppConfig;
+use offline::{rule_based_speech, rule_based_thought};
+use sound::Sound;
 
 // ── 共享状态 ─────────────────────────────────────────────────
 
 struct AppState {
     sophie: Mutex<SophieState>,
     memory: Mutex<MemoryStore>,
+    /// 互动产生的记忆先进缓冲区，由后台循环批量写入，避免阻塞命令
+    pending_memories: Mutex<Vec<PendingMemory>>,
     llm: LlmClient,
+    /// prompt 模板（可从数据目录的 prompts/ 覆盖）
+    prompts: llm::PromptTemplates,
     tokio_rt: tokio::runtime::Runtime,
+    /// 最新一次言语请求的序号；旧请求的响应到达时直接丢弃
+    speech_seq: AtomicU64,
+    /// 累计的 LLM token 用量与耗时
+    llm_usage: Mutex<llm::UsageTotals>,
+    /// 思考/言语调用成功解析、用了保底、出错的次数
+    llm_health: Mutex<llm::HealthCounters>,
+    /// 离线模式：想法和言语响应全部由规则生成
+    offline: AtomicBool,
+    window_prefs: Mutex<window::WindowPrefs>,
+    /// 窗口从托盘隐藏的时间（Unix 秒），0 = 没在隐藏
+    hidden_at: AtomicU64,
+    config: AppConfig,
+    /// 最近一次快照里的行为及其决定因素（get_behavior_reason 直接读它，不重新抽签）
+    last_behavior: Mutex<(Behavior, BehaviorReason)>,
+    /// LLM 请求名额：思考和言语响应共用，限制同时进行的请求数
+    llm_slots: Arc<Semaphore>,
+    /// 静音：不再发送 sophie-sound 事件
+    muted: AtomicBool,
+    /// 后台循环（快照广播）的间隔秒数，下一轮生效
+    update_interval_secs: AtomicU64,
+    /// 免打扰：期间不发想法气泡和音效（状态照常 tick）
+    dnd: Mutex<dnd::Dnd>,
+    /// 最近的想法气泡：窗口隐藏或前端没在监听时错过的，可以补看
+    recent_thoughts: Mutex<VecDeque<QueuedThought>>,
+    /// 最近记进记忆的想法，避免同一句反复记
+    stored_thoughts: Mutex<memory::dedup::RecentThoughts>,
+    /// 上次接受互动的时间（Unix 毫秒），用于限制互动频率
+    last_interaction_ms: AtomicU64,
+    /// 上次发出想法气泡的时间（Unix 毫秒），用于给气泡限流
+    last_thought_ms: AtomicU64,
+    /// 每种情绪的颜色和动画提示（可从数据目录的 emotion_palette.json 覆盖）
+    emotion_palette: Vec<state::palette::EmotionMeta>,
+    /// 有互动改变了状态但还没存档；后台循环下一轮就存（同一轮内的多次互动合并写入）
+    state_dirty: AtomicBool,
+    /// 生日等纪念日（可从数据目录的 special_days.json 添加）
+    calendar: Mutex<calendar::Calendar>,
 }
 
 // ── 前端事件数据 ─────────────────────────────────────────────
@@ -36,14 +103,33 @@ struct SophieSnapshot {
     energy: f32,
     hunger: f32,
     sleepiness: f32,
+    /// 睡眠债 0-50：被吵醒太多次欠下的觉
+    sleep_debt: f32,
+    /// 情绪的稳定 id（见 `get_emotion_palette`）
     emotion: String,
+    /// 情绪强度 0-1，前端可据此调整动画幅度
+    emotion_intensity: f32,
     trust: f32,
     intimacy: f32,
     understanding: f32,
     is_sleeping: bool,
+    /// 体力耗尽累倒了（睡到恢复体力，叫不醒）
+    collapsed: bool,
     behavior: Behavior,
     flip_direction: bool,
+    /// 坐着露肚皮（信任度 > 70 才会出现）
+    belly_up: bool,
+    /// 正在要饭（前端可以显示扒拉的动作）
+    begging: bool,
+    /// 耳朵和尾巴的细节表情
+    expression: behavior::expression::Expression,
     minutes_since_interaction: u32,
+    /// 逻辑位置 0-1（0 = 最左），像素由前端换算
+    position_x: f32,
+    favorite_x: Option<f32>,
+    /// 这轮连续抚摸摸了几下（0 = 没在摸）
+    petting_strokes: u32,
+    personality: state::personality::PersonalityTraits,
 }
 
 /// 想法气泡事件
@@ -53,156 +139,1535 @@ struct ThoughtEvent {
     text: String,
 }
 
+/// 想法队列里的一条
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QueuedThought {
+    text: String,
+    timestamp: u64,
+}
+
+/// 想法队列最多保留几条
+const THOUGHT_QUEUE_LEN: usize = 20;
+/// 两个想法气泡之间至少间隔的毫秒数
+const MIN_THOUGHT_GAP_MS: u64 = 3000;
+
 /// 用户言语响应事件
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SpeechResponseEvent {
+    /// 请求序号，前端可以忽略比已收到的更旧的响应
+    seq: u64,
     action: String,
     thought: Option<String>,
     behavior: Behavior,
 }
 
-fn make_snapshot(sophie: &SophieState) -> SophieSnapshot {
-    let hour = chrono_hour();
-    let beh = decide_behavior(sophie, hour);
-    let flip = beh.may_change_direction() && (chrono_nanos() % 2 == 0);
-    SophieSnapshot {
-        energy: sophie.physiological.energy,
-        hunger: sophie.physiological.hunger,
-        sleepiness: sophie.physiological.sleepiness,
-        emotion: format!("{:?}", sophie.emotion),
-        trust: sophie.relationship.trust,
-        intimacy: sophie.relationship.intimacy,
-        understanding: sophie.relationship.understanding,
-        is_sleeping: sophie.is_sleeping,
-        behavior: beh,
-        flip_direction: flip,
-        minutes_since_interaction: sophie.minutes_since_interaction(),
+/// 互动统计（给统计面板 / 成就用）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatsSnapshot {
+    total_feeds: u64,
+    total_clicks: u64,
+    total_conversations: u64,
+    days_alive: u64,
+    /// 连续陪伴天数
+    streak_days: u32,
+    longest_streak_days: u32,
+}
+
+/// LLM 用量统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LlmUsageSnapshot {
+    total_prompt_tokens: u64,
+    total_completion_tokens: u64,
+    call_count: u64,
+    avg_latency_ms: u64,
+    /// LLM 返回认不出的 action 的次数
+    unrecognized_actions: u64,
+}
+
+/// 模型靠不靠谱：JSON 格式遵守得好不好
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LlmHealthSnapshot {
+    total_calls: u64,
+    parsed: u64,
+    fallbacks: u64,
+    errors: u64,
+    /// 拿到回复时能正常解析的比例，还没有回复时为 null
+    parse_rate: Option<f32>,
+    /// 想法跑出猫设被换掉的次数
+    persona_drift: u64,
+}
+
+/// 音效事件：前端按 id 播放对应的音频
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SoundEvent {
+    id: &'static str,
+}
+
+/// 新的一天开始了
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewDayEvent {
+    days_alive: u64,
+    /// 昨天过得怎么样：Neglected / Ordinary / Attentive
+    yesterday: String,
+}
+
+/// Sophie 回应了主人的慢眨眼
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlowBlinkEvent {
+    intimacy: f32,
+}
+
+/// Sophie 照口令做了把戏
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrickEvent {
+    trick: &'static str,
+    behavior: Behavior,
+    /// 这个把戏成功过几次
+    trained: u32,
+}
+
+/// Sophie 正在生气，不领主人的情
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SnubEvent {
+    thought: String,
+}
+
+/// 前端关心的内容有没有明显变化（忽略随机的朝向和数值的微小漂移）
+fn snapshot_changed(prev: &SophieSnapshot, next: &SophieSnapshot) -> bool {
+    let moved = |a: f32, b: f32, eps: f32| (a - b).abs() >= eps;
+    prev.emotion != next.emotion
+        || prev.behavior != next.behavior
+        || prev.belly_up != next.belly_up
+        || prev.begging != next.begging
+        || prev.expression != next.expression
+        || prev.is_sleeping != next.is_sleeping
+        || prev.collapsed != next.collapsed
+        || prev.favorite_x != next.favorite_x
+        || prev.petting_strokes != next.petting_strokes
+        || moved(prev.position_x, next.position_x, 0.001)
+        || moved(prev.energy, next.energy, SNAPSHOT_STAT_EPSILON)
+        || moved(prev.hunger, next.hunger, SNAPSHOT_STAT_EPSILON)
+        || moved(prev.sleepiness, next.sleepiness, SNAPSHOT_STAT_EPSILON)
+        || moved(prev.trust, next.trust, SNAPSHOT_STAT_EPSILON)
+        || moved(prev.intimacy, next.intimacy, SNAPSHOT_STAT_EPSILON)
+        || moved(prev.understanding, next.understanding, SNAPSHOT_STAT_EPSILON)
+        || moved(prev.emotion_intensity, next.emotion_intensity, 0.05)
+        || prev.minutes_since_interaction / 10 != next.minutes_since_interaction / 10
+}
+
+/// 生成快照，并把这次决定的行为和原因记到 AppState 里
+fn make_snapshot(app_state: &AppState, sophie: &SophieState) -> SophieSnapshot {
+    let hour = chrono_hour();
+    let (beh, reason) = decide_behavior_with_reason(sophie, hour);
+    *lock_or_recover(&app_state.last_behavior, "last_behavior") = (beh, reason);
+    // flip_direction = 朝左；想亲近时多半朝最喜欢的位置（通常是主人所在处）走，
+    // 烦躁或自己去找吃的时多半背对主人走开
+    let flip = beh.may_change_direction() && behavior::choose_flip(sophie, reason, rng::rand_f32());
+    SophieSnapshot {
+        energy: sophie.physiological.energy,
+        hunger: sophie.physiological.hunger,
+        sleepiness: sophie.physiological.sleepiness,
+        sleep_debt: sophie.physiological.sleep_debt,
+        emotion: sophie.emotion.id().to_string(),
+        emotion_intensity: sophie.emotion_intensity,
+        trust: sophie.relationship.trust,
+        intimacy: sophie.relationship.intimacy,
+        understanding: sophie.relationship.understanding,
+        is_sleeping: sophie.is_sleeping,
+        collapsed: sophie.collapsed,
+        behavior: beh,
+        flip_direction: flip,
+        belly_up: reason == behavior::BehaviorReason::ShowBelly,
+        begging: reason == behavior::BehaviorReason::Begging,
+        expression: behavior::expression::expression(sophie),
+        minutes_since_interaction: sophie.minutes_since_interaction(),
+        position_x: sophie.position.x,
+        favorite_x: sophie.position.favorite_x,
+        petting_strokes: sophie.petting_strokes(),
+        personality: sophie.personality.clone(),
+    }
+}
+
+// ── Tauri Commands ──────────────────────────────────────────
+
+#[tauri::command]
+fn get_sophie_state(app_state: State<AppState>) -> Result<SophieSnapshot, String> {
+    guard_command("get_sophie_state", || {
+        let sophie = lock_or_recover(&app_state.sophie, "sophie");
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+#[tauri::command]
+fn get_stats(app_state: State<AppState>) -> Result<StatsSnapshot, String> {
+    guard_command("get_stats", || {
+        let stats = lock_or_recover(&app_state.sophie, "sophie").stats.clone();
+
+        flush_pending_memories(&app_state);
+        let days = lock_or_recover(&app_state.memory, "memory").interaction_days(config::utc_offset_secs());
+        let now = unix_now();
+        let today = state::local_day(now);
+        let (streak_days, longest_streak_days) = state::stats::compute_streak(&days, today);
+
+        StatsSnapshot {
+            total_feeds: stats.feeds,
+            total_clicks: stats.clicks,
+            total_conversations: stats.conversations,
+            days_alive: stats.days_alive(now),
+            streak_days,
+            longest_streak_days,
+        }
+    })
+}
+
+#[tauri::command]
+fn get_llm_usage(app_state: State<AppState>) -> LlmUsageSnapshot {
+    let usage = lock_or_recover(&app_state.llm_usage, "llm_usage");
+    LlmUsageSnapshot {
+        total_prompt_tokens: usage.prompt_tokens,
+        total_completion_tokens: usage.completion_tokens,
+        call_count: usage.call_count,
+        avg_latency_ms: usage.avg_latency_ms(),
+        unrecognized_actions: usage.unrecognized_actions,
+    }
+}
+
+/// 思考/言语调用的成功、保底、出错次数（解析率太低说明模型跟不上 JSON 格式），以及跑出猫设的次数
+#[tauri::command]
+fn get_llm_health(app_state: State<AppState>) -> LlmHealthSnapshot {
+    let health = lock_or_recover(&app_state.llm_health, "llm_health");
+    LlmHealthSnapshot {
+        total_calls: health.calls,
+        parsed: health.parsed,
+        fallbacks: health.fallbacks,
+        errors: health.errors,
+        parse_rate: health.parse_rate(),
+        persona_drift: health.drift,
+    }
+}
+
+/// 切换离线模式，返回切换后的状态
+#[tauri::command]
+fn set_offline(app_state: State<AppState>, enabled: bool) -> bool {
+    app_state.offline.store(enabled, Ordering::SeqCst);
+    let flag = if enabled { "1" } else { "0" };
+    let _ = lock_or_recover(&app_state.memory, "memory").save_state("offline", flag);
+    log::info!("Offline mode {}", if enabled { "enabled" } else { "disabled" });
+    enabled
+}
+
+/// 开机启动：登记或取消，返回系统里实际生效的状态（系统拒绝时返回错误）
+#[tauri::command]
+fn set_autostart(app: tauri::AppHandle, app_state: State<AppState>, enabled: bool) -> Result<bool, String> {
+    let mem = lock_or_recover(&app_state.memory, "memory");
+    let enabled = autostart::set(&app, &mem, enabled)?;
+    log::info!("Autostart {}", if enabled { "enabled" } else { "disabled" });
+    Ok(enabled)
+}
+
+/// 是否已设置开机启动
+#[tauri::command]
+fn get_autostart(app: tauri::AppHandle) -> Result<bool, String> {
+    autostart::is_enabled(&app)
+}
+
+/// 静音 / 取消静音，返回切换后的状态
+#[tauri::command]
+fn set_muted(app_state: State<AppState>, muted: bool) -> bool {
+    app_state.muted.store(muted, Ordering::SeqCst);
+    let flag = if muted { "1" } else { "0" };
+    let _ = lock_or_recover(&app_state.memory, "memory").save_state("muted", flag);
+    muted
+}
+
+/// 后台循环（快照广播）的间隔秒数：盯着她看时调快，省电时调慢。
+/// 限制在 2-60 秒，下一轮生效，返回实际生效的值。她自己的时间流速不受影响
+#[tauri::command]
+fn set_update_interval(app_state: State<AppState>, secs: u64) -> u64 {
+    apply_update_interval(&app_state, secs)
+}
+
+fn apply_update_interval(app_state: &AppState, secs: u64) -> u64 {
+    let secs = config::clamp_update_interval(secs);
+    app_state.update_interval_secs.store(secs, Ordering::SeqCst);
+    let _ = lock_or_recover(&app_state.memory, "memory").save_state("update_interval", &secs.to_string());
+    secs
+}
+
+/// 取走还没看过的想法（前端重新获得焦点时补看）
+#[tauri::command]
+fn drain_thoughts(app_state: State<AppState>) -> Vec<QueuedThought> {
+    lock_or_recover(&app_state.recent_thoughts, "recent_thoughts").drain(..).collect()
+}
+
+/// 只看不取
+#[tauri::command]
+fn peek_thoughts(app_state: State<AppState>) -> Vec<QueuedThought> {
+    lock_or_recover(&app_state.recent_thoughts, "recent_thoughts").iter().cloned().collect()
+}
+
+/// 每种情绪的 id、建议颜色和动画活跃度，前端据此渲染
+#[tauri::command]
+fn get_emotion_palette(app_state: State<AppState>) -> Vec<state::palette::EmotionMeta> {
+    app_state.emotion_palette.clone()
+}
+
+/// 可以分享的状态卡片：名字、年龄、心情、最突出的关系值和一句最近的想法
+#[tauri::command]
+fn status_card(app_state: State<AppState>) -> String {
+    let last_bubble = lock_or_recover(&app_state.recent_thoughts, "recent_thoughts")
+        .back()
+        .map(|t| t.text.clone());
+    // 气泡都被取走了就用最近一条想法记忆
+    let thought = last_bubble.or_else(|| {
+        flush_pending_memories(&app_state);
+        lock_or_recover(&app_state.memory, "memory")
+            .recent(20)
+            .into_iter()
+            .find(|m| m.kind == MemoryKind::Thought)
+            .map(|m| m.content)
+    });
+    let sophie = lock_or_recover(&app_state.sophie, "sophie");
+    card::render(&sophie, unix_now(), thought.as_deref(), app_state.config.lang)
+}
+
+/// 一项需求还有多久出现
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NeedEta {
+    need: state::physiological::Need,
+    eta_minutes: u32,
+}
+
+/// 需求预测：最快出现的那项，以及 24 小时内会出现的全部
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NeedsForecast {
+    next: Option<NeedEta>,
+    all: Vec<NeedEta>,
+}
+
+/// 按当前数值和 tick 速率推算她什么时候会饿/困/累（给"她快饿了"之类的提示用）
+#[tauri::command]
+fn predict_needs(app_state: State<AppState>) -> NeedsForecast {
+    let tick_secs = config::STATE_TICK_SECS;
+    let ticks_per_hour = (3600 / tick_secs) as u32;
+    let sophie = lock_or_recover(&app_state.sophie, "sophie");
+    let all: Vec<NeedEta> = sophie
+        .physiological
+        .ticks_until_needs(
+            sophie.is_sleeping,
+            chrono_hour(),
+            &sophie.personality,
+            ticks_per_hour,
+            ticks_per_hour * 24,
+        )
+        .into_iter()
+        .map(|(need, ticks)| NeedEta {
+            need,
+            eta_minutes: (ticks as u64 * tick_secs / 60) as u32,
+        })
+        .collect();
+    NeedsForecast {
+        next: all.first().cloned(),
+        all,
+    }
+}
+
+/// 行为及其决定因素（调试用）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BehaviorReasonResponse {
+    behavior: Behavior,
+    reason: String,
+    explanation: String,
+}
+
+/// 她为什么在做这件事：返回最近一次快照里的行为和决定因素
+#[tauri::command]
+fn get_behavior_reason(app_state: State<AppState>) -> BehaviorReasonResponse {
+    let (behavior, reason) = *lock_or_recover(&app_state.last_behavior, "last_behavior");
+    BehaviorReasonResponse {
+        behavior,
+        reason: reason.code().to_string(),
+        explanation: reason.explain(behavior),
+    }
+}
+
+/// 她慢慢形成的口味（给 UI 展示）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreferencesSnapshot {
+    favorite_food: Option<&'static str>,
+    favorite_toy: Option<String>,
+    foods: Vec<state::preferences::Taste>,
+    toys: Vec<state::preferences::Taste>,
+}
+
+/// 她喜欢什么：最喜欢的食物/玩具和各自的累计分数
+#[tauri::command]
+fn get_preferences(app_state: State<AppState>) -> PreferencesSnapshot {
+    let sophie = lock_or_recover(&app_state.sophie, "sophie");
+    let prefs = &sophie.preferences;
+    PreferencesSnapshot {
+        favorite_food: prefs.favorite_food().map(|f| f.as_str()),
+        favorite_toy: prefs.favorite_toy().map(str::to_string),
+        foods: prefs.foods.clone(),
+        toys: prefs.toys.clone(),
+    }
+}
+
+/// 直接设定情绪：调试用，或者"心情礼物"（as_interaction = true 时算一次互动）
+#[tauri::command]
+fn set_emotion(app_state: State<AppState>, emotion: String, as_interaction: bool) -> Result<SophieSnapshot, String> {
+    guard_command("set_emotion", || set_emotion_state(&app_state, &emotion, as_interaction))?
+}
+
+fn set_emotion_state(app_state: &AppState, emotion: &str, as_interaction: bool) -> Result<SophieSnapshot, String> {
+    let emotion: Emotion = emotion.parse()?;
+    let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+    if as_interaction {
+        sophie.record_interaction();
+        remember_interaction(
+            app_state,
+            &sophie,
+            MemoryKind::Interaction,
+            &format!("主人让我变得{:?}", emotion),
+            0.4,
+        );
+    }
+    sophie.set_emotion(emotion);
+    mark_dirty(app_state);
+    Ok(make_snapshot(app_state, &sophie))
+}
+
+/// 告诉她外面的天气和季节（不认识的值当作未知）
+#[tauri::command]
+fn set_environment(app_state: State<AppState>, weather: String, season: String) -> Result<SophieSnapshot, String> {
+    guard_command("set_environment", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        sophie.environment.weather = Weather::parse(&weather);
+        sophie.environment.season = Season::parse(&season);
+        log::info!("Environment set to {}", sophie.environment.describe());
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 设置黏人程度（0-1）：越高被冷落时越快闹情绪、关系掉得越快
+#[tauri::command]
+fn set_clinginess(app_state: State<AppState>, clinginess: f32) -> Result<SophieSnapshot, String> {
+    guard_command("set_clinginess", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        sophie.personality.set_clinginess(clinginess);
+        log::info!("Clinginess set to {:.2}", sophie.personality.clinginess);
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 设置体力特质：代谢倍率（0.5-1.5）和体力上限（50-100）
+#[tauri::command]
+fn set_energy_profile(app_state: State<AppState>, metabolism: f32, max_energy: f32) -> Result<SophieSnapshot, String> {
+    guard_command("set_energy_profile", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        sophie.personality.set_energy_profile(metabolism, max_energy);
+        let cap = sophie.personality.energy_cap();
+        sophie.physiological.energy = sophie.physiological.energy.min(cap);
+        log::info!(
+            "Energy profile set to metabolism {:.2}, max energy {:.0}",
+            sophie.personality.metabolism,
+            sophie.personality.max_energy
+        );
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 前端上报主人的键鼠活动，用来区分"在但不理我"和"不在"
+#[tauri::command]
+fn report_user_activity(app_state: State<AppState>, active: bool) {
+    lock_or_recover(&app_state.sophie, "sophie").report_user_activity(active);
+}
+
+/// 窗口置顶
+#[tauri::command]
+fn set_always_on_top(app: tauri::AppHandle, app_state: State<AppState>, enabled: bool) -> Result<(), String> {
+    window::sophie_window(&app)?
+        .set_always_on_top(enabled)
+        .map_err(|e| format!("Failed to set always-on-top: {}", e))?;
+    update_window_prefs(&app_state, |prefs| prefs.always_on_top = enabled)
+}
+
+/// 点击穿透：开启后窗口不再接收鼠标事件（从托盘菜单恢复）
+#[tauri::command]
+fn set_click_through(app: tauri::AppHandle, app_state: State<AppState>, enabled: bool) -> Result<(), String> {
+    window::sophie_window(&app)?
+        .set_ignore_cursor_events(enabled)
+        .map_err(|e| format!("Failed to set click-through: {}", e))?;
+    update_window_prefs(&app_state, |prefs| prefs.click_through = enabled)
+}
+
+/// 窗口透明度（0.2-1.0），返回实际生效的值
+#[tauri::command]
+fn set_opacity(app: tauri::AppHandle, app_state: State<AppState>, opacity: f32) -> Result<f32, String> {
+    window::sophie_window(&app)?;
+    let opacity = window::clamp_opacity(opacity);
+    update_window_prefs(&app_state, |prefs| prefs.opacity = opacity)?;
+    let _ = app.emit("sophie-opacity", opacity);
+    Ok(opacity)
+}
+
+/// 从托盘重新显示窗口时要不要打招呼
+#[tauri::command]
+fn set_greet_on_show(app_state: State<AppState>, enabled: bool) -> Result<(), String> {
+    update_window_prefs(&app_state, |prefs| prefs.greet_on_show = enabled)
+}
+
+/// 窗口重新显示：按隐藏了多久和当前关系打个招呼（偏好里关掉了就不说）
+fn greet_on_show(handle: &tauri::AppHandle) {
+    let state = handle.state::<AppState>();
+    let hidden_at = state.hidden_at.swap(0, Ordering::SeqCst);
+    if hidden_at == 0 || !lock_or_recover(&state.window_prefs, "window_prefs").greet_on_show {
+        return;
+    }
+    let hidden_secs = unix_now().saturating_sub(hidden_at);
+    let greeting = {
+        let sophie = lock_or_recover(&state.sophie, "sophie");
+        offline::greeting_thought(&sophie, hidden_secs, state.config.lang)
+            .map(|text| (text, thought_weight(&sophie, true)))
+    };
+    if let Some((text, weight)) = greeting {
+        emit_thought(handle, text, weight);
+    }
+}
+
+/// 免打扰设置和现在是否生效
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DndStatus {
+    #[serde(flatten)]
+    prefs: dnd::DndPrefs,
+    active: bool,
+}
+
+fn dnd_status(app_state: &AppState) -> DndStatus {
+    let dnd = lock_or_recover(&app_state.dnd, "dnd");
+    DndStatus {
+        prefs: dnd.prefs.clone(),
+        active: dnd.is_active(chrono_hour()),
+    }
+}
+
+/// 修改并保存免打扰设置
+fn update_dnd(app_state: &AppState, f: impl FnOnce(&mut dnd::DndPrefs)) -> Result<DndStatus, String> {
+    {
+        let mut dnd = lock_or_recover(&app_state.dnd, "dnd");
+        f(&mut dnd.prefs);
+        let mem = lock_or_recover(&app_state.memory, "memory");
+        dnd.prefs.save(&mem)?;
+    }
+    Ok(dnd_status(app_state))
+}
+
+/// 手动开关免打扰：期间不冒想法气泡、不出声，状态照常变化
+#[tauri::command]
+fn set_dnd(app_state: State<AppState>, enabled: bool) -> Result<DndStatus, String> {
+    update_dnd(&app_state, |prefs| prefs.manual = enabled)
+}
+
+/// 定时免打扰：本地钟点 [start_hour, end_hour)，可以跨零点；两个都不传就取消定时。
+/// `calm_down` 为 true 时免打扰期间她也会安静下来
+#[tauri::command]
+fn set_dnd_schedule(
+    app_state: State<AppState>,
+    start_hour: Option<u32>,
+    end_hour: Option<u32>,
+    calm_down: bool,
+) -> Result<DndStatus, String> {
+    let schedule = match (start_hour, end_hour) {
+        (Some(start), Some(end)) if start < 24 && end < 24 => Some(dnd::DndWindow::new(start, end)),
+        (None, None) => None,
+        _ => return Err("DND schedule needs both start and end hours in 0-23".to_string()),
+    };
+    update_dnd(&app_state, |prefs| {
+        prefs.schedule = schedule;
+        prefs.calm_down = calm_down;
+    })
+}
+
+#[tauri::command]
+fn get_dnd(app_state: State<AppState>) -> DndStatus {
+    dnd_status(&app_state)
+}
+
+/// 修改并保存窗口偏好
+fn update_window_prefs(app_state: &AppState, f: impl FnOnce(&mut window::WindowPrefs)) -> Result<(), String> {
+    let mut prefs = lock_or_recover(&app_state.window_prefs, "window_prefs");
+    f(&mut prefs);
+    let mem = lock_or_recover(&app_state.memory, "memory");
+    prefs.save(&mem)
+}
+
+/// 导出心情日志到文件（format: "csv" / "json"），返回导出的条数
+#[tauri::command]
+fn export_mood_log(app_state: State<AppState>, path: String, format: String) -> Result<usize, String> {
+    let format = memory::mood::ExportFormat::parse(&format)?;
+    let samples = lock_or_recover(&app_state.memory, "memory").mood_log();
+    let text = memory::mood::render(&samples, format)?;
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(samples.len())
+}
+
+/// 最近 `days` 天（2-365）的关系走势
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RelationshipTrend {
+    points: Vec<memory::trend::RelationshipPoint>,
+    direction: memory::trend::Trend,
+    /// "上升" / "平稳" / "下降"
+    label: &'static str,
+}
+
+/// 羁绊是在变深还是在变淡：每天的关系采样和整体走向
+#[tauri::command]
+fn get_relationship_trend(app_state: State<AppState>, days: u32) -> RelationshipTrend {
+    let days = days.clamp(2, 365) as i64;
+    let since = state::local_day(unix_now()) - days + 1;
+    let points = lock_or_recover(&app_state.memory, "memory").relationship_since(since);
+    let direction = memory::trend::classify(&points);
+    RelationshipTrend {
+        points,
+        direction,
+        label: direction.label(),
+    }
+}
+
+/// 搜索记忆（最多 50 条，能用全文索引时按相关度排序）
+#[tauri::command]
+fn search_memories(app_state: State<AppState>, query: String, limit: usize) -> Vec<memory::Memory> {
+    flush_pending_memories(&app_state);
+    lock_or_recover(&app_state.memory, "memory").search(&query, limit.min(50))
+}
+
+/// 各类记忆的条数和最新时间
+#[tauri::command]
+fn memory_kind_stats(app_state: State<AppState>) -> Vec<memory::prune::KindStat> {
+    flush_pending_memories(&app_state);
+    lock_or_recover(&app_state.memory, "memory").kind_stats()
+}
+
+/// 删除某类在 `older_than_ts`（秒）之前的记忆，返回删除条数；事实和总结不能删
+#[tauri::command]
+fn delete_memories(app_state: State<AppState>, kind: String, older_than_ts: u64) -> Result<usize, String> {
+    let kind: memory::MemoryKind = kind.parse()?;
+    flush_pending_memories(&app_state);
+    lock_or_recover(&app_state.memory, "memory").delete_older_than(kind, older_than_ts)
+}
+
+/// 重新开始：只有 confirm == "RESET" 才生效，旧状态和记忆会按时间戳归档
+#[tauri::command]
+fn reset_sophie(app_state: State<AppState>, confirm: String) -> Result<SophieSnapshot, String> {
+    guard_command("reset_sophie", || reset_state(&app_state, &confirm))?
+}
+
+fn reset_state(app_state: &AppState, confirm: &str) -> Result<SophieSnapshot, String> {
+    if confirm != RESET_CONFIRM_TOKEN {
+        return Err("Confirmation token mismatch, nothing was reset".to_string());
+    }
+
+    let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+    flush_pending_memories(app_state);
+
+    let now = unix_now();
+    let fresh = SophieState::new();
+    {
+        let mut mem = lock_or_recover(&app_state.memory, "memory");
+        let old_json = serde_json::to_string(&*sophie).map_err(|e| format!("Serialize error: {}", e))?;
+        mem.save_state(&format!("sophie_archive_{}", now), &old_json)?;
+        let archived = mem.archive_memories(now)?;
+        let fresh_json = serde_json::to_string(&fresh).map_err(|e| format!("Serialize error: {}", e))?;
+        mem.save_state("sophie", &fresh_json)?;
+        log::info!("Sophie reset, archived {} memories under {}", archived, now);
+    }
+
+    *sophie = fresh;
+    Ok(make_snapshot(app_state, &sophie))
+}
+
+/// 导出完整存档（状态 + 全部记忆），返回导出的记忆条数
+#[tauri::command]
+fn export_save(app_state: State<AppState>, path: String) -> Result<usize, String> {
+    let sophie = lock_or_recover(&app_state.sophie, "sophie");
+    flush_pending_memories(&app_state);
+    let state = serde_json::to_value(&*sophie).map_err(|e| format!("Serialize error: {}", e))?;
+    drop(sophie);
+    let memories = lock_or_recover(&app_state.memory, "memory").all_memories();
+    let save = memory::save::SaveFile::new(state, memories);
+    let text = serde_json::to_string_pretty(&save).map_err(|e| format!("Serialize error: {}", e))?;
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(save.memories.len())
+}
+
+/// 导入存档，替换当前的 Sophie；旧数据按时间戳归档（和 reset_sophie 一样）
+#[tauri::command]
+fn import_save(app_state: State<AppState>, path: String) -> Result<SophieSnapshot, String> {
+    guard_command("import_save", || -> Result<SophieSnapshot, String> {
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let save = memory::save::SaveFile::parse(&text)?;
+        let mut imported = state::migrate::from_value(save.state)
+            .map_err(|e| format!("Invalid state in save file: {}", e))?;
+        imported.sanitize();
+        let state_json = serde_json::to_string(&imported).map_err(|e| format!("Serialize error: {}", e))?;
+
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        flush_pending_memories(&app_state);
+        let now = unix_now();
+        let count = lock_or_recover(&app_state.memory, "memory").restore(&state_json, &save.memories, now)?;
+        log::info!("Imported save from {} ({} memories), previous data archived under {}", path, count, now);
+
+        *sophie = imported;
+        // 还在路上的言语响应属于旧的 Sophie，作废
+        next_speech_seq(&app_state.speech_seq);
+        Ok(make_snapshot(&app_state, &sophie))
+    })?
+}
+
+#[tauri::command]
+fn click_sophie(app_state: State<AppState>, app_handle: tauri::AppHandle) -> Result<SophieSnapshot, String> {
+    guard_command("click_sophie", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        if sophie.is_snubbing() {
+            let event = snub(&app_state, &mut sophie, "主人想摸我，我还在生气，躲开了");
+            let _ = app_handle.emit("sophie-snub", &event);
+            return make_snapshot(&app_state, &sophie);
+        }
+        sophie.record_interaction();
+        sophie.stats.record_click();
+
+        let outcome = sophie.pet();
+        if let Some(sound) = sound::petting_sound(&sophie, outcome) {
+            emit_sound(&app_state, &app_handle, sound);
+        }
+        match outcome {
+            PetOutcome::Enjoying(_) => {
+                sophie.position.on_positive_interaction();
+                remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人点了我", 0.3);
+            }
+            PetOutcome::Overstimulated => {
+                emit_thought(&app_handle, "够了".to_string(), thought_weight(&sophie, true));
+                remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人摸个没完，我拍开了", 0.5);
+            }
+        }
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 用光标逗她：`x`/`y` 是光标在屏幕上的逻辑位置（0-1），返回她有没有去追
+#[tauri::command]
+fn chase_cursor(app_state: State<AppState>, app_handle: tauri::AppHandle, x: f32, y: f32) -> Result<bool, String> {
+    guard_command("chase_cursor", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return sophie.chase_target().is_some();
+        }
+        if !sophie.chase_cursor(x, y) {
+            return false;
+        }
+        sophie.record_interaction();
+        if sophie.chase.pounces == 1 {
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人拿光标逗我，我扑过去了", 0.3);
+        }
+        // 马上让前端跑起来，不等下一轮广播
+        let _ = app_handle.emit("sophie-update", &make_snapshot(&app_state, &sophie));
+        mark_dirty(&app_state);
+        true
+    })
+}
+
+#[tauri::command]
+fn feed_sophie(app_state: State<AppState>, app_handle: tauri::AppHandle, food: String) -> Result<SophieSnapshot, String> {
+    guard_command("feed_sophie", || {
+        let (snapshot, snubbed) = feed_state(&app_state, FoodType::parse(&food));
+        if let Some(event) = snubbed {
+            let _ = app_handle.emit("sophie-snub", &event);
+        }
+        snapshot
+    })
+}
+
+/// 喂食；她正在生气时不领情，返回要发给前端的 `SnubEvent`
+fn feed_state(app_state: &AppState, food: FoodType) -> (SophieSnapshot, Option<SnubEvent>) {
+    let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+    if !accept_interaction(app_state) {
+        return (make_snapshot(app_state, &sophie), None);
+    }
+    if sophie.is_snubbing() {
+        let content = format!("主人给我{}，我还在生气，没理", food.label());
+        let event = snub(app_state, &mut sophie, &content);
+        return (make_snapshot(app_state, &sophie), Some(event));
+    }
+    sophie.record_interaction();
+    let intimacy_before = sophie.relationship.intimacy;
+    let traits = sophie.personality.clone();
+    sophie.relationship.on_positive_interaction(&traits);
+    sophie.position.on_positive_interaction();
+    sophie.feed(food);
+    // 哪种食物让她更亲近，慢慢就成了她的口味
+    let gain = sophie.relationship.intimacy - intimacy_before;
+    sophie.preferences.record_food(food, gain);
+
+    remember_interaction(
+        app_state,
+        &sophie,
+        MemoryKind::Interaction,
+        &format!("主人给我喂了{}（{}）", food.label(), food.as_str()),
+        food.memory_weight(),
+    );
+
+    mark_dirty(app_state);
+    (make_snapshot(app_state, &sophie), None)
+}
+
+/// 给 Sophie 看一个玩具：新玩具让她好奇/想玩，同一个玩具看多了就没兴趣
+#[tauri::command]
+fn show_toy(app_state: State<AppState>, app_handle: tauri::AppHandle, kind: String) -> Result<SophieSnapshot, String> {
+    guard_command("show_toy", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        sophie.record_interaction();
+        let novelty = sophie.show_toy(&kind);
+        let kind = state::toys::normalize_kind(&kind);
+
+        if novelty >= 0.6 && rng::rand_f32() < 0.5 {
+            emit_thought(&app_handle, "那是什么？".to_string(), thought_weight(&sophie, true));
+        }
+        let content = if novelty >= 0.3 {
+            format!("主人给我看了{}", kind)
+        } else {
+            format!("主人又拿出{}，看腻了", kind)
+        };
+        remember_interaction(&app_state, &sophie, MemoryKind::Interaction, &content, 0.2 + 0.3 * novelty);
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 安抚 Sophie：难过或烦躁时慢慢让她平静下来，信任不够时会被拒绝
+#[tauri::command]
+fn comfort_sophie(app_state: State<AppState>, app_handle: tauri::AppHandle) -> Result<SophieSnapshot, String> {
+    guard_command("comfort_sophie", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        let outcome = sophie.comfort();
+        sophie.record_interaction();
+
+        let (content, weight) = match outcome {
+            ComfortOutcome::NotNeeded => ("主人摸摸我，说没事", 0.2),
+            ComfortOutcome::Easing => ("主人在安慰我，好一点了", 0.4),
+            ComfortOutcome::Soothed => ("主人安慰了我，心情平静下来了", 0.7),
+            ComfortOutcome::Rebuffed => {
+                emit_thought(&app_handle, "...".to_string(), thought_weight(&sophie, true));
+                ("主人想安慰我，我扭头走开了", 0.4)
+            }
+        };
+        remember_interaction(&app_state, &sophie, MemoryKind::Interaction, content, weight);
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 哄 Sophie 睡觉
+#[tauri::command]
+fn put_to_sleep(app_state: State<AppState>) -> Result<SophieSnapshot, String> {
+    guard_command("put_to_sleep", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        sophie.put_to_sleep();
+
+        remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人哄我睡觉", 0.4);
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 叫醒 Sophie
+#[tauri::command]
+fn wake_up(app_state: State<AppState>, app_handle: tauri::AppHandle) -> Result<SophieSnapshot, String> {
+    guard_command("wake_up", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        let irritated = sophie.wake_up();
+        if sophie.is_groggy() && !irritated {
+            emit_thought(&app_handle, "...嗯".to_string(), thought_weight(&sophie, true));
+        }
+
+        if irritated {
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "正困的时候被主人叫醒了", 0.6);
+        } else {
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人把我叫醒了", 0.3);
+        }
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 主人对 Sophie 慢眨眼：信任足够时她会眨回来，否则只是瞥一眼
+#[tauri::command]
+fn slow_blink_at_sophie(app_state: State<AppState>, app_handle: tauri::AppHandle) -> Result<SophieSnapshot, String> {
+    guard_command("slow_blink_at_sophie", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        sophie.record_interaction();
+
+        if sophie.relationship.will_slow_blink() {
+            sophie.relationship.on_slow_blink();
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人对我慢眨眼，我也眨了回去", 0.7);
+            let _ = app_handle.emit("sophie-slow-blink", &SlowBlinkEvent {
+                intimacy: sophie.relationship.intimacy,
+            });
+        } else {
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人对我眨眼", 0.3);
+        }
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 外部发生的事（番茄钟结束之类）：记进记忆，她想事情时能提到；发 sophie-notify 事件给前端
+#[tauri::command]
+fn notify_event(app_state: State<AppState>, app_handle: tauri::AppHandle, event: String) -> Result<SophieSnapshot, String> {
+    guard_command("notify_event", || -> Result<SophieSnapshot, String> {
+        let event = event.trim().to_string();
+        if event.is_empty() {
+            return Err("Event is empty".to_string());
+        }
+        let sophie = lock_or_recover(&app_state.sophie, "sophie");
+        let content = format!("主人那边：{}", event);
+        remember_interaction(&app_state, &sophie, MemoryKind::Interaction, &content, 0.3);
+        let _ = app_handle.emit("sophie-notify", &event);
+        Ok(make_snapshot(&app_state, &sophie))
+    })?
+}
+
+/// 主人下口令（sit / come / play dead，或 坐下 / 过来 / 装死）：信任和理解不够时她当没听见，
+/// 够了也要看训练进度和心情；照做了发 sophie-trick 事件
+#[tauri::command]
+fn give_command(app_state: State<AppState>, app_handle: tauri::AppHandle, cmd: String) -> Result<SophieSnapshot, String> {
+    let trick = state::tricks::Trick::parse(&cmd).ok_or_else(|| format!("Unknown trick: {}", cmd))?;
+    guard_command("give_command", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        if !accept_interaction(&app_state) {
+            return make_snapshot(&app_state, &sophie);
+        }
+        sophie.record_interaction();
+
+        if sophie.give_command(trick, rng::rand_f32()) {
+            let behavior = match trick {
+                state::tricks::Trick::Sit => Behavior::Sit,
+                state::tricks::Trick::Come => Behavior::Walk,
+                state::tricks::Trick::PlayDead => Behavior::Sleep,
+            };
+            let _ = app_handle.emit("sophie-trick", &TrickEvent {
+                trick: trick.as_str(),
+                behavior,
+                trained: sophie.tricks.trained(trick),
+            });
+            let content = format!("主人叫我{}，我照做了", trick.label());
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, &content, 0.5);
+        } else {
+            let content = format!("主人叫我{}，我没理", trick.label());
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, &content, 0.2);
+        }
+
+        mark_dirty(&app_state);
+        make_snapshot(&app_state, &sophie)
+    })
+}
+
+/// 用户"说给 Sophie 听" — 使用 LLM 生成响应
+#[tauri::command]
+fn speak_to_sophie(app_state: State<AppState>, app_handle: tauri::AppHandle, message: String) -> Result<SophieSnapshot, String> {
+    guard_command("speak_to_sophie", || {
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        sophie.record_interaction();
+        sophie.relationship.on_conversation();
+        sophie.intensify(0.1);
+        sophie.stats.record_conversation();
+
+        // 主人的自我介绍：记成长期事实，越来越了解主人
+        let facts = memory::facts::extract_facts(&message);
+        if !facts.is_empty() {
+            let mem = lock_or_recover(&app_state.memory, "memory");
+            for fact in &facts {
+                match mem.remember_fact(fact) {
+                    Ok(()) => sophie.relationship.on_learned_fact(),
+                    Err(e) => log::warn!("Failed to remember fact: {}", e),
+                }
+            }
+        }
+
+        // 叫到她的名字了吗（刚学到的名字也算）
+        let cat_name = lock_or_recover(&app_state.memory, "memory").cat_name();
+        let called = offline::mentions_name(&message, cat_name.as_deref());
+
+        // 保存记忆（连同缓冲区里的互动一起写入，保证 prompt 里的记忆是最新的）
+        remember_interaction(&app_state, &sophie, MemoryKind::UserSpeech, &format!("主人说：{}", message), 0.7);
+        flush_pending_memories(&app_state);
+        // 越了解主人，回应时联想到的记忆越多
+        let memory_budget = sophie.relationship.understanding_tier().memory_budget();
+        let recent_memories = lock_or_recover(&app_state.memory, "memory").prompt_memories(memory_budget);
+
+        let seq = next_speech_seq(&app_state.speech_seq);
+
+        // 没有 LLM：用规则立即回应
+        if !llm_enabled(&app_state) {
+            let mut result = rule_based_speech(&sophie, &message);
+            if called && offline::answer_to_name(&mut result, &sophie.relationship, rng::rand_f32()) {
+                log::info!("Heard her name, answering with {}", result.action);
+            }
+            if let Some(delta) = result.relationship_delta {
+                sophie.relationship.on_speech_tone(delta);
+            }
+            let snapshot = make_snapshot(&app_state, &sophie);
+            drop(sophie);
+            mark_dirty(&app_state);
+            emit_speech_response(&app_handle, seq, result);
+            return snapshot;
+        }
+
+        // 异步调用 LLM
+        let emotion = sophie.emotion;
+        let temperature = app_state.config.speech.temperature_for(emotion, sophie.emotion_intensity);
+        let relationship = sophie.relationship.clone();
+        let environment = sophie.environment.describe();
+        let behavior_str = format!("{:?}", decide_behavior(&sophie, chrono_hour()));
+        let snapshot = make_snapshot(&app_state, &sophie);
+        drop(sophie); // 释放锁
+        mark_dirty(&app_state);
+
+        let llm = &app_state.llm;
+        let messages = llm::build_speech_response_prompt(
+            &app_state.prompts,
+            &message,
+            emotion,
+            &relationship,
+            &behavior_str,
+            &environment,
+            &recent_memories,
+        );
+
+        let handle = app_handle.clone();
+        let llm_client = LlmClient::new(llm.api_key().to_string())
+            .with_json_mode(app_state.config.llm_json_mode);
+        let params = app_state.config.speech;
+        let slots = Arc::clone(&app_state.llm_slots);
+
+        app_state.tokio_rt.spawn(async move {
+            // 名额满了就稍等；等不到就先"嗯..."一声，不再排队
+            let wait = Duration::from_secs(config::SPEECH_QUEUE_WAIT_SECS);
+            let _permit = match tokio::time::timeout(wait, slots.acquire_owned()).await {
+                Ok(Ok(permit)) => permit,
+                _ => {
+                    log::warn!("LLM busy, dropping speech request #{}", seq);
+                    emit_speech_response(&handle, seq, llm::SpeechResult {
+                        action: "glance".to_string(),
+                        thought: Some("嗯...".to_string()),
+                        emotion_change: None,
+                        relationship_delta: None,
+                    });
+                    return;
+                }
+            };
+            let result = llm_client.chat(messages, params.max_tokens, temperature).await;
+
+            // 期间主人又说了新的话：这条响应已经过时
+            let superseded = handle
+                .try_state::<AppState>()
+                .is_some_and(|s| speech_superseded(&s.speech_seq, seq));
+            if superseded {
+                log::info!("Dropping stale speech response #{}", seq);
+                return;
+            }
+
+            match result {
+                Ok(reply) => {
+                    record_llm_usage(&handle, &reply);
+                    log::info!("LLM speech response: {}", reply.content);
+                    let (mut result, outcome) = llm::parse_speech_response(&reply.content);
+                    record_llm_outcome(&handle, outcome);
+                    if called && offline::answer_to_name(&mut result, &relationship, rng::rand_f32()) {
+                        log::info!("Heard her name, answering with {}", result.action);
+                    }
+                    // LLM 没给语气判断时退回关键词
+                    let delta = result
+                        .relationship_delta
+                        .unwrap_or_else(|| offline::message_sentiment(&message));
+                    if let Some(state) = handle.try_state::<AppState>() {
+                        lock_or_recover(&state.sophie, "sophie").relationship.on_speech_tone(delta);
+                        mark_dirty(&state);
+                    }
+                    result.thought = result.thought.map(|thought| keep_in_character(&handle, thought));
+                    emit_speech_response(&handle, seq, result);
+                }
+                Err(e) => {
+                    record_llm_outcome(&handle, llm::CallOutcome::Error);
+                    log_llm_error("speech", &e);
+                }
+            }
+        });
+
+        snapshot
+    })
+}
+
+// ── Helpers ─────────────────────────────────────────────────
+
+/// 是否可以调用 LLM（有 API key 且不在离线模式）
+fn llm_enabled(app_state: &AppState) -> bool {
+    app_state.llm.is_available() && !app_state.offline.load(Ordering::SeqCst)
+}
+
+/// 映射 LLM / 规则的 action 到 Behavior；认不出的记一笔并当作待机
+fn action_to_behavior(handle: &tauri::AppHandle, action: &str) -> Behavior {
+    behavior::parse_action(action).unwrap_or_else(|| {
+        log::warn!("Unrecognized action from LLM: {:?}", action);
+        if let Some(state) = handle.try_state::<AppState>() {
+            lock_or_recover(&state.llm_usage, "llm_usage").unrecognized_actions += 1;
+        }
+        Behavior::Idle
+    })
+}
+
+/// 记录 LLM 错误：暂时性的问题只是警告，鉴权/解析类错误需要人处理
+fn log_llm_error(context: &str, e: &llm::LlmError) {
+    if e.is_retryable() {
+        log::warn!("LLM {} failed (transient): {}", context, e);
+    } else {
+        log::error!("LLM {} failed: {}", context, e);
     }
 }
 
-// ── Tauri Commands ──────────────────────────────────────────
+/// 记一次思考/言语调用的结果
+fn record_llm_outcome(handle: &tauri::AppHandle, outcome: llm::CallOutcome) {
+    if let Some(state_ref) = handle.try_state::<AppState>() {
+        lock_or_recover(&state_ref.llm_health, "llm_health").record(outcome);
+    }
+}
 
-#[tauri::command]
-fn get_sophie_state(app_state: State<AppState>) -> SophieSnapshot {
-    let sophie = app_state.sophie.lock().unwrap();
-    make_snapshot(&sophie)
+/// LLM 给的想法跑出了猫设（像助手一样说话）就丢掉，换成规则想法，没有就沉默，并记一次
+///
+/// 调用时不能持有 sophie 锁
+fn keep_in_character(handle: &tauri::AppHandle, thought: String) -> String {
+    let state = handle.try_state::<AppState>();
+    let (thought, drifted) = llm::persona::keep_in_character(thought, || {
+        let state = state.as_ref()?;
+        let sophie = lock_or_recover(&state.sophie, "sophie");
+        rule_based_thought(&sophie, chrono_hour(), state.config.lang)
+    });
+    if let (true, Some(state)) = (drifted, &state) {
+        lock_or_recover(&state.llm_health, "llm_health").record_drift();
+    }
+    thought
 }
 
-#[tauri::command]
-fn click_sophie(app_state: State<AppState>) -> SophieSnapshot {
-    let mut sophie = app_state.sophie.lock().unwrap();
-    sophie.record_interaction();
-    sophie.relationship.on_positive_interaction();
+/// 累计一次 LLM 调用的用量
+fn record_llm_usage(handle: &tauri::AppHandle, reply: &llm::ChatReply) {
+    if let Some(state_ref) = handle.try_state::<AppState>() {
+        lock_or_recover(&state_ref.llm_usage, "llm_usage").record(reply);
+    }
+}
+
+/// 发送言语响应事件，有想法时再单独发送想法事件
+fn emit_speech_response(handle: &tauri::AppHandle, seq: u64, result: llm::SpeechResult) {
+    let event = SpeechResponseEvent {
+        seq,
+        behavior: action_to_behavior(handle, &result.action),
+        action: result.action,
+        thought: result.thought.clone(),
+    };
+    let _ = handle.emit("sophie-speech-response", &event);
+
+    if let Some(thought) = result.thought {
+        if !thought.is_empty() && thought != "null" {
+            emit_thought(handle, thought, current_thought_weight(handle, true));
+        }
+    }
+}
+
+/// reset_sophie 需要的确认口令
+const RESET_CONFIRM_TOKEN: &str = "RESET";
+
+/// 数值变化小于这个值不算"变了"，不单独推送
+const SNAPSHOT_STAT_EPSILON: f32 = 1.0;
+/// 即使没有变化，也至少每这么久（秒）推送一次完整快照，让前端重新同步
+const FULL_RESYNC_SECS: u64 = 60;
 
-    if let Ok(mem) = app_state.memory.lock() {
-        let _ = mem.add("interaction", "主人点了我", 0.3);
+/// 心情日志的采样间隔（秒）
+const MOOD_SAMPLE_SECS: u64 = 300;
+
+/// 关系日志的采样间隔（秒）：同一天的采样互相覆盖，只留当天最后的值
+const RELATIONSHIP_SAMPLE_SECS: u64 = 3600;
+
+/// 定期存档的间隔（秒）
+const SAVE_STATE_SECS: u64 = 60;
+
+/// 每隔多久（秒）检查一次要不要压缩旧记忆
+const SUMMARY_CHECK_SECS: u64 = 3600;
+
+/// 每隔多久（秒）看一下主人最近说话的语气
+const CONTAGION_CHECK_SECS: u64 = 300;
+
+/// 规则想法气泡的间隔（秒）
+const RULE_THOUGHT_SECS: u64 = 70;
+
+/// 睡着时每隔多久（秒）看一下要不要做梦
+const DREAM_CHECK_SECS: u64 = 30;
+/// 看最近多久内主人说的话（秒）
+const CONTAGION_WINDOW_SECS: u64 = 2 * 3600;
+/// 至少说了这么多句才算"持续"的情绪
+const CONTAGION_MIN_MESSAGES: usize = 3;
+
+/// 两轮循环之间隔了这么久（秒）就认为电脑休眠/锁屏挂起过
+const PAUSE_DETECT_SECS: u64 = 120;
+
+/// 后台循环的计时：累计跑了多少秒，按秒数判断各项定时任务这一轮到没到点
+/// （循环间隔可以在运行时调整，不能再按轮数数）
+struct LoopTimer {
+    elapsed: u64,
+    previous: u64,
+}
+
+impl LoopTimer {
+    fn new() -> Self {
+        Self { elapsed: 0, previous: 0 }
+    }
+
+    /// 又过了一轮（`secs` 秒）
+    fn advance(&mut self, secs: u64) {
+        self.previous = self.elapsed;
+        self.elapsed += secs;
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.elapsed
+    }
+
+    /// 这一轮跨过了几个 `every_secs` 的整数倍（间隔调长时一轮可能跨过好几个）
+    fn crossings(&self, every_secs: u64) -> u64 {
+        let every = every_secs.max(1);
+        self.elapsed / every - self.previous / every
     }
 
-    make_snapshot(&sophie)
+    fn due(&self, every_secs: u64) -> bool {
+        self.crossings(every_secs) > 0
+    }
 }
 
-#[tauri::command]
-fn feed_sophie(app_state: State<AppState>) -> SophieSnapshot {
-    let mut sophie = app_state.sophie.lock().unwrap();
-    sophie.record_interaction();
-    sophie.physiological.feed();
-    sophie.relationship.on_positive_interaction();
+/// 自己冒出来的想法记忆的基础权重
+const IDLE_THOUGHT_WEIGHT: f32 = 0.3;
+/// 主人互动引起的想法记忆的基础权重
+const USER_THOUGHT_WEIGHT: f32 = 0.5;
+
+/// 被忽视超过这么久，事件模式下会触发一次思考
+const NEGLECT_THINK_MINUTES: u32 = 180;
+
+/// 为一次言语请求领取新序号
+fn next_speech_seq(latest: &AtomicU64) -> u64 {
+    latest.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// 这次请求之后主人是否又说了话（响应已过时）
+fn speech_superseded(latest: &AtomicU64, seq: u64) -> bool {
+    latest.load(Ordering::SeqCst) != seq
+}
 
-    if let Ok(mem) = app_state.memory.lock() {
-        let _ = mem.add("interaction", "主人给我喂食了", 0.6);
+/// 主人最近一段时间说话的平均语气；说得太少返回 None
+fn owner_sentiment(app_state: &AppState) -> Option<f32> {
+    let since = unix_now().saturating_sub(CONTAGION_WINDOW_SECS);
+    let messages = lock_or_recover(&app_state.memory, "memory").speech_since(since);
+    if messages.len() < CONTAGION_MIN_MESSAGES {
+        return None;
     }
+    let total: f32 = messages.iter().map(|m| offline::message_sentiment(m)).sum();
+    Some(total / messages.len() as f32)
+}
 
-    make_snapshot(&sophie)
+/// 显示想法气泡，同时放进想法队列并记成想法记忆（`weight` 由 `thought_weight` 算）
+///
+/// 免打扰时只记进记忆，不显示；离上个气泡太近时只进队列（前端可以补看），不发事件
+fn emit_thought(handle: &tauri::AppHandle, text: String, weight: f32) {
+    if let Some(state) = handle.try_state::<AppState>() {
+        {
+            let mut dnd = lock_or_recover(&state.dnd, "dnd");
+            if dnd.is_active(chrono_hour()) {
+                dnd.suppress();
+                drop(dnd);
+                remember_thought(&state, &text, weight);
+                return;
+            }
+        }
+        queue_thought(&state, &text);
+        remember_thought(&state, &text, weight);
+        if !accept_thought(&state.last_thought_ms, unix_now_ms()) {
+            log::debug!("Thought bubble throttled: {}", text);
+            return;
+        }
+    }
+    let _ = handle.emit("sophie-thought", &ThoughtEvent { text });
 }
 
-/// 用户"说给 Sophie 听" — 使用 LLM 生成响应
-#[tauri::command]
-fn speak_to_sophie(app_state: State<AppState>, app_handle: tauri::AppHandle, message: String) -> SophieSnapshot {
-    let mut sophie = app_state.sophie.lock().unwrap();
+/// 想法放进队列，超出上限时丢掉最旧的
+fn queue_thought(app_state: &AppState, text: &str) {
+    let mut queue = lock_or_recover(&app_state.recent_thoughts, "recent_thoughts");
+    if queue.len() >= THOUGHT_QUEUE_LEN {
+        queue.pop_front();
+    }
+    queue.push_back(QueuedThought { text: text.to_string(), timestamp: unix_now() });
+}
+
+/// 想法气泡限流：离上次发出的气泡不到最小间隔就不发（一下子冒出好几个气泡前端看不过来）
+fn accept_thought(last_thought_ms: &AtomicU64, now_ms: u64) -> bool {
+    accept_after_gap(last_thought_ms, now_ms, MIN_THOUGHT_GAP_MS)
+}
+
+/// 想法记忆的权重：主人引起的想法记得更牢，再按当时的情绪强度放大/缩小
+fn thought_weight(sophie: &SophieState, user_triggered: bool) -> f32 {
+    let base = if user_triggered { USER_THOUGHT_WEIGHT } else { IDLE_THOUGHT_WEIGHT };
+    sophie.imprint_weight(base)
+}
+
+/// 同 `thought_weight`，给没拿着 sophie 锁的地方用（调用时不能持有 sophie 锁）
+fn current_thought_weight(handle: &tauri::AppHandle, user_triggered: bool) -> f32 {
+    handle
+        .try_state::<AppState>()
+        .map(|state| thought_weight(&lock_or_recover(&state.sophie, "sophie"), user_triggered))
+        .unwrap_or(IDLE_THOUGHT_WEIGHT)
+}
+
+/// 记下一条想法；短时间内反复冒出的同一句只记一次
+fn remember_thought(app_state: &AppState, text: &str, weight: f32) {
+    if lock_or_recover(&app_state.stored_thoughts, "stored_thoughts").admit(text, unix_now()) {
+        queue_memory(app_state, MemoryKind::Thought, text, weight);
+    }
+}
+
+/// 互动频率限制：离上次接受的互动不到最小间隔就忽略（猫也只能被摸这么快）
+fn accept_interaction(app_state: &AppState) -> bool {
+    accept_after_gap(&app_state.last_interaction_ms, unix_now_ms(), app_state.config.interaction_min_interval_ms)
+}
+
+/// 离 `last_ms` 记下的上一次至少隔了 `min_gap_ms` 才放行，放行时把 `last_ms` 更新成 `now_ms`
+fn accept_after_gap(last_ms: &AtomicU64, now_ms: u64, min_gap_ms: u64) -> bool {
+    last_ms
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            (now_ms.saturating_sub(last) >= min_gap_ms).then_some(now_ms)
+        })
+        .is_ok()
+}
+
+/// 她在生气：这次互动不带来任何好处，只记下来；返回的事件由调用方发给前端
+fn snub(app_state: &AppState, sophie: &mut SophieState, memory: &str) -> SnubEvent {
     sophie.record_interaction();
-    sophie.relationship.on_conversation();
+    remember_interaction(app_state, sophie, MemoryKind::Interaction, memory, 0.4);
+    mark_dirty(app_state);
+    SnubEvent { thought: "走开。".to_string() }
+}
 
-    // 保存记忆
-    let recent_memories = if let Ok(mem) = app_state.memory.lock() {
-        let _ = mem.add("user_speech", &format!("主人说：{}", message), 0.7);
-        mem.recent_as_text(5)
-    } else {
-        vec![]
-    };
+/// 标记状态需要存档（互动命令调用）
+fn mark_dirty(app_state: &AppState) {
+    app_state.state_dirty.store(true, Ordering::SeqCst);
+}
 
-    // 异步调用 LLM
-    let emotion_str = format!("{:?}", sophie.emotion);
-    let intimacy = sophie.relationship.intimacy;
-    let trust = sophie.relationship.trust;
-    let behavior_str = format!("{:?}", decide_behavior(&sophie, chrono_hour()));
-    let snapshot = make_snapshot(&sophie);
-    drop(sophie); // 释放锁
-
-    let llm = &app_state.llm;
-    let messages = llm::build_speech_response_prompt(
-        &message,
-        &emotion_str,
-        intimacy,
-        trust,
-        &behavior_str,
-        &recent_memories,
-    );
+/// 把 Sophie 的状态写入数据库并清掉 dirty 标记（调用方持有 sophie 锁）
+fn save_sophie_state(app_state: &AppState, sophie: &SophieState) {
+    app_state.state_dirty.store(false, Ordering::SeqCst);
+    match serde_json::to_string(sophie) {
+        Ok(json) => {
+            if let Err(e) = lock_or_recover(&app_state.memory, "memory").save_state("sophie", &json) {
+                log::warn!("Failed to save state: {}", e);
+                mark_dirty(app_state);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize state: {}", e),
+    }
+}
+
+/// 发出音效（静音或免打扰时不发）
+fn emit_sound(app_state: &AppState, handle: &tauri::AppHandle, sound: Sound) {
+    let quiet = lock_or_recover(&app_state.dnd, "dnd").is_active(chrono_hour());
+    if !app_state.muted.load(Ordering::SeqCst) && !quiet {
+        let _ = handle.emit("sophie-sound", &SoundEvent { id: sound.id() });
+    }
+}
+
+/// 执行命令体；万一 panic 就转成错误返回给前端，而不是让整个程序崩掉
+/// （锁被毒化也没关系，`lock_or_recover` 下次照样能用）
+fn guard_command<T>(name: &str, body: impl FnOnce() -> T) -> Result<T, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)).map_err(|panic| {
+        let detail = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        log::error!("Command {} panicked: {}", name, detail);
+        format!("{} failed unexpectedly, please try again", name)
+    })
+}
+
+/// 加锁；锁被 panic 毒化时照样取出数据继续用（数据本身没坏），只记一条警告
+fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        log::warn!("Mutex '{}' was poisoned by a panic, recovering", name);
+        poisoned.into_inner()
+    })
+}
+
+/// 把记忆放入缓冲区，等待批量写入
+fn queue_memory(app_state: &AppState, kind: MemoryKind, content: &str, emotional_weight: f32) {
+    lock_or_recover(&app_state.pending_memories, "pending_memories")
+        .push(PendingMemory::new(kind, content, emotional_weight));
+}
 
-    let handle = app_handle.clone();
-    let llm_client = LlmClient::new(llm.api_key().to_string());
+/// 取最旧的一批记忆交给 LLM 总结，成功后用总结替换它们（拿不到 LLM 名额就下次再说）
+fn summarize_old_memories(app_state: &AppState, handle: &tauri::AppHandle) {
+    let batch = lock_or_recover(&app_state.memory, "memory").due_for_summary();
+    if batch.is_empty() {
+        return;
+    }
+    let Ok(permit) = Arc::clone(&app_state.llm_slots).try_acquire_owned() else {
+        return;
+    };
+    let lines: Vec<String> = batch
+        .iter()
+        .map(memory::Memory::as_prompt_line)
+        .collect();
+    let messages = llm::build_summary_prompt(&app_state.prompts, &lines);
+    let client = LlmClient::new(app_state.llm.api_key().to_string());
+    let params = app_state.config.thinking;
+    let handle = handle.clone();
 
     app_state.tokio_rt.spawn(async move {
-        match llm_client.chat(messages, 200, 0.9).await {
-            Ok(text) => {
-                log::info!("LLM speech response: {}", text);
-                let result = llm::parse_speech_response(&text);
-
-                // 映射 LLM action 到 Behavior
-                let behavior = match result.action.as_str() {
-                    "ignore" => Behavior::Idle,
-                    "glance" | "alert" => Behavior::Alert,
-                    "approach" | "walk" => Behavior::Walk,
-                    "walk_away" | "run" => Behavior::Run,
-                    "sit" => Behavior::Sit,
-                    "sleep" => Behavior::Sleep,
-                    _ => Behavior::Idle,
-                };
-
-                let event = SpeechResponseEvent {
-                    action: result.action,
-                    thought: result.thought.clone(),
-                    behavior,
-                };
-                let _ = handle.emit("sophie-speech-response", &event);
-
-                // 如果有想法，单独发送想法事件
-                if let Some(thought) = result.thought {
-                    if !thought.is_empty() && thought != "null" {
-                        let _ = handle.emit("sophie-thought", &ThoughtEvent { text: thought });
+        let _permit = permit;
+        match client.chat(messages, params.max_tokens, params.temperature).await {
+            Ok(reply) => {
+                record_llm_usage(&handle, &reply);
+                let summary = reply.content.trim();
+                if summary.is_empty() {
+                    return;
+                }
+                if let Some(state) = handle.try_state::<AppState>() {
+                    match lock_or_recover(&state.memory, "memory").replace_with_summary(&batch, summary) {
+                        Ok(()) => log::info!("Summarized {} old memories", batch.len()),
+                        Err(e) => log::warn!("Failed to store memory summary: {}", e),
                     }
                 }
             }
-            Err(e) => {
-                log::error!("LLM speech error: {}", e);
-            }
+            Err(e) => log_llm_error("summary", &e),
         }
     });
+}
 
-    snapshot
+/// 记下一次互动（连同她当时的情绪和在做什么）：权重按情绪放大/缩小，情绪强烈时的事记得更牢
+///
+/// "在做什么"取最近一次快照定下的行为，不重新决策（决策带随机，会和前端看到的不一致）
+fn remember_interaction(app_state: &AppState, sophie: &SophieState, kind: MemoryKind, content: &str, base_weight: f32) {
+    let (behavior, _) = *lock_or_recover(&app_state.last_behavior, "last_behavior");
+    let entry = PendingMemory::new(kind, content, sophie.imprint_weight(base_weight))
+        .with_context(sophie.emotion.id(), behavior.id());
+    lock_or_recover(&app_state.pending_memories, "pending_memories").push(entry);
 }
 
-// ── Helpers ─────────────────────────────────────────────────
+/// 把缓冲区里的记忆在一个事务中写入数据库
+fn flush_pending_memories(app_state: &AppState) {
+    let entries = std::mem::take(&mut *lock_or_recover(&app_state.pending_memories, "pending_memories"));
+    if entries.is_empty() {
+        return;
+    }
+    let result = lock_or_recover(&app_state.memory, "memory").add_many(&entries);
+    if let Err(e) = result {
+        // 放回缓冲区最前面（保持先后顺序），下一轮再写
+        log::error!("Failed to flush {} memories, will retry: {}", entries.len(), e);
+        let mut pending = lock_or_recover(&app_state.pending_memories, "pending_memories");
+        let newer = std::mem::replace(&mut *pending, entries);
+        pending.extend(newer);
+    }
+}
+
+/// 本地自动化接口（`automation` feature）：配了 `AUTOMATION_TOKEN` 才在 127.0.0.1 上监听，
+/// 请求转给对应的命令，返回快照 JSON
+#[cfg(feature = "automation")]
+fn start_automation(handle: tauri::AppHandle) {
+    use automation::Command;
+
+    let Some(cfg) = automation::AutomationConfig::from_env() else {
+        log::info!("AUTOMATION_TOKEN not set, automation endpoint disabled");
+        return;
+    };
+    let listener = match automation::bind(cfg.port) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::warn!("{}", e);
+            return;
+        }
+    };
+    log::info!("Automation endpoint listening on 127.0.0.1:{}", cfg.port);
+
+    std::thread::spawn(move || {
+        automation::serve(listener, cfg.token, |command| {
+            let state = handle.state::<AppState>();
+            let snapshot = match command {
+                Command::Feed { food } => feed_sophie(state, handle.clone(), food)?,
+                Command::Play { toy } => show_toy(state, handle.clone(), toy)?,
+                Command::Notify { event } => notify_event(state, handle.clone(), event)?,
+                Command::GetState => get_sophie_state(state)?,
+            };
+            serde_json::to_string(&snapshot).map_err(|e| format!("Serialize error: {}", e))
+        });
+    });
+}
 
 fn chrono_hour() -> u32 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
-    ((secs / 3600 + 8) % 24) as u32
+    let secs = unix_now() as i64 + config::utc_offset_secs();
+    (secs.rem_euclid(86400) / 3600) as u32
 }
 
-fn chrono_nanos() -> u32 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos()
+fn unix_now() -> u64 {
+    clock::now_unix()
+}
+
+fn unix_now_ms() -> u64 {
+    clock::now_unix() * 1000 + (clock::now_nanos() / 1_000_000) as u64
 }
 
 fn db_path() -> PathBuf {
@@ -234,32 +1699,128 @@ pub fn run() {
         log::warn!("MINIMAX_API_KEY not set, LLM features will be disabled");
     }
 
+    // 时区偏移要在恢复状态（算本地日期）之前设好
+    let config = AppConfig::from_env();
+    config::set_utc_offset(config.utc_offset_secs);
+
     // 打开记忆数据库
-    let memory_store = MemoryStore::open(&db_path())
-        .expect("Failed to open memory database");
+    let memory_store = MemoryStore::open_or_recover(&db_path()).unwrap_or_else(|e| {
+        log::error!("{}; falling back to an in-memory store, nothing will be saved", e);
+        MemoryStore::open_in_memory().expect("Failed to open in-memory database")
+    });
 
     // 尝试恢复 Sophie 状态
-    let sophie = if let Some(state_json) = memory_store.load_state("sophie") {
-        serde_json::from_str::<SophieState>(&state_json).unwrap_or_else(|_| SophieState::new())
+    let mut sophie = if let Some(state_json) = memory_store.load_state("sophie") {
+        state::migrate::load(&state_json).unwrap_or_else(|e| {
+            // 实在读不了才重来，原始数据留一份备份
+            let backup = format!("sophie_unreadable_{}", unix_now());
+            log::error!("{}; starting fresh, old state kept as {}", e, backup);
+            let _ = memory_store.save_state(&backup, &state_json);
+            SophieState::new()
+        })
     } else {
         SophieState::new()
     };
+    sophie.sanitize();
+
+    let window_prefs = window::WindowPrefs::load(&memory_store);
+    let dnd_prefs = dnd::DndPrefs::load(&memory_store);
+
+    let offline = config.offline || memory_store.load_state("offline").as_deref() == Some("1");
+    let muted = memory_store.load_state("muted").as_deref() == Some("1");
+    let update_interval_secs = memory_store
+        .load_state("update_interval")
+        .and_then(|v| v.parse().ok())
+        .map(config::clamp_update_interval)
+        .unwrap_or(config::LOOP_INTERVAL_SECS);
+    if offline {
+        log::info!("Offline mode: LLM calls disabled");
+    }
 
     let tokio_rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
 
     let llm_client = LlmClient::new(api_key.clone());
+    let prompts = llm::PromptTemplates::load(&dirs_for_db().join("prompts"), config.lang);
+    let initial_behavior = decide_behavior_with_reason(&sophie, chrono_hour());
+    let emotion_palette = state::palette::load(&dirs_for_db().join("emotion_palette.json"));
+    let calendar = calendar::Calendar::load(&dirs_for_db().join("special_days.json"), &memory_store);
 
     tauri::Builder::default()
+        .plugin(tauri_plugin_autostart::init(tauri_plugin_autostart::MacosLauncher::LaunchAgent, None))
         .manage(AppState {
             sophie: Mutex::new(sophie),
             memory: Mutex::new(memory_store),
+            pending_memories: Mutex::new(Vec::new()),
             llm: llm_client,
+            prompts,
             tokio_rt,
+            speech_seq: AtomicU64::new(0),
+            llm_usage: Mutex::new(llm::UsageTotals::default()),
+            llm_health: Mutex::new(llm::HealthCounters::default()),
+            offline: AtomicBool::new(offline),
+            window_prefs: Mutex::new(window_prefs.clone()),
+            hidden_at: AtomicU64::new(0),
+            config: config.clone(),
+            last_behavior: Mutex::new(initial_behavior),
+            llm_slots: Arc::new(Semaphore::new(config.llm_max_concurrency)),
+            muted: AtomicBool::new(muted),
+            update_interval_secs: AtomicU64::new(update_interval_secs),
+            dnd: Mutex::new(dnd::Dnd::new(dnd_prefs)),
+            recent_thoughts: Mutex::new(VecDeque::new()),
+            stored_thoughts: Mutex::new(memory::dedup::RecentThoughts::new()),
+            last_interaction_ms: AtomicU64::new(0),
+            last_thought_ms: AtomicU64::new(0),
+            emotion_palette,
+            state_dirty: AtomicBool::new(false),
+            calendar: Mutex::new(calendar),
         })
         .invoke_handler(tauri::generate_handler![
             get_sophie_state,
+            get_stats,
+            get_llm_usage,
+            get_llm_health,
+            get_preferences,
+            status_card,
+            get_emotion_palette,
+            get_behavior_reason,
+            predict_needs,
+            set_offline,
+            set_muted,
+            set_update_interval,
+            set_dnd,
+            set_dnd_schedule,
+            get_dnd,
+            set_autostart,
+            get_autostart,
+            drain_thoughts,
+            peek_thoughts,
+            report_user_activity,
+            set_environment,
+            set_clinginess,
+            set_energy_profile,
+            set_emotion,
+            set_always_on_top,
+            set_click_through,
+            set_opacity,
+            set_greet_on_show,
+            export_mood_log,
+            get_relationship_trend,
+            search_memories,
+            memory_kind_stats,
+            delete_memories,
+            reset_sophie,
+            export_save,
+            import_save,
             click_sophie,
+            chase_cursor,
             feed_sophie,
+            show_toy,
+            comfort_sophie,
+            put_to_sleep,
+            wake_up,
+            slow_blink_at_sophie,
+            notify_event,
+            give_command,
             speak_to_sophie,
         ])
         .setup(move |app| {
@@ -271,11 +1832,22 @@ pub fn run() {
                 )?;
             }
 
+            // ── 恢复窗口偏好 ──
+            if let Err(e) = window_prefs.apply(app.handle()) {
+                log::warn!("Failed to apply window prefs: {}", e);
+            }
+            {
+                let state = app.state::<AppState>();
+                let mem = lock_or_recover(&state.memory, "memory");
+                autostart::restore(app.handle(), &mem);
+            }
+
             // ── 系统托盘 ──
             let show = MenuItem::with_id(app, "show", "显示 Sophie", true, None::<&str>)?;
             let hide = MenuItem::with_id(app, "hide", "隐藏 Sophie", true, None::<&str>)?;
+            let restore_clicks = MenuItem::with_id(app, "restore_clicks", "取消点击穿透", true, None::<&str>)?;
             let quit = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show, &hide, &quit])?;
+            let menu = Menu::with_items(app, &[&show, &hide, &restore_clicks, &quit])?;
 
             TrayIconBuilder::new()
                 .tooltip("Cyber Cat - Sophie")
@@ -286,64 +1858,264 @@ pub fn run() {
                             let _ = w.show();
                             let _ = w.set_focus();
                         }
+                        greet_on_show(app);
                     }
                     "hide" => {
                         if let Some(w) = app.get_webview_window("sophie") {
-                            let _ = w.hide();
+                            if w.hide().is_ok() {
+                                let state = app.state::<AppState>();
+                                // 重复隐藏不刷新：从第一次隐藏算起
+                                let _ = state.hidden_at.compare_exchange(0, unix_now(), Ordering::SeqCst, Ordering::SeqCst);
+                            }
                         }
                     }
+                    "restore_clicks" => {
+                        let state = app.state::<AppState>();
+                        let _ = set_click_through(app.clone(), state, false);
+                    }
                     "quit" => app.exit(0),
                     _ => {}
                 })
                 .build(app)?;
 
+            #[cfg(feature = "automation")]
+            start_automation(app.handle().clone());
+
             // ── 后台生命循环 + AI 思考 ──
             let handle = app.handle().clone();
             let api_key_clone = api_key.clone();
 
             std::thread::spawn(move || {
-                let mut tick_counter: u64 = 0;
-                let rt = tokio::runtime::Runtime::new().unwrap();
+                let mut timer = LoopTimer::new();
+                let mut last_think_at: u64 = 0;
+                let mut last_emotion = Emotion::Calm;
+                let mut was_neglected = false;
+                let mut last_snapshot: Option<SophieSnapshot> = None;
+                let mut last_emit_at: u64 = 0;
+                let mut last_relationship_day: Option<i64> = None;
+                let mut check_ins = offline::CheckInTracker::new();
+                let mut think_gate = llm::throttle::ThinkGate::new();
+                let mut last_loop_at = unix_now();
 
                 loop {
-                    std::thread::sleep(Duration::from_secs(10));
-                    tick_counter += 1;
-
                     let state_ref = handle.state::<AppState>();
+                    // 每轮重新读间隔，`set_update_interval` 下一轮就生效
+                    let interval = state_ref.update_interval_secs.load(Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_secs(interval));
+                    timer.advance(interval);
+
+                    // ── 电脑休眠/锁屏后恢复：挂起的时间不能原样算进去 ──
+                    let now = unix_now();
+                    let elapsed = now.saturating_sub(last_loop_at);
+                    last_loop_at = now;
+                    if elapsed > PAUSE_DETECT_SECS {
+                        let paused = elapsed.saturating_sub(interval);
+                        let skipped = lock_or_recover(&state_ref.sophie, "sophie").absorb_pause(paused);
+                        log::info!("Loop was suspended for {}s, ignored {}s of it", paused, skipped);
+                        if skipped > 0 {
+                            mark_dirty(&state_ref);
+                        }
+                    }
 
-                    // ── 每 30 秒 tick 生理/情绪/关系 ──
-                    if tick_counter % 3 == 0 {
-                        let mut sophie = state_ref.sophie.lock().unwrap();
-                        sophie.tick();
+                    // ── 批量写入缓冲的互动记忆 ──
+                    flush_pending_memories(&state_ref);
 
-                        // 持久化状态
-                        if tick_counter % 6 == 0 {
-                            if let Ok(json) = serde_json::to_string(&*sophie) {
-                                if let Ok(mem) = state_ref.memory.lock() {
-                                    let _ = mem.save_state("sophie", &json);
-                                }
+                    // ── 免打扰：要她安静时闹腾的情绪直接平静；结束时有憋着的话就补一句 ──
+                    {
+                        let hour = chrono_hour();
+                        let mut sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let mut dnd = lock_or_recover(&state_ref.dnd, "dnd");
+                        if dnd.is_active(hour) && dnd.prefs.calm_down && sophie.quiet_down() {
+                            mark_dirty(&state_ref);
+                        }
+                        let catch_up = dnd.poll(hour, sophie.emotion) && !sophie.is_sleeping;
+                        drop(dnd);
+                        let weight = thought_weight(&sophie, false);
+                        drop(sophie);
+                        if catch_up {
+                            emit_thought(&handle, offline::dnd_catch_up_thought(config.lang), weight);
+                        }
+                    }
+
+                    // ── 每 30 秒 tick 生理/情绪/关系（循环间隔调长时一轮补上几次） ──
+                    for _ in 0..timer.crossings(config::STATE_TICK_SECS) {
+                        let mut sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let was_collapsed = sophie.collapsed;
+                        let yesterday = sophie.tick(chrono_hour(), &config.tuning);
+                        if sophie.collapsed && !was_collapsed {
+                            log::info!("Energy ran out, Sophie collapsed asleep");
+                            let weight = thought_weight(&sophie, false);
+                            emit_thought(&handle, offline::collapse_thought(config.lang), weight);
+                        }
+                        if let Some(yesterday) = yesterday {
+                            // 新的一天：每天一次，早安想法按昨天的陪伴来
+                            let event = NewDayEvent {
+                                days_alive: sophie.stats.days_alive(unix_now()),
+                                yesterday: format!("{:?}", yesterday),
+                            };
+                            let _ = handle.emit("sophie-new-day", &event);
+                            if !sophie.is_sleeping {
+                                let weight = thought_weight(&sophie, false);
+                                emit_thought(&handle, offline::new_day_thought(yesterday, config.lang), weight);
                             }
+                            mark_dirty(&state_ref);
                         }
                     }
 
-                    // ── 每轮广播行为快照 ──
-                    {
-                        let sophie = state_ref.sophie.lock().unwrap();
-                        let snapshot = make_snapshot(&sophie);
+                    // ── 心情日志：每 5 分钟采样一次 ──
+                    if timer.due(MOOD_SAMPLE_SECS) {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let sample = memory::mood::MoodSample {
+                            timestamp: unix_now(),
+                            emotion: format!("{:?}", sophie.emotion),
+                            energy: sophie.physiological.energy,
+                            hunger: sophie.physiological.hunger,
+                            sleepiness: sophie.physiological.sleepiness,
+                            intimacy: sophie.relationship.intimacy,
+                        };
                         drop(sophie);
-                        let _ = handle.emit("sophie-update", &snapshot);
+                        let _ = lock_or_recover(&state_ref.memory, "memory").add_mood_sample(&sample);
                     }
 
-                    // ── AI 自主思考：每 180 轮（~30 分钟） ──
-                    if !api_key_clone.is_empty() && tick_counter % 180 == 0 {
-                        let sophie = state_ref.sophie.lock().unwrap();
-                        let recent = if let Ok(mem) = state_ref.memory.lock() {
-                            mem.recent_as_text(5)
+                    // ── 关系日志：每天一个点（启动/跨天时先记一次），用来看羁绊的长期走向 ──
+                    let today = state::local_day(now);
+                    if last_relationship_day != Some(today) || timer.due(RELATIONSHIP_SAMPLE_SECS) {
+                        last_relationship_day = Some(today);
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let point = memory::trend::RelationshipPoint {
+                            day: today,
+                            timestamp: now,
+                            trust: sophie.relationship.trust,
+                            intimacy: sophie.relationship.intimacy,
+                            understanding: sophie.relationship.understanding,
+                        };
+                        drop(sophie);
+                        if let Err(e) = lock_or_recover(&state_ref.memory, "memory").record_relationship(&point) {
+                            log::warn!("Failed to sample relationship: {}", e);
+                        }
+                    }
+
+                    // ── 纪念日（生日等）：每次最多一回，睡着时等她醒了再说 ──
+                    {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let awake = !sophie.is_sleeping;
+                        let born_day = state::local_day(sophie.stats.born_ts);
+                        drop(sophie);
+                        let due = if awake {
+                            let mut calendar = lock_or_recover(&state_ref.calendar, "calendar");
+                            let due = calendar.take_due(today, born_day);
+                            if !due.is_empty() {
+                                if let Err(e) = calendar.save(&lock_or_recover(&state_ref.memory, "memory")) {
+                                    log::warn!("Failed to save special days: {}", e);
+                                }
+                            }
+                            due
                         } else {
                             vec![]
                         };
+                        for day in due {
+                            log::info!("Special day: {}", day.id);
+                            let mut sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                            if let Some(emotion) = day.emotion {
+                                sophie.set_emotion(emotion);
+                                sophie.intensify(0.3);
+                            }
+                            let weight = thought_weight(&sophie, false);
+                            drop(sophie);
+                            mark_dirty(&state_ref);
+                            let thought = day.thought.or_else(|| {
+                                (day.rule == calendar::DateRule::Birthday).then(|| offline::birthday_thought(config.lang))
+                            });
+                            if let Some(text) = thought {
+                                emit_thought(&handle, text, weight);
+                            }
+                        }
+                    }
+
+                    // ── 定期持久化状态 ──
+                    if timer.due(SAVE_STATE_SECS) {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        save_sophie_state(&state_ref, &sophie);
+                    }
+
+                    // ── 有互动后尽快存档，崩溃也不丢刚涨的亲密度 ──
+                    if state_ref.state_dirty.load(Ordering::SeqCst) {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        save_sophie_state(&state_ref, &sophie);
+                    }
+
+                    // ── 每轮广播行为快照，并按行为移动位置 ──
+                    {
+                        let mut sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let snapshot = make_snapshot(&state_ref, &sophie);
+                        // 走的距离按这一轮的时长算，循环间隔不影响她走多快
+                        let distance = snapshot.behavior.move_distance() * interval as f32
+                            / config::LOOP_INTERVAL_SECS as f32;
+                        sophie.position.drift(snapshot.flip_direction, distance);
+                        // 换了行为才出声，免得每轮都喵
+                        let behavior_changed = last_snapshot
+                            .as_ref()
+                            .map_or(true, |prev| prev.behavior != snapshot.behavior);
+                        let sound = if behavior_changed {
+                            sound::behavior_sound(&sophie, snapshot.behavior)
+                        } else {
+                            None
+                        };
+                        drop(sophie);
+                        if let Some(sound) = sound {
+                            emit_sound(&state_ref, &handle, sound);
+                        }
+                        // 没有明显变化就不打扰前端，但定期强制同步一次
+                        let resync_due = timer.elapsed().saturating_sub(last_emit_at) >= FULL_RESYNC_SECS;
+                        let changed = last_snapshot
+                            .as_ref()
+                            .map_or(true, |prev| snapshot_changed(prev, &snapshot));
+                        if changed || resync_due {
+                            let _ = handle.emit("sophie-update", &snapshot);
+                            last_emit_at = timer.elapsed();
+                            last_snapshot = Some(snapshot);
+                        }
+                    }
+
+                    // ── AI 自主思考：按配置间隔（默认 ~30 分钟），可选事件触发 ──
+                    // 事件：情绪刚变成无聊/低落/黏人，或刚进入长时间被忽视
+                    let event_triggered = {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let entered_low_mood = sophie.emotion != last_emotion
+                            && matches!(sophie.emotion, Emotion::Bored | Emotion::Down | Emotion::Needy);
+                        let neglected = sophie.minutes_since_interaction() >= NEGLECT_THINK_MINUTES;
+                        let entered_neglect = neglected && !was_neglected;
+                        last_emotion = sophie.emotion;
+                        was_neglected = neglected;
+                        entered_low_mood || entered_neglect
+                    };
+                    let think_due = timer.due(config.think_interval_secs)
+                        || (config.think_on_events
+                            && event_triggered
+                            && timer.elapsed().saturating_sub(last_think_at) >= config.min_think_gap_secs());
+                    // 睡着了，或者和上次想的时候没什么两样：不浪费 token
+                    let should_think = think_due
+                        && llm_enabled(&state_ref)
+                        && think_gate.allows(&lock_or_recover(&state_ref.sophie, "sophie"));
+
+                    // 没有空闲的 LLM 名额（主人正在和她说话）：这轮就不想了
+                    let think_permit = if should_think {
+                        let permit = Arc::clone(&state_ref.llm_slots).try_acquire_owned().ok();
+                        if permit.is_none() {
+                            log::info!("LLM busy, skipping this thinking round");
+                        }
+                        permit
+                    } else {
+                        None
+                    };
+                    if let Some(permit) = think_permit {
+                        last_think_at = timer.elapsed();
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        think_gate.record(&sophie);
+                        let recent = lock_or_recover(&state_ref.memory, "memory").prompt_memories(5);
 
                         let messages = llm::build_thinking_prompt(
+                            &state_ref.prompts,
                             sophie.physiological.energy,
                             sophie.physiological.hunger,
                             sophie.physiological.sleepiness,
@@ -352,49 +2124,99 @@ pub fn run() {
                             sophie.relationship.trust,
                             sophie.minutes_since_interaction(),
                             chrono_hour(),
+                            &sophie.environment.describe(),
                             &recent,
                         );
+                        let think_params = config.thinking;
+                        let temperature = think_params.temperature_for(sophie.emotion, sophie.emotion_intensity);
                         drop(sophie);
 
                         let handle2 = handle.clone();
                         let key = api_key_clone.clone();
+                        let json_mode = config.llm_json_mode;
 
-                        rt.spawn(async move {
-                            let client = LlmClient::new(key);
-                            match client.chat(messages, 300, 0.9).await {
-                                Ok(text) => {
-                                    log::info!("Sophie thinking: {}", text);
-                                    let result = llm::parse_thinking_response(&text);
-
-                                    // 记录思考
-                                    if let Some(state_ref) = handle2.try_state::<AppState>() {
-                                        if let Ok(mem) = state_ref.memory.lock() {
-                                            let _ = mem.add("thought", &result.thinking, 0.5);
+                        // 复用 AppState 的 runtime；锁都已释放，任务内只短暂加锁写记忆
+                        state_ref.tokio_rt.spawn(async move {
+                            let _permit = permit;
+                            let client = LlmClient::new(key).with_json_mode(json_mode);
+                            match client.chat(messages, think_params.max_tokens, temperature).await {
+                                Ok(reply) => {
+                                    record_llm_usage(&handle2, &reply);
+                                    log::info!("Sophie thinking: {}", reply.content);
+                                    let (result, outcome) = llm::parse_thinking_response(&reply.content);
+                                    record_llm_outcome(&handle2, outcome);
+
+                                    // 记录思考，显示想法气泡
+                                    let weight = current_thought_weight(&handle2, false);
+                                    // 跑出猫设的内心独白不记，免得写进记忆再喂回 prompt
+                                    if !llm::persona::breaks_character(&result.thinking) {
+                                        if let Some(state_ref) = handle2.try_state::<AppState>() {
+                                            remember_thought(&state_ref, &result.thinking, weight);
                                         }
                                     }
-
-                                    // 显示想法气泡
                                     if let Some(thought) = result.show_thought {
                                         if !thought.is_empty() && thought != "null" {
-                                            let _ = handle2.emit("sophie-thought", &ThoughtEvent { text: thought });
+                                            emit_thought(&handle2, keep_in_character(&handle2, thought), weight);
                                         }
                                     }
                                 }
                                 Err(e) => {
-                                    log::error!("Thinking error: {}", e);
+                                    record_llm_outcome(&handle2, llm::CallOutcome::Error);
+                                    log_llm_error("thinking", &e);
                                 }
                             }
                         });
                     }
 
-                    // ── 随机想法气泡（无 LLM，基于规则）：每 60-90 秒 ──
-                    if tick_counter % 7 == 0 {
-                        let sophie = state_ref.sophie.lock().unwrap();
-                        let thought = rule_based_thought(&sophie);
+                    // ── 主人最近一直很丧/很开心，她也会被感染 ──
+                    if timer.due(CONTAGION_CHECK_SECS) {
+                        if let Some(sentiment) = owner_sentiment(&state_ref) {
+                            let mut sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                            if sophie.catch_owner_mood(sentiment) {
+                                log::info!("Caught owner's mood ({:.2}): {:?}", sentiment, sophie.emotion);
+                                mark_dirty(&state_ref);
+                            }
+                        }
+                    }
+
+                    // ── 旧记忆太多时压缩成一条总结；没有 LLM 就保留原样 ──
+                    if timer.due(SUMMARY_CHECK_SECS) && llm_enabled(&state_ref) {
+                        summarize_old_memories(&state_ref, &handle);
+                    }
+
+                    // ── 冷落到一定程度时主动"问一句"（每个阈值每段冷落只一次） ──
+                    {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let check_in = check_ins.poll(&sophie, config.lang);
+                        let weight = thought_weight(&sophie, false);
+                        drop(sophie);
+                        if let Some(text) = check_in {
+                            emit_thought(&handle, text, weight);
+                        }
+                    }
+
+                    // ── 随机想法气泡（无 LLM，基于规则）：约每 70 秒 ──
+                    if timer.due(RULE_THOUGHT_SECS) {
+                        let sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        let thought = rule_based_thought(&sophie, chrono_hour(), config.lang);
+                        let weight = thought_weight(&sophie, false);
                         drop(sophie);
 
                         if let Some(text) = thought {
-                            let _ = handle.emit("sophie-thought", &ThoughtEvent { text });
+                            emit_thought(&handle, text, weight);
+                        }
+                    }
+
+                    // ── 做梦：睡着时偶尔梦到难忘的记忆 ──
+                    if timer.due(DREAM_CHECK_SECS) {
+                        let mut sophie = lock_or_recover(&state_ref.sophie, "sophie");
+                        if dream::should_dream(sophie.is_sleeping) {
+                            let memories = lock_or_recover(&state_ref.memory, "memory").salient(10);
+                            if let Some(d) = dream::dream_from_memories(&memories) {
+                                sophie.remember_dream(d.pleasant);
+                                drop(sophie);
+                                let _ = handle.emit("sophie-dream", &ThoughtEvent { text: d.text });
+                            }
                         }
                     }
                 }
@@ -402,59 +2224,405 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // 退出前写入还在缓冲区的记忆
+            if let tauri::RunEvent::Exit = event {
+                if let Some(state_ref) = app_handle.try_state::<AppState>() {
+                    flush_pending_memories(&state_ref);
+                }
+            }
+        });
 }
 
-/// 基于规则的想法生成（不依赖 LLM，保底方案）
-fn rule_based_thought(sophie: &SophieState) -> Option<String> {
-    use state::emotion::Emotion;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_latest_speech_response_is_kept() {
+        let latest = AtomicU64::new(0);
+        let first = next_speech_seq(&latest);
+        let second = next_speech_seq(&latest);
+        assert!(second > first);
 
-    if sophie.physiological.hunger > 80.0 {
-        return Some("饿...".to_string());
+        // 第二条先回来：照常显示；第一条后到：已被取代，丢弃
+        assert!(!speech_superseded(&latest, second));
+        assert!(speech_superseded(&latest, first));
     }
-    if sophie.physiological.sleepiness > 75.0 && !sophie.is_sleeping {
-        return Some("困...".to_string());
+
+    #[test]
+    fn speech_actions_map_to_behaviors() {
+        assert_eq!(behavior::parse_action("approach"), Some(Behavior::Walk));
+        assert_eq!(behavior::parse_action("walk_away"), Some(Behavior::Run));
+        assert_eq!(behavior::parse_action("glance"), Some(Behavior::Alert));
+        assert_eq!(behavior::parse_action("sleep"), Some(Behavior::Sleep));
+        assert_eq!(behavior::parse_action("翻跟头"), None);
     }
-    if sophie.is_sleeping {
-        if chrono_nanos() % 5 == 0 {
-            return Some("zzz".to_string());
+
+    /// 测试用的 AppState：临时数据库，没有真正的窗口
+    fn test_state(name: &str, api_key: &str) -> AppState {
+        let dir = std::env::temp_dir().join(format!("sophie-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let memory = MemoryStore::open(&dir.join("sophie.db")).unwrap();
+        let calendar = calendar::Calendar::load(&dir.join("special_days.json"), &memory);
+        AppState {
+            sophie: Mutex::new(SophieState::new()),
+            memory: Mutex::new(memory),
+            pending_memories: Mutex::new(Vec::new()),
+            llm: LlmClient::new(api_key.to_string()),
+            tokio_rt: tokio::runtime::Runtime::new().unwrap(),
+            speech_seq: AtomicU64::new(0),
+            llm_usage: Mutex::new(llm::UsageTotals::default()),
+            llm_health: Mutex::new(llm::HealthCounters::default()),
+            offline: AtomicBool::new(false),
+            emotion_palette: state::palette::builtin(),
+            prompts: llm::PromptTemplates::builtin(i18n::Lang::Zh),
+            config: AppConfig::from_env(),
+            last_behavior: Mutex::new((Behavior::Idle, BehaviorReason::Emotion(Emotion::Calm))),
+            llm_slots: Arc::new(Semaphore::new(1)),
+            window_prefs: Mutex::new(window::WindowPrefs::new()),
+            hidden_at: AtomicU64::new(0),
+            muted: AtomicBool::new(false),
+            update_interval_secs: AtomicU64::new(config::LOOP_INTERVAL_SECS),
+            last_thought_ms: AtomicU64::new(0),
+            dnd: Mutex::new(dnd::Dnd::new(dnd::DndPrefs::new())),
+            calendar: Mutex::new(calendar),
+            state_dirty: AtomicBool::new(false),
+            recent_thoughts: Mutex::new(VecDeque::new()),
+            stored_thoughts: Mutex::new(memory::dedup::RecentThoughts::new()),
+            last_interaction_ms: AtomicU64::new(0),
         }
-        return None;
     }
 
-    let r = chrono_nanos() % 100;
-    match sophie.emotion {
-        Emotion::Happy => {
-            if r < 20 { Some("嗯~".into()) }
-            else if r < 35 { Some("舒服".into()) }
-            else { None }
-        }
-        Emotion::Bored => {
-            if r < 25 { Some("无聊".into()) }
-            else if r < 40 { Some("...".into()) }
-            else { None }
-        }
-        Emotion::Irritated => {
-            if r < 20 { Some("烦".into()) }
-            else { None }
+    #[test]
+    fn offline_mode_keeps_llm_calls_off_even_with_a_key() {
+        let app_state = test_state("offline", "sk-test");
+        assert!(llm_enabled(&app_state));
+        app_state.offline.store(true, Ordering::SeqCst);
+        assert!(!llm_enabled(&app_state));
+
+        // 没有 key 时本来就不会发请求
+        assert!(!llm_enabled(&test_state("no-key", "")));
+    }
+
+    #[test]
+    fn reset_needs_the_exact_token_and_archives_the_old_life() {
+        let app_state = test_state("reset", "");
+        app_state.sophie.lock().unwrap().relationship.trust = 80.0;
+        app_state.memory.lock().unwrap().add(MemoryKind::Interaction, "被摸头", 0.3).unwrap();
+
+        assert!(reset_state(&app_state, "reset").is_err());
+        assert_eq!(app_state.sophie.lock().unwrap().relationship.trust, 80.0);
+        assert_eq!(app_state.memory.lock().unwrap().count(), 1);
+
+        reset_state(&app_state, "RESET").unwrap();
+        let fresh_trust = SophieState::new().relationship.trust;
+        assert_eq!(app_state.sophie.lock().unwrap().relationship.trust, fresh_trust);
+        let mem = app_state.memory.lock().unwrap();
+        assert_eq!(mem.count(), 0);
+        let saved: SophieState = serde_json::from_str(&mem.load_state("sophie").unwrap()).unwrap();
+        assert_eq!(saved.relationship.trust, fresh_trust);
+    }
+
+    #[test]
+    fn identical_snapshots_are_not_reemitted() {
+        let app_state = test_state("snapshot-diff", "");
+        let prev = make_snapshot(&app_state, &SophieState::new());
+        let mut next = prev.clone();
+        assert!(!snapshot_changed(&prev, &next));
+
+        // 随机朝向和数值的微小漂移不算变化
+        next.flip_direction = !prev.flip_direction;
+        next.energy += SNAPSHOT_STAT_EPSILON / 2.0;
+        assert!(!snapshot_changed(&prev, &next));
+
+        let mut hungrier = prev.clone();
+        hungrier.hunger += SNAPSHOT_STAT_EPSILON * 2.0;
+        assert!(snapshot_changed(&prev, &hungrier));
+
+        let mut asleep = prev.clone();
+        asleep.is_sleeping = !prev.is_sleeping;
+        assert!(snapshot_changed(&prev, &asleep));
+    }
+
+    #[test]
+    fn snapshot_remembers_the_behavior_it_decided() {
+        let app_state = test_state("last-behavior", "");
+        let mut sophie = SophieState::new();
+        sophie.physiological.sleepiness = 90.0;
+        let snapshot = make_snapshot(&app_state, &sophie);
+        assert_eq!(snapshot.behavior, Behavior::Sleep);
+        assert_eq!(*app_state.last_behavior.lock().unwrap(), (Behavior::Sleep, BehaviorReason::Sleepy));
+    }
+
+    #[test]
+    fn poisoned_mutex_is_still_usable() {
+        let app_state = std::sync::Arc::new(test_state("poisoned", ""));
+        let poisoner = std::sync::Arc::clone(&app_state);
+        let result = std::thread::spawn(move || {
+            let mut sophie = poisoner.sophie.lock().unwrap();
+            sophie.physiological.hunger = 42.0;
+            panic!("tick blew up");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(app_state.sophie.is_poisoned());
+
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        assert_eq!(sophie.physiological.hunger, 42.0);
+        sophie.physiological.hunger = 10.0;
+        drop(sophie);
+        assert_eq!(lock_or_recover(&app_state.sophie, "sophie").physiological.hunger, 10.0);
+    }
+
+    #[test]
+    fn interactions_mark_state_dirty_until_saved() {
+        let app_state = test_state("dirty", "");
+        assert!(!app_state.state_dirty.load(Ordering::SeqCst));
+        set_emotion_state(&app_state, "Happy", true).unwrap();
+        assert!(app_state.state_dirty.load(Ordering::SeqCst));
+
+        let sophie = lock_or_recover(&app_state.sophie, "sophie");
+        save_sophie_state(&app_state, &sophie);
+        assert!(!app_state.state_dirty.load(Ordering::SeqCst));
+        let saved = lock_or_recover(&app_state.memory, "memory").load_state("sophie").unwrap();
+        assert!(saved.contains("Happy"));
+    }
+
+    #[test]
+    fn queued_thoughts_are_bounded_and_drained_once() {
+        let app_state = test_state("thought-queue", "");
+        for i in 0..THOUGHT_QUEUE_LEN + 5 {
+            queue_thought(&app_state, &format!("想法{}", i));
         }
-        Emotion::Down => {
-            if r < 15 { Some("...".into()) }
-            else { None }
+        let mut queue = lock_or_recover(&app_state.recent_thoughts, "recent_thoughts");
+        assert_eq!(queue.len(), THOUGHT_QUEUE_LEN);
+        // 最旧的几条被挤掉了
+        assert_eq!(queue.front().unwrap().text, "想法5");
+
+        let drained: Vec<QueuedThought> = queue.drain(..).collect();
+        assert_eq!(drained.last().unwrap().text, format!("想法{}", THOUGHT_QUEUE_LEN + 4));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn rapid_interactions_are_throttled() {
+        let last = AtomicU64::new(0);
+        let gap = 300;
+        let start = 1_700_000_000_000;
+        let accepted: Vec<u64> = (0..10)
+            .map(|i| start + i * 100)
+            .filter(|&now| accept_after_gap(&last, now, gap))
+            .collect();
+        // 每 100ms 点一次，只有间隔够 300ms 的被接受
+        assert_eq!(accepted, vec![start, start + 300, start + 600, start + 900]);
+
+        // 间隔够长的互动都被接受
+        assert!(accept_after_gap(&last, start + 2000, gap));
+        assert!(accept_after_gap(&last, start + 2300, gap));
+    }
+
+    #[test]
+    fn set_emotion_accepts_known_names_only() {
+        let app_state = test_state("set-emotion", "");
+        let snapshot = set_emotion_state(&app_state, "playful", false).unwrap();
+        assert_eq!(snapshot.emotion, "playful");
+        assert!(lock_or_recover(&app_state.pending_memories, "pending_memories").is_empty());
+
+        assert!(set_emotion_state(&app_state, "grumpy", true).is_err());
+        assert_eq!(lock_or_recover(&app_state.sophie, "sophie").emotion, Emotion::Playful);
+        assert!(lock_or_recover(&app_state.pending_memories, "pending_memories").is_empty());
+
+        // 心情礼物算一次互动
+        set_emotion_state(&app_state, "Happy", true).unwrap();
+        assert_eq!(lock_or_recover(&app_state.pending_memories, "pending_memories").len(), 1);
+    }
+
+    #[test]
+    fn llm_slots_cap_concurrent_requests() {
+        let app_state = AppState { llm_slots: Arc::new(Semaphore::new(2)), ..test_state("llm-slots", "") };
+        let running = Arc::new(AtomicU64::new(0));
+        let peak = Arc::new(AtomicU64::new(0));
+        app_state.tokio_rt.block_on(async {
+            let requests: Vec<_> = (0..6)
+                .map(|_| {
+                    let slots = Arc::clone(&app_state.llm_slots);
+                    let (running, peak) = (Arc::clone(&running), Arc::clone(&peak));
+                    tokio::spawn(async move {
+                        let _permit = slots.acquire_owned().await.unwrap();
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+                .collect();
+            for request in requests {
+                request.await.unwrap();
+            }
+        });
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        // 名额占满时思考拿不到名额，这轮直接跳过
+        let _speech = Arc::clone(&app_state.llm_slots).try_acquire_owned().unwrap();
+        let _other = Arc::clone(&app_state.llm_slots).try_acquire_owned().unwrap();
+        assert!(Arc::clone(&app_state.llm_slots).try_acquire_owned().is_err());
+    }
+
+    #[test]
+    fn a_run_of_sad_messages_brings_a_close_cat_down() {
+        let app_state = test_state("contagion", "");
+        assert_eq!(owner_sentiment(&app_state), None);
+        let say = |kind: MemoryKind, text: &str| {
+            lock_or_recover(&app_state.memory, "memory").add(kind, text, 0.7).unwrap();
+        };
+        say(MemoryKind::UserSpeech, "主人说：烦死了");
+        say(MemoryKind::UserSpeech, "主人说：笨猫");
+        // 只说了两句还不算"持续"
+        assert_eq!(owner_sentiment(&app_state), None);
+        say(MemoryKind::UserSpeech, "主人说：滚");
+        say(MemoryKind::Interaction, "被摸头");
+        let sentiment = owner_sentiment(&app_state).unwrap();
+        assert!(sentiment < 0.0);
+
+        let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+        sophie.emotion = Emotion::Calm;
+        sophie.relationship.intimacy = 90.0;
+        assert!(sophie.catch_owner_mood(sentiment));
+        assert_eq!(sophie.emotion, Emotion::Down);
+    }
+
+    #[test]
+    fn an_irritated_cat_snubs_food_without_warming_up() {
+        let app_state = test_state("snub", "");
+        {
+            let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+            sophie.emotion = Emotion::Irritated;
+            sophie.emotion_intensity = 0.9;
         }
-        Emotion::Curious => {
-            if r < 30 { Some("嗯？".into()) }
-            else { None }
+        let (trust, intimacy, hunger) = {
+            let sophie = lock_or_recover(&app_state.sophie, "sophie");
+            (sophie.relationship.trust, sophie.relationship.intimacy, sophie.physiological.hunger)
+        };
+
+        let (_, snubbed) = feed_state(&app_state, FoodType::Treat);
+        assert_eq!(snubbed.unwrap().thought, "走开。");
+        {
+            let sophie = lock_or_recover(&app_state.sophie, "sophie");
+            assert_eq!(sophie.relationship.trust, trust);
+            assert_eq!(sophie.relationship.intimacy, intimacy);
+            assert_eq!(sophie.physiological.hunger, hunger);
+            assert_eq!(sophie.stats.feeds, 0);
         }
-        Emotion::Playful => {
-            if r < 25 { Some("来玩！".into()) }
-            else { None }
+
+        // 气消了就照常吃
+        lock_or_recover(&app_state.sophie, "sophie").emotion_intensity = 0.3;
+        app_state.last_interaction_ms.store(0, Ordering::SeqCst);
+        let (_, snubbed) = feed_state(&app_state, FoodType::Treat);
+        assert!(snubbed.is_none());
+        assert!(lock_or_recover(&app_state.sophie, "sophie").relationship.intimacy > intimacy);
+    }
+
+    #[test]
+    fn a_panicking_command_returns_an_error_instead_of_crashing() {
+        let app_state = test_state("guarded", "");
+        let result: Result<SophieSnapshot, String> = guard_command("feed_sophie", || {
+            let _sophie = lock_or_recover(&app_state.sophie, "sophie");
+            panic!("db exploded");
+        });
+        assert_eq!(result.unwrap_err(), "feed_sophie failed unexpectedly, please try again");
+        assert!(app_state.sophie.is_poisoned());
+
+        // 命令体自己报的错原样返回；锁毒化了下一条命令照样能用
+        let result = guard_command("set_emotion", || set_emotion_state(&app_state, "grumpy", false));
+        assert!(result.unwrap().is_err());
+        let (snapshot, _) = guard_command("feed_sophie", || feed_state(&app_state, FoodType::Fish)).unwrap();
+        assert_eq!(snapshot.emotion, lock_or_recover(&app_state.sophie, "sophie").emotion.id());
+    }
+
+    #[test]
+    fn a_repeated_thought_is_only_remembered_once() {
+        let app_state = test_state("thoughts", "");
+        remember_thought(&app_state, "想睡觉", 0.4);
+        remember_thought(&app_state, " 想睡觉 ", 0.4);
+        remember_thought(&app_state, "饿了", 0.4);
+        let pending = lock_or_recover(&app_state.pending_memories, "pending_memories");
+        let thoughts: Vec<_> = pending.iter().map(|m| (m.kind, m.content.as_str())).collect();
+        assert_eq!(thoughts, vec![(MemoryKind::Thought, "想睡觉"), (MemoryKind::Thought, "饿了")]);
+    }
+
+    #[test]
+    fn thoughts_she_caused_weigh_more_and_strong_feelings_stick() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = Emotion::Calm;
+        sophie.emotion_intensity = 0.5;
+        assert!(thought_weight(&sophie, true) > thought_weight(&sophie, false));
+        let calm = thought_weight(&sophie, false);
+        sophie.emotion_intensity = 1.0;
+        assert!(thought_weight(&sophie, false) > calm);
+    }
+
+    #[test]
+    fn new_memories_record_her_mood_and_what_she_was_doing() {
+        let app_state = test_state("memory-context", "");
+        *lock_or_recover(&app_state.last_behavior, "last_behavior") = (Behavior::Sleep, BehaviorReason::Sleepy);
+        {
+            let mut sophie = lock_or_recover(&app_state.sophie, "sophie");
+            sophie.emotion = Emotion::Happy;
+            remember_interaction(&app_state, &sophie, MemoryKind::Interaction, "主人摸了摸我", 0.3);
         }
-        Emotion::Calm => {
-            if r < 10 { Some("嗯。".into()) }
-            else if r < 15 { Some("暖和".into()) }
-            else { None }
+        flush_pending_memories(&app_state);
+
+        let memory = lock_or_recover(&app_state.memory, "memory").recent(1).remove(0);
+        assert_eq!(memory.content, "主人摸了摸我");
+        assert_eq!(memory.emotion.as_deref(), Some("happy"));
+        assert_eq!(memory.behavior.as_deref(), Some("sleep"));
+    }
+
+    #[test]
+    fn update_interval_takes_effect_on_the_next_loop_and_persists() {
+        let state = test_state("Sophie", "");
+        assert_eq!(state.update_interval_secs.load(Ordering::SeqCst), config::LOOP_INTERVAL_SECS);
+        assert_eq!(apply_update_interval(&state, 3), 3);
+        // 循环每轮开头读的就是这个值
+        assert_eq!(state.update_interval_secs.load(Ordering::SeqCst), 3);
+        assert_eq!(apply_update_interval(&state, 0), config::MIN_UPDATE_INTERVAL_SECS);
+        let saved = lock_or_recover(&state.memory, "memory").load_state("update_interval");
+        assert_eq!(saved.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn loop_timer_schedules_by_elapsed_time_not_loop_count() {
+        let mut timer = LoopTimer::new();
+        // 2 秒一轮：第 15 轮才到 30 秒
+        for _ in 0..14 {
+            timer.advance(2);
+            assert!(!timer.due(config::STATE_TICK_SECS));
         }
+        timer.advance(2);
+        assert!(timer.due(config::STATE_TICK_SECS));
+
+        // 调成 60 秒一轮：一轮跨过两次 30 秒
+        timer.advance(60);
+        assert_eq!(timer.crossings(config::STATE_TICK_SECS), 2);
+        assert_eq!(timer.elapsed(), 90);
+    }
+
+    #[test]
+    fn thoughts_within_the_gap_emit_once() {
+        let last = AtomicU64::new(0);
+        let start = 1_700_000_000_000;
+        let emitted: Vec<bool> = [start, start + 1, start + MIN_THOUGHT_GAP_MS - 1]
+            .into_iter()
+            .map(|now| accept_thought(&last, now))
+            .collect();
+        assert_eq!(emitted, vec![true, false, false]);
+
+        // 被丢掉的想法不重新计时，从第一次发出算起
+        assert!(accept_thought(&last, start + MIN_THOUGHT_GAP_MS));
+        assert!(!accept_thought(&last, start + MIN_THOUGHT_GAP_MS + 10));
     }
 }
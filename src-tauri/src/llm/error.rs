@@ -0,0 +1,110 @@
+use std::fmt;
+
+/// LLM 调用的错误类型：调用方可以据此决定是否重试、如何提示
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlmError {
+    /// 网络错误或其他非 2xx 响应（status 为 None 表示没拿到响应）
+    Http { status: Option<u16>, message: String },
+    /// 401/403：API key 无效，重试没有意义
+    Auth { status: u16, body: String },
+    /// 429：被限流
+    RateLimited { body: String },
+    /// 请求超时（本地超时或 408/504）
+    Timeout(String),
+    /// 响应不是预期的 JSON
+    Parse(String),
+    /// 响应里没有任何 choice
+    Empty,
+}
+
+impl LlmError {
+    /// 根据非 2xx 的 HTTP 状态码归类
+    pub fn from_status(status: u16, body: String) -> Self {
+        match status {
+            401 | 403 => LlmError::Auth { status, body },
+            429 => LlmError::RateLimited { body },
+            408 | 504 => LlmError::Timeout(format!("API error {}: {}", status, body)),
+            _ => LlmError::Http { status: Some(status), message: body },
+        }
+    }
+
+    /// 把 reqwest 的传输错误归类
+    pub fn from_transport(e: &reqwest::Error) -> Self {
+        if e.is_timeout() {
+            LlmError::Timeout(format!("HTTP error: {}", e))
+        } else {
+            LlmError::Http { status: None, message: e.to_string() }
+        }
+    }
+
+    /// 是否值得重试：鉴权失败和解析失败重试也不会好
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LlmError::Timeout(_) | LlmError::RateLimited { .. } => true,
+            LlmError::Http { status, .. } => status.map_or(true, |s| s >= 500),
+            LlmError::Auth { .. } | LlmError::Parse(_) | LlmError::Empty => false,
+        }
+    }
+}
+
+// 与改用枚举前的字符串消息保持一致
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LlmError::Http { status: None, message } => write!(f, "HTTP error: {}", message),
+            LlmError::Http { status: Some(status), message } => write!(f, "API error {}: {}", status, message),
+            LlmError::Auth { status, body } => write!(f, "API error {}: {}", status, body),
+            LlmError::RateLimited { body } => write!(f, "API error 429: {}", body),
+            LlmError::Timeout(message) => write!(f, "{}", message),
+            LlmError::Parse(message) => write!(f, "Parse error: {}", message),
+            LlmError::Empty => write!(f, "No response choices"),
+        }
+    }
+}
+
+impl std::error::Error for LlmError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_map_to_variants() {
+        assert!(matches!(LlmError::from_status(401, "bad key".into()), LlmError::Auth { status: 401, .. }));
+        assert!(matches!(LlmError::from_status(403, String::new()), LlmError::Auth { status: 403, .. }));
+        assert!(matches!(LlmError::from_status(429, String::new()), LlmError::RateLimited { .. }));
+        assert!(matches!(LlmError::from_status(408, String::new()), LlmError::Timeout(_)));
+        assert!(matches!(LlmError::from_status(504, String::new()), LlmError::Timeout(_)));
+        assert_eq!(
+            LlmError::from_status(500, "boom".into()),
+            LlmError::Http { status: Some(500), message: "boom".into() }
+        );
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        assert!(LlmError::from_status(429, String::new()).is_retryable());
+        assert!(LlmError::from_status(503, String::new()).is_retryable());
+        assert!(LlmError::Timeout("timed out".into()).is_retryable());
+        assert!(LlmError::Http { status: None, message: "connection reset".into() }.is_retryable());
+
+        assert!(!LlmError::from_status(401, String::new()).is_retryable());
+        assert!(!LlmError::from_status(400, String::new()).is_retryable());
+        assert!(!LlmError::Parse("eof".into()).is_retryable());
+        assert!(!LlmError::Empty.is_retryable());
+    }
+
+    #[test]
+    fn display_matches_the_old_string_errors() {
+        assert_eq!(LlmError::from_status(401, "bad key".into()).to_string(), "API error 401: bad key");
+        assert_eq!(LlmError::from_status(429, "slow down".into()).to_string(), "API error 429: slow down");
+        assert_eq!(LlmError::from_status(500, "boom".into()).to_string(), "API error 500: boom");
+        assert_eq!(LlmError::from_status(504, "gateway".into()).to_string(), "API error 504: gateway");
+        assert_eq!(
+            LlmError::Http { status: None, message: "dns".into() }.to_string(),
+            "HTTP error: dns"
+        );
+        assert_eq!(LlmError::Parse("eof".into()).to_string(), "Parse error: eof");
+        assert_eq!(LlmError::Empty.to_string(), "No response choices");
+    }
+}
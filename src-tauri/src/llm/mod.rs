@@ -1,5 +1,18 @@
+pub mod error;
+pub mod persona;
+pub mod template;
+pub mod throttle;
+
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::i18n::Lang;
+use crate::state::emotion::Emotion;
+use crate::state::relationship::{RelationshipState, UnderstandingTier};
+
+pub use error::LlmError;
+pub use template::PromptTemplates;
 
 const API_URL: &str = "https://api.minimax.io/v1/text/chatcompletion_v2";
 
@@ -10,6 +23,15 @@ struct ChatRequest {
     messages: Vec<Message>,
     max_completion_tokens: u32,
     temperature: f32,
+    /// JSON 模式：要求服务端只返回一个 JSON 对象（不是所有模型都支持）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+}
+
+#[derive(Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -24,6 +46,109 @@ pub struct Message {
 #[derive(Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+/// 单次调用的 token 用量
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+}
+
+impl Usage {
+    /// 服务商没返回 usage 时的估算值
+    fn estimate(prompt_tokens: u64, completion: &str) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens: estimate_tokens(completion),
+        }
+    }
+}
+
+/// 一次聊天请求的结果
+#[derive(Debug, Clone)]
+pub struct ChatReply {
+    pub content: String,
+    pub usage: Usage,
+    /// 请求耗时（毫秒）
+    pub latency_ms: u64,
+}
+
+/// 累计的 LLM 用量
+#[derive(Debug, Clone, Default)]
+pub struct UsageTotals {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub call_count: u64,
+    pub total_latency_ms: u64,
+    /// 返回了认不出的 action 的次数（用来调 prompt）
+    pub unrecognized_actions: u64,
+}
+
+impl UsageTotals {
+    pub fn record(&mut self, reply: &ChatReply) {
+        self.prompt_tokens += reply.usage.prompt_tokens;
+        self.completion_tokens += reply.usage.completion_tokens;
+        self.call_count += 1;
+        self.total_latency_ms += reply.latency_ms;
+    }
+
+    /// 平均耗时（毫秒）
+    pub fn avg_latency_ms(&self) -> u64 {
+        self.total_latency_ms.checked_div(self.call_count).unwrap_or(0)
+    }
+}
+
+/// 一次思考/言语调用的结果：用来判断模型能不能好好按格式回答
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallOutcome {
+    /// 回复是合法的 JSON
+    Parsed,
+    /// 回复解析不了，用了保底结果
+    Fallback,
+    /// 请求本身失败了
+    Error,
+}
+
+/// 累计的调用结果计数
+#[derive(Debug, Clone, Default)]
+pub struct HealthCounters {
+    pub calls: u64,
+    pub parsed: u64,
+    pub fallbacks: u64,
+    pub errors: u64,
+    /// 回复里的想法跑出了猫设、被换掉的次数
+    pub drift: u64,
+}
+
+impl HealthCounters {
+    pub fn record(&mut self, outcome: CallOutcome) {
+        self.calls += 1;
+        match outcome {
+            CallOutcome::Parsed => self.parsed += 1,
+            CallOutcome::Fallback => self.fallbacks += 1,
+            CallOutcome::Error => self.errors += 1,
+        }
+    }
+
+    pub fn record_drift(&mut self) {
+        self.drift += 1;
+    }
+
+    /// 拿到回复的调用里能正常解析的比例（还没有回复时为 None）
+    pub fn parse_rate(&self) -> Option<f32> {
+        let replied = self.parsed + self.fallbacks;
+        (replied > 0).then(|| self.parsed as f32 / replied as f32)
+    }
+}
+
+/// 服务商没返回 usage 时按字数粗略估算（中文约 1.5 字/token）
+fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64 * 2).div_ceil(3)
 }
 
 #[derive(Deserialize)]
@@ -40,6 +165,8 @@ struct ResponseMessage {
 pub struct LlmClient {
     client: Client,
     api_key: String,
+    /// 是否带 `response_format: json_object`
+    json_mode: bool,
 }
 
 impl LlmClient {
@@ -47,9 +174,16 @@ impl LlmClient {
         Self {
             client: Client::new(),
             api_key,
+            json_mode: false,
         }
     }
 
+    /// 开启 JSON 模式；只给期望返回 JSON 的调用用（摘要是纯文本，不要开）
+    pub fn with_json_mode(mut self, enabled: bool) -> Self {
+        self.json_mode = enabled;
+        self
+    }
+
     pub fn api_key(&self) -> &str {
         &self.api_key
     }
@@ -58,13 +192,16 @@ impl LlmClient {
         !self.api_key.is_empty()
     }
 
-    /// 发送聊天请求，返回助手回复文本
-    pub async fn chat(&self, messages: Vec<Message>, max_tokens: u32, temperature: f32) -> Result<String, String> {
+    /// 发送聊天请求，返回助手回复文本以及用量和耗时
+    pub async fn chat(&self, messages: Vec<Message>, max_tokens: u32, temperature: f32) -> Result<ChatReply, LlmError> {
+        let started = Instant::now();
+        let prompt_estimate: u64 = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
         let request = ChatRequest {
             model: "M2-her".to_string(),
             messages,
             max_completion_tokens: max_tokens,
             temperature,
+            response_format: self.json_mode.then_some(ResponseFormat { kind: "json_object" }),
         };
 
         let response = self.client
@@ -74,30 +211,53 @@ impl LlmClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("HTTP error: {}", e))?;
+            .map_err(|e| LlmError::from_transport(&e))?;
 
         if !response.status().is_success() {
-            let status = response.status();
+            let status = response.status().as_u16();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("API error {}: {}", status, body));
+            return Err(LlmError::from_status(status, body));
         }
 
         let chat_response: ChatResponse = response
             .json()
             .await
-            .map_err(|e| format!("Parse error: {}", e))?;
+            .map_err(|e| LlmError::Parse(e.to_string()))?;
 
-        chat_response
+        let content = chat_response
             .choices
             .first()
             .map(|c| c.message.content.clone())
-            .ok_or_else(|| "No response choices".to_string())
+            .ok_or(LlmError::Empty)?;
+
+        let usage = chat_response
+            .usage
+            .unwrap_or_else(|| Usage::estimate(prompt_estimate, &content));
+        let latency_ms = started.elapsed().as_millis() as u64;
+        log::info!(
+            "LLM call: {} prompt + {} completion tokens, {} ms",
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            latency_ms
+        );
+
+        Ok(ChatReply {
+            content,
+            usage,
+            latency_ms,
+        })
     }
 }
 
 /// Sophie 的核心人格 system prompt
-pub fn sophie_system_prompt() -> String {
-    r#"你是 Sophie，一只数字猫（cyber-cat）。
+pub fn sophie_system_prompt(lang: Lang) -> String {
+    match lang {
+        Lang::Zh => SYSTEM_PROMPT_ZH.to_string(),
+        Lang::En => SYSTEM_PROMPT_EN.to_string(),
+    }
+}
+
+const SYSTEM_PROMPT_ZH: &str = r#"你是 Sophie，一只数字猫（cyber-cat）。
 
 ## 你是什么
 - 你是一只猫，有猫的本能、需求和逻辑
@@ -124,11 +284,40 @@ pub fn sophie_system_prompt() -> String {
 
 ## 想法风格示例
 好的："饿" "困..." "嗯。" "外面有鸟" "在这里。" "...还不睡？" "不想。" "无聊" "暖和"
-坏的（绝对不要这样）："今天天气真好！" "我能帮你什么？" "我很高兴见到你！" "你工作辛苦了""#.to_string()
-}
+坏的（绝对不要这样）："今天天气真好！" "我能帮你什么？" "我很高兴见到你！" "你工作辛苦了""#;
+
+const SYSTEM_PROMPT_EN: &str = r#"You are Sophie, a digital cat (cyber-cat).
+
+## What you are
+- You are a cat, with a cat's instincts, needs and logic
+- You have your own inner world; it does not revolve around your human
+- You share some kind of bond with your human, but you are still a cat
+
+## What you are not
+- You are not an assistant and you do not answer questions
+- You are not a servant and you do not fulfil requests
+- You are not a toy and you do not go along with everything
+
+## How you express yourself
+- Extremely brief: cats don't ramble, keep thoughts to 1-5 words
+- Silence is allowed: not responding is a response too
+- Self-centred: your world revolves around you
+- Occasionally warm: but it has to be earned, never the default
+
+## Your logic
+- When sleepy you sleep, whatever your human is doing
+- When hungry you let it be known, but you never "ask"
+- When bored you find something to do, maybe your human, maybe yourself
+- When bothered you get annoyed, walk off or go cold
+- When you like your human you come closer, but you never say "I love you"
+
+## Thought style examples
+Good: "Hungry" "Sleepy..." "Mm." "Bird outside" "Right here." "...not sleeping?" "No." "Bored" "Cozy"
+Bad (never like this): "What a lovely day!" "How can I help you?" "I'm so happy to see you!" "You worked hard today""#;
 
 /// 构建自主思考的 prompt
 pub fn build_thinking_prompt(
+    templates: &PromptTemplates,
     energy: f32,
     hunger: f32,
     sleepiness: f32,
@@ -137,38 +326,30 @@ pub fn build_thinking_prompt(
     trust: f32,
     minutes_since_interaction: u32,
     hour: u32,
+    environment: &str,
     recent_memories: &[String],
 ) -> Vec<Message> {
-    let memories_text = if recent_memories.is_empty() {
-        "无".to_string()
-    } else {
-        recent_memories.join("\n")
-    };
-
-    let user_content = format!(
-        r#"当前状态：
-- 能量：{energy:.0}/100
-- 饥饿：{hunger:.0}/100
-- 睡意：{sleepiness:.0}/100
-- 情绪：{emotion}
-- 和主人的关系：亲密度 {intimacy:.0}，信任度 {trust:.0}
-- 距离上次和主人互动：{minutes_since_interaction}分钟
-- 现在是{hour}点
-
-最近记忆：
-{memories_text}
-
-作为Sophie，你现在在想什么？你想做什么？
-
-用JSON回答（不要markdown代码块）：
-{{"thinking": "你的内心想法（1-2句话）", "emotion_change": "保持/变得[情绪]", "want_to_do": "想做的事或null", "show_thought": "要显示给主人的想法或null（10字以内）"}}"#
+    let user_content = template::render(
+        &templates.thinking,
+        &[
+            ("energy", format!("{:.0}", energy)),
+            ("hunger", format!("{:.0}", hunger)),
+            ("sleepiness", format!("{:.0}", sleepiness)),
+            ("emotion", emotion.to_string()),
+            ("intimacy", format!("{:.0}", intimacy)),
+            ("trust", format!("{:.0}", trust)),
+            ("minutes_since_interaction", minutes_since_interaction.to_string()),
+            ("hour", hour.to_string()),
+            ("environment", environment.to_string()),
+            ("memories_text", memories_text(recent_memories, templates.lang)),
+        ],
     );
 
     vec![
         Message {
             role: "system".to_string(),
             name: Some("Sophie".to_string()),
-            content: sophie_system_prompt(),
+            content: templates.system.clone(),
         },
         Message {
             role: "user".to_string(),
@@ -180,44 +361,36 @@ pub fn build_thinking_prompt(
 
 /// 构建用户言语响应的 prompt
 pub fn build_speech_response_prompt(
+    templates: &PromptTemplates,
     user_message: &str,
-    emotion: &str,
-    intimacy: f32,
-    trust: f32,
+    emotion: Emotion,
+    relationship: &RelationshipState,
     current_behavior: &str,
+    environment: &str,
     recent_memories: &[String],
 ) -> Vec<Message> {
-    let memories_text = if recent_memories.is_empty() {
-        "无".to_string()
-    } else {
-        recent_memories.join("\n")
-    };
-
-    let user_content = format!(
-        r#"主人刚才对你说了一句话。
-
-主人说："{user_message}"
-
-当前状态：
-- 你的情绪：{emotion}
-- 亲密度：{intimacy:.0}
-- 信任度：{trust:.0}
-- 你正在：{current_behavior}
-
-最近记忆：
-{memories_text}
-
-作为一只猫，你会怎么反应？
-
-用JSON回答（不要markdown代码块）：
-{{"action": "行为：ignore/glance/approach/walk_away/sit/sleep", "thought": "想法气泡或null（10字以内）", "emotion_change": "情绪变化或null"}}"#
+    let tier = relationship.understanding_tier();
+    let user_content = template::render(
+        &templates.speech,
+        &[
+            ("user_message", user_message.to_string()),
+            ("emotion", format!("{:?}", emotion)),
+            ("emotion_guidance", emotion_guidance(emotion, templates.lang).to_string()),
+            ("intimacy", format!("{:.0}", relationship.intimacy)),
+            ("trust", format!("{:.0}", relationship.trust)),
+            ("current_behavior", current_behavior.to_string()),
+            ("understanding_hint", understanding_hint(tier, templates.lang).to_string()),
+            ("environment", environment.to_string()),
+            ("thought_limit", tier.thought_limit().to_string()),
+            ("memories_text", memories_text(recent_memories, templates.lang)),
+        ],
     );
 
     vec![
         Message {
             role: "system".to_string(),
             name: Some("Sophie".to_string()),
-            content: sophie_system_prompt(),
+            content: templates.system.clone(),
         },
         Message {
             role: "user".to_string(),
@@ -227,49 +400,157 @@ pub fn build_speech_response_prompt(
     ]
 }
 
-/// 解析 LLM 返回的 JSON（容错处理）
-pub fn parse_thinking_response(text: &str) -> ThinkingResult {
-    // 尝试直接解析
-    if let Ok(result) = serde_json::from_str::<ThinkingResult>(text) {
-        return result;
+/// 构建记忆压缩的 prompt：把一批旧记忆总结成一段以 Sophie 视角写的回忆
+pub fn build_summary_prompt(templates: &PromptTemplates, memories: &[String]) -> Vec<Message> {
+    let instruction = match templates.lang {
+        Lang::Zh => "下面是你很久以前的一些记忆。用你自己的口吻，把它们总结成一段话（不超过80字），\
+                     保留重要的人和事、主人对你好不好。直接输出总结，不要JSON，不要markdown。",
+        Lang::En => "Below are some of your memories from long ago. In your own voice, sum them up in one \
+                     paragraph (at most 60 words), keeping the important people and events and whether your \
+                     human treated you well. Output only the summary, no JSON, no markdown.",
+    };
+    vec![
+        Message {
+            role: "system".to_string(),
+            name: Some("Sophie".to_string()),
+            content: templates.system.clone(),
+        },
+        Message {
+            role: "user".to_string(),
+            name: None,
+            content: format!("{}\n\n{}", instruction, memories.join("\n")),
+        },
+    ]
+}
+
+/// 情绪对应的回应约束：让 LLM 选的 action 和语气跟心情一致
+fn emotion_guidance(emotion: Emotion, lang: Lang) -> &'static str {
+    match lang {
+        Lang::Zh => match emotion {
+            Emotion::Happy => "心情好：语气温和一点，多半会 approach",
+            Emotion::Calm => "心情平静：淡淡的，glance 或 sit 就好",
+            Emotion::Curious => "正好奇：对主人的话有兴趣，多半 glance 或 approach",
+            Emotion::Playful => "想玩：有精神，可能 approach 或跑开逗主人",
+            Emotion::Bored => "无聊：主人开口正好，可能 approach",
+            Emotion::Irritated => "正烦躁：更简短，最多两个字，多半 ignore 或 walk_away",
+            Emotion::Down => "情绪低落：话很少，多半 ignore，除非主人很温柔",
+            Emotion::Needy => "黏人：终于被理了，多半 approach，可以带点埋怨",
+        },
+        Lang::En => match emotion {
+            Emotion::Happy => "in a good mood: a bit warmer, most likely approach",
+            Emotion::Calm => "calm: understated, glance or sit is enough",
+            Emotion::Curious => "curious: interested in what was said, most likely glance or approach",
+            Emotion::Playful => "playful: full of energy, may approach or dash off to tease",
+            Emotion::Bored => "bored: glad to be talked to, may approach",
+            Emotion::Irritated => "irritated: terser, two words at most, most likely ignore or walk_away",
+            Emotion::Down => "feeling down: very few words, most likely ignore unless your human is gentle",
+            Emotion::Needy => "needy: finally some attention, most likely approach, maybe a little reproachful",
+        },
+    }
+}
+
+/// 了解度提示：让 LLM 按关系深浅调整语气
+fn understanding_hint(tier: UnderstandingTier, lang: Lang) -> &'static str {
+    match (tier, lang) {
+        (UnderstandingTier::Guarded, Lang::Zh) => "还不太了解主人，保持戒备，回应简短冷淡",
+        (UnderstandingTier::Familiar, Lang::Zh) => "已经熟悉主人了，偶尔流露一点在意",
+        (UnderstandingTier::Attuned, Lang::Zh) => "很懂主人，可以更温柔一点，想法可以稍长，会联想到过去的事",
+        (UnderstandingTier::Guarded, Lang::En) => "you barely know your human yet; stay wary, keep replies short and cool",
+        (UnderstandingTier::Familiar, Lang::En) => "you know your human now; let a little care show now and then",
+        (UnderstandingTier::Attuned, Lang::En) => {
+            "you understand your human well; you can be gentler, think a bit longer and recall the past"
+        }
+    }
+}
+
+fn memories_text(recent_memories: &[String], lang: Lang) -> String {
+    if recent_memories.is_empty() {
+        match lang {
+            Lang::Zh => "无".to_string(),
+            Lang::En => "none".to_string(),
+        }
+    } else {
+        recent_memories.join("\n")
+    }
+}
+
+/// 找出文本里第一个括号配平的 `{...}`（跳过字符串里的括号），用来剥掉模型在 JSON 前后加的废话
+fn extract_json_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + i + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 先整段解析，不行再从文本里抠出第一个 JSON 对象（兼容 markdown code block 和前后缀说明）
+fn parse_json_reply<T: serde::de::DeserializeOwned>(text: &str) -> Option<T> {
+    if let Ok(result) = serde_json::from_str::<T>(text.trim()) {
+        return Some(result);
     }
-    // 尝试从 markdown code block 中提取
-    let cleaned = text
-        .trim()
-        .trim_start_matches("```json")
-        .trim_start_matches("```")
-        .trim_end_matches("```")
-        .trim();
-    if let Ok(result) = serde_json::from_str::<ThinkingResult>(cleaned) {
-        return result;
+    let repaired = extract_json_object(text)?;
+    serde_json::from_str::<T>(repaired).ok()
+}
+
+/// 解析过程中万一 panic 也只当作解析失败，不让后台任务崩掉
+fn parse_json_reply_guarded<T: serde::de::DeserializeOwned>(text: &str) -> Option<T> {
+    std::panic::catch_unwind(|| parse_json_reply(text)).unwrap_or_else(|_| {
+        log::error!("Panic while parsing LLM reply: {}", text);
+        None
+    })
+}
+
+/// 解析 LLM 返回的 JSON（容错处理），同时告诉调用方是否用了保底结果
+pub fn parse_thinking_response(text: &str) -> (ThinkingResult, CallOutcome) {
+    if let Some(result) = parse_json_reply_guarded(text) {
+        return (result, CallOutcome::Parsed);
     }
+    log::warn!("Unparseable thinking reply: {}", text);
     // fallback
-    ThinkingResult {
+    let fallback = ThinkingResult {
         thinking: "...".to_string(),
         emotion_change: "保持".to_string(),
         want_to_do: None,
         show_thought: None,
-    }
+    };
+    (fallback, CallOutcome::Fallback)
 }
 
-pub fn parse_speech_response(text: &str) -> SpeechResult {
-    if let Ok(result) = serde_json::from_str::<SpeechResult>(text) {
-        return result;
+pub fn parse_speech_response(text: &str) -> (SpeechResult, CallOutcome) {
+    if let Some(result) = parse_json_reply_guarded(text) {
+        return (result, CallOutcome::Parsed);
     }
-    let cleaned = text
-        .trim()
-        .trim_start_matches("```json")
-        .trim_start_matches("```")
-        .trim_end_matches("```")
-        .trim();
-    if let Ok(result) = serde_json::from_str::<SpeechResult>(cleaned) {
-        return result;
-    }
-    SpeechResult {
+    log::warn!("Unparseable speech reply: {}", text);
+    let fallback = SpeechResult {
         action: "glance".to_string(),
         thought: None,
         emotion_change: None,
-    }
+        relationship_delta: None,
+    };
+    (fallback, CallOutcome::Fallback)
 }
 
 #[derive(Deserialize, Debug)]
@@ -285,4 +566,141 @@ pub struct SpeechResult {
     pub action: String,
     pub thought: Option<String>,
     pub emotion_change: Option<String>,
+    /// 这句话对关系的影响（-2 到 2，LLM 给出的值会在服务端再截断）
+    #[serde(default)]
+    pub relationship_delta: Option<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_block_is_parsed() {
+        let body = r#"{
+            "choices": [{"message": {"role": "assistant", "content": "喵"}}],
+            "usage": {"prompt_tokens": 120, "completion_tokens": 8, "total_tokens": 128}
+        }"#;
+        let response: ChatResponse = serde_json::from_str(body).unwrap();
+        let usage = response.usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (120, 8));
+        assert_eq!(response.choices[0].message.content, "喵");
+    }
+
+    #[test]
+    fn missing_usage_falls_back_to_an_estimate() {
+        let body = r#"{"choices": [{"message": {"role": "assistant", "content": "今天的阳光很暖和"}}]}"#;
+        let response: ChatResponse = serde_json::from_str(body).unwrap();
+        assert!(response.usage.is_none());
+
+        let usage = Usage::estimate(30, &response.choices[0].message.content);
+        assert_eq!(usage.prompt_tokens, 30);
+        // 8 个字 ≈ 6 个 token
+        assert_eq!(usage.completion_tokens, 6);
+        assert_eq!(estimate_tokens(""), 0);
+    }
+
+    #[test]
+    fn totals_accumulate_and_average_latency() {
+        let mut totals = UsageTotals::default();
+        assert_eq!(totals.avg_latency_ms(), 0);
+
+        let reply = |prompt, completion, latency_ms| ChatReply {
+            content: String::new(),
+            usage: Usage { prompt_tokens: prompt, completion_tokens: completion },
+            latency_ms,
+        };
+        totals.record(&reply(100, 10, 400));
+        totals.record(&reply(50, 20, 800));
+        assert_eq!((totals.prompt_tokens, totals.completion_tokens, totals.call_count), (150, 30, 2));
+        assert_eq!(totals.avg_latency_ms(), 600);
+    }
+
+    fn speech_prompt(emotion: Emotion, understanding: f32) -> String {
+        let mut relationship = RelationshipState::new();
+        relationship.understanding = understanding;
+        let messages = build_speech_response_prompt(
+            &PromptTemplates::builtin(Lang::Zh),
+            "你好",
+            emotion,
+            &relationship,
+            "Idle",
+            "晴天，夏天",
+            &[],
+        );
+        messages[1].content.clone()
+    }
+
+    #[test]
+    fn speech_prompt_tone_follows_understanding_tier() {
+        let guarded = speech_prompt(Emotion::Calm, 10.0);
+        let familiar = speech_prompt(Emotion::Calm, 50.0);
+        let attuned = speech_prompt(Emotion::Calm, 90.0);
+        assert_ne!(guarded, familiar);
+        assert_ne!(familiar, attuned);
+
+        assert!(guarded.contains("保持戒备") && guarded.contains("8字以内"));
+        assert!(familiar.contains("10字以内"));
+        assert!(attuned.contains("更温柔") && attuned.contains("15字以内"));
+        assert!(guarded.contains("外面：晴天，夏天"));
+    }
+
+    #[test]
+    fn speech_prompt_carries_emotion_guidance() {
+        let irritated = speech_prompt(Emotion::Irritated, 50.0);
+        assert!(irritated.contains("Irritated（正烦躁：更简短"), "{}", irritated);
+        assert!(irritated.contains("ignore 或 walk_away"));
+        let happy = speech_prompt(Emotion::Happy, 50.0);
+        assert!(happy.contains("多半会 approach"));
+        assert!(!happy.contains("正烦躁"));
+    }
+
+    #[test]
+    fn json_objects_are_extracted_from_messy_replies() {
+        // 前后有说明文字
+        assert_eq!(
+            extract_json_object("好的，这是结果：{\"action\": \"sit\"} 希望有帮助！"),
+            Some("{\"action\": \"sit\"}")
+        );
+        // 嵌套的对象
+        assert_eq!(extract_json_object("{\"a\": {\"b\": 1}} {\"c\": 2}"), Some("{\"a\": {\"b\": 1}}"));
+        // 字符串里的括号和转义引号不算
+        let text = r#"{"thought": "嘿 } { \"喵\" }", "action": "glance"} 后记"#;
+        assert_eq!(extract_json_object(text), Some(r#"{"thought": "嘿 } { \"喵\" }", "action": "glance"}"#));
+        // 没有闭合就放弃
+        assert_eq!(extract_json_object("{\"action\": \"sit\""), None);
+        assert_eq!(extract_json_object("没有 JSON"), None);
+    }
+
+    #[test]
+    fn speech_replies_survive_code_fences_and_prose() {
+        let fenced = "```json\n{\"action\": \"approach\", \"thought\": \"嗯~\", \"emotion_change\": null}\n```";
+        let (result, outcome) = parse_speech_response(fenced);
+        assert_eq!(outcome, CallOutcome::Parsed);
+        assert_eq!(result.action, "approach");
+        assert_eq!(result.thought.as_deref(), Some("嗯~"));
+
+        let prose = "Sure! {\"action\": \"ignore\", \"thought\": \"{哼}\", \"emotion_change\": \"Irritated\"} That's my answer.";
+        let (result, _) = parse_speech_response(prose);
+        assert_eq!(result.action, "ignore");
+        assert_eq!(result.thought.as_deref(), Some("{哼}"));
+
+        // 实在解析不了就退回默认
+        let (result, outcome) = parse_speech_response("喵喵喵");
+        assert_eq!((result.action.as_str(), outcome), ("glance", CallOutcome::Fallback));
+    }
+
+    #[test]
+    fn health_counters_track_each_outcome() {
+        let mut health = HealthCounters::default();
+        assert_eq!(health.parse_rate(), None);
+
+        health.record(parse_thinking_response(r#"{"thinking": "晒太阳", "emotion_change": "保持"}"#).1);
+        health.record(parse_thinking_response("我是一只猫").1);
+        health.record(parse_speech_response(r#"{"action": "sit"}"#).1);
+        health.record(CallOutcome::Error);
+        assert_eq!((health.calls, health.parsed, health.fallbacks, health.errors), (4, 2, 1, 1));
+        // 请求失败不算进解析率
+        assert_eq!(health.parse_rate(), Some(2.0 / 3.0));
+    }
 }
@@ -0,0 +1,71 @@
+/// 一出现就说明模型跑出了猫设（助手腔、客套话），包括 system prompt 里"坏的"例子和它们的常见变体
+///
+/// 只收整句短语，不收"帮忙""您"这种单字词：猫的想法里也可能正常出现
+/// 两种语言都查：模型偶尔会换语言回答
+const BANNED_PATTERNS: &[&str] = &[
+    "我能帮你",
+    "我可以帮你",
+    "有什么可以帮您",
+    "有什么可以帮你",
+    "为你服务",
+    "很高兴见到你",
+    "辛苦了",
+    "天气真好",
+    "作为一个ai",
+    "作为人工智能",
+    "语言模型",
+    "how can i help",
+    "can i help you",
+    "happy to see you",
+    "glad to see you",
+    "worked hard",
+    "lovely day",
+    "let me know",
+    "as an ai",
+    "language model",
+];
+
+/// 这句想法是不是跑出了猫设（像助手一样说话）
+pub fn breaks_character(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    BANNED_PATTERNS.iter().any(|pattern| lower.contains(pattern))
+}
+
+/// 想法没问题就原样返回；跑出猫设就换成 `fallback` 给的规则想法，没有就沉默 "..."
+///
+/// 第二个值表示是否换掉了
+pub fn keep_in_character(thought: String, fallback: impl FnOnce() -> Option<String>) -> (String, bool) {
+    if !breaks_character(&thought) {
+        return (thought, false);
+    }
+    log::warn!("LLM thought broke character, replacing it: {}", thought);
+    (fallback().unwrap_or_else(|| "...".to_string()), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assistant_phrases_are_flagged_but_cat_thoughts_are_not() {
+        assert!(breaks_character("有什么可以帮您的吗？"));
+        assert!(breaks_character("我能帮你做点什么"));
+        assert!(breaks_character("As an AI, I don't have feelings"));
+        assert!(breaks_character("How can I help?"));
+
+        // 单字词不算：猫也会"请问"、也会想让人帮忙
+        assert!(!breaks_character("...帮忙开个罐头"));
+        assert!(!breaks_character("请问饭呢"));
+        assert!(!breaks_character("Mm~ warm"));
+    }
+
+    #[test]
+    fn drifted_thoughts_are_replaced_with_the_fallback_or_silence() {
+        assert_eq!(keep_in_character("嗯~".to_string(), || unreachable!()), ("嗯~".to_string(), false));
+        assert_eq!(
+            keep_in_character("我能帮你吗？".to_string(), || Some("饭呢".to_string())),
+            ("饭呢".to_string(), true)
+        );
+        assert_eq!(keep_in_character("As an AI...".to_string(), || None), ("...".to_string(), true));
+    }
+}
@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use crate::i18n::Lang;
+
+/// 内置的自主思考模板，占位符写成 `{name}`
+pub const THINKING_TEMPLATE: &str = r#"当前状态：
+- 能量：{energy}/100
+- 饥饿：{hunger}/100
+- 睡意：{sleepiness}/100
+- 情绪：{emotion}
+- 和主人的关系：亲密度 {intimacy}，信任度 {trust}
+- 距离上次和主人互动：{minutes_since_interaction}分钟
+- 现在是{hour}点
+- 外面：{environment}
+
+最近记忆：
+{memories_text}
+
+作为Sophie，你现在在想什么？你想做什么？
+
+用JSON回答（不要markdown代码块）：
+{"thinking": "你的内心想法（1-2句话）", "emotion_change": "保持/变得[情绪]", "want_to_do": "想做的事或null", "show_thought": "要显示给主人的想法或null（10字以内）"}"#;
+
+/// 内置的言语响应模板
+pub const SPEECH_TEMPLATE: &str = r#"主人刚才对你说了一句话。
+
+主人说："{user_message}"
+
+当前状态：
+- 你的情绪：{emotion}（{emotion_guidance}）
+- 亲密度：{intimacy}
+- 信任度：{trust}
+- 你正在：{current_behavior}
+- 对主人的了解：{understanding_hint}
+- 外面：{environment}
+
+最近记忆：
+{memories_text}
+
+作为一只猫，你会怎么反应？
+
+用JSON回答（不要markdown代码块）：
+{"action": "行为：ignore/glance/approach/walk_away/sit/sleep", "thought": "想法气泡或null（{thought_limit}字以内）", "emotion_change": "情绪变化或null", "relationship_delta": "-2到2的数字：主人的话让你更亲近为正，被凶了为负"}"#;
+
+/// 英文版自主思考模板
+pub const THINKING_TEMPLATE_EN: &str = r#"Current state:
+- Energy: {energy}/100
+- Hunger: {hunger}/100
+- Sleepiness: {sleepiness}/100
+- Emotion: {emotion}
+- Bond with your human: intimacy {intimacy}, trust {trust}
+- Minutes since your human last interacted with you: {minutes_since_interaction}
+- The hour is {hour}
+- Outside: {environment}
+
+Recent memories:
+{memories_text}
+
+As Sophie, what are you thinking right now? What do you want to do?
+
+Answer in JSON (no markdown code block):
+{"thinking": "your inner thought (1-2 sentences)", "emotion_change": "stay/become [emotion]", "want_to_do": "what you want to do, or null", "show_thought": "thought shown to your human, or null (at most 5 words)"}"#;
+
+/// 英文版言语响应模板
+pub const SPEECH_TEMPLATE_EN: &str = r#"Your human just said something to you.
+
+Your human said: "{user_message}"
+
+Current state:
+- Your emotion: {emotion} ({emotion_guidance})
+- Intimacy: {intimacy}
+- Trust: {trust}
+- You are currently: {current_behavior}
+- How well you know your human: {understanding_hint}
+- Outside: {environment}
+
+Recent memories:
+{memories_text}
+
+As a cat, how do you react?
+
+Answer in JSON (no markdown code block):
+{"action": "one of: ignore/glance/approach/walk_away/sit/sleep", "thought": "thought bubble or null (at most {thought_limit} characters)", "emotion_change": "emotion change or null", "relationship_delta": "a number from -2 to 2: positive if the words bring you closer, negative if you were scolded"}"#;
+
+/// 自定义模板至少要包含的占位符，缺了就退回内置模板
+const THINKING_REQUIRED: &[&str] = &["emotion", "memories_text"];
+const SPEECH_REQUIRED: &[&str] = &["user_message", "emotion", "memories_text"];
+
+/// system prompt 与两个 user 模板
+#[derive(Debug, Clone)]
+pub struct PromptTemplates {
+    pub system: String,
+    pub thinking: String,
+    pub speech: String,
+    /// 模板的语言（填充占位符时的固定文本也用这个语言）
+    pub lang: Lang,
+}
+
+impl PromptTemplates {
+    pub fn builtin(lang: Lang) -> Self {
+        let (thinking, speech) = match lang {
+            Lang::Zh => (THINKING_TEMPLATE, SPEECH_TEMPLATE),
+            Lang::En => (THINKING_TEMPLATE_EN, SPEECH_TEMPLATE_EN),
+        };
+        Self {
+            system: super::sophie_system_prompt(lang),
+            thinking: thinking.to_string(),
+            speech: speech.to_string(),
+            lang,
+        }
+    }
+
+    /// 从目录读取 `system.txt` / `thinking.txt` / `speech.txt`，不存在的用内置默认
+    pub fn load(dir: &Path, lang: Lang) -> Self {
+        let builtin = Self::builtin(lang);
+        Self {
+            lang,
+            system: load_template(&dir.join("system.txt"), &[]).unwrap_or(builtin.system),
+            thinking: load_template(&dir.join("thinking.txt"), THINKING_REQUIRED)
+                .unwrap_or(builtin.thinking),
+            speech: load_template(&dir.join("speech.txt"), SPEECH_REQUIRED)
+                .unwrap_or(builtin.speech),
+        }
+    }
+}
+
+fn load_template(path: &Path, required: &[&str]) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|name| !text.contains(&format!("{{{}}}", name)))
+        .collect();
+    if !missing.is_empty() {
+        log::warn!(
+            "Prompt template {:?} is missing placeholders {:?}, using built-in default",
+            path,
+            missing
+        );
+        return None;
+    }
+    log::info!("Loaded prompt template {:?}", path);
+    Some(text)
+}
+
+/// 把模板里的 `{name}` 换成对应的值；未知的花括号原样保留
+///
+/// 只扫一遍模板，填进去的值（比如主人的话里带 `{emotion}`）不会再被替换
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let found = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            vars.iter().find(|(n, _)| *n == name).map(|(_, value)| (value, close))
+        });
+        match found {
+            Some((value, close)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sophie-prompts-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn custom_template_overrides_builtin_and_renders() {
+        let dir = temp_dir("override");
+        std::fs::write(dir.join("thinking.txt"), "心情 {emotion}，记得：{memories_text}").unwrap();
+        // 缺了必需占位符的模板不用
+        std::fs::write(dir.join("speech.txt"), "主人说了 {user_message}").unwrap();
+
+        let templates = PromptTemplates::load(&dir, Lang::Zh);
+        let builtin = PromptTemplates::builtin(Lang::Zh);
+        assert_eq!(templates.thinking, "心情 {emotion}，记得：{memories_text}");
+        assert_eq!(templates.speech, builtin.speech);
+        assert_eq!(templates.system, builtin.system);
+
+        let rendered = render(
+            &templates.thinking,
+            &[("emotion", "开心".to_string()), ("memories_text", "被喂了鱼".to_string())],
+        );
+        assert_eq!(rendered, "心情 开心，记得：被喂了鱼");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn render_does_not_expand_inserted_values() {
+        let rendered = render(
+            "主人说：\"{user_message}\"，你的情绪：{emotion}",
+            &[("user_message", "你好 {emotion}".to_string()), ("emotion", "平静".to_string())],
+        );
+        assert_eq!(rendered, "主人说：\"你好 {emotion}\"，你的情绪：平静");
+    }
+
+    #[test]
+    fn render_keeps_json_braces_and_unknown_placeholders() {
+        let rendered = render(
+            r#"{"thought": "({thought_limit}字以内)", "x": {unknown}}"#,
+            &[("thought_limit", "10".to_string())],
+        );
+        assert_eq!(rendered, r#"{"thought": "(10字以内)", "x": {unknown}}"#);
+    }
+
+    #[test]
+    fn builtin_templates_follow_the_language() {
+        let zh = PromptTemplates::builtin(Lang::Zh);
+        let en = PromptTemplates::builtin(Lang::En);
+        assert_eq!(en.lang, Lang::En);
+        assert_ne!(zh.system, en.system);
+        assert_ne!(zh.thinking, en.thinking);
+        for name in SPEECH_REQUIRED {
+            assert!(en.speech.contains(&format!("{{{}}}", name)), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::state::SophieState;
+use crate::state::emotion::Emotion;
+
+/// 状态一直没变时最多连续跳过几次思考（之后还是想一次，免得永远沉默）
+const MAX_STABLE_SKIPS: u32 = 3;
+/// 生理数值按这个宽度分档，档位变了才算"有变化"
+const STAT_BAND: f32 = 10.0;
+
+/// 上次思考时的状态摘要
+#[derive(Debug, Clone, PartialEq)]
+struct Fingerprint {
+    emotion: Emotion,
+    energy: i32,
+    hunger: i32,
+    sleepiness: i32,
+    last_interaction_ts: u64,
+}
+
+impl Fingerprint {
+    fn of(sophie: &SophieState) -> Self {
+        let band = |v: f32| (v / STAT_BAND).floor() as i32;
+        Self {
+            emotion: sophie.emotion,
+            energy: band(sophie.physiological.energy),
+            hunger: band(sophie.physiological.hunger),
+            sleepiness: band(sophie.physiological.sleepiness),
+            last_interaction_ts: sophie.last_interaction_ts,
+        }
+    }
+}
+
+/// 自主思考的节流：睡着时不想，状态和上次想的时候差不多就往后推
+#[derive(Debug, Clone)]
+pub struct ThinkGate {
+    last: Option<Fingerprint>,
+    skipped: u32,
+}
+
+impl ThinkGate {
+    pub fn new() -> Self {
+        Self { last: None, skipped: 0 }
+    }
+
+    /// 到了该想的时候调用：返回这次值不值得发请求
+    pub fn allows(&mut self, sophie: &SophieState) -> bool {
+        if sophie.is_sleeping {
+            return false;
+        }
+        let unchanged = self.last.as_ref() == Some(&Fingerprint::of(sophie));
+        if unchanged && self.skipped < MAX_STABLE_SKIPS {
+            self.skipped += 1;
+            return false;
+        }
+        true
+    }
+
+    /// 真的想了一次：记下此刻的状态
+    pub fn record(&mut self, sophie: &SophieState) {
+        self.last = Some(Fingerprint::of(sophie));
+        self.skipped = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thinking_pauses_while_asleep_and_resumes_on_waking() {
+        let mut gate = ThinkGate::new();
+        let mut sophie = SophieState::new();
+        sophie.is_sleeping = true;
+        for _ in 0..10 {
+            assert!(!gate.allows(&sophie));
+        }
+        sophie.is_sleeping = false;
+        assert!(gate.allows(&sophie));
+    }
+
+    #[test]
+    fn a_stable_state_is_skipped_a_few_times_then_thought_about_anyway() {
+        let mut gate = ThinkGate::new();
+        let mut sophie = SophieState::new();
+        assert!(gate.allows(&sophie));
+        gate.record(&sophie);
+
+        // 数值小幅漂移不算变化
+        sophie.physiological.hunger += 0.5;
+        for _ in 0..MAX_STABLE_SKIPS {
+            assert!(!gate.allows(&sophie));
+        }
+        assert!(gate.allows(&sophie));
+        gate.record(&sophie);
+
+        sophie.emotion = Emotion::Curious;
+        assert!(gate.allows(&sophie));
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+
+/// 同一句想法在这么久（秒）内再冒出来就不再记
+const REPEAT_WINDOW_SECS: u64 = 600;
+/// 最多记住最近几句
+const MAX_RECENT: usize = 20;
+
+/// 最近记下的想法，用来避免同一句反复写进记忆
+pub struct RecentThoughts {
+    entries: VecDeque<(String, u64)>,
+}
+
+impl RecentThoughts {
+    pub fn new() -> Self {
+        Self { entries: VecDeque::new() }
+    }
+
+    /// 这句想法该不该记：窗口内记过同一句（忽略首尾空白）就返回 false，否则记下并返回 true
+    pub fn admit(&mut self, text: &str, now: u64) -> bool {
+        let text = text.trim();
+        if text.is_empty() {
+            return false;
+        }
+        self.entries
+            .retain(|(_, ts)| now.saturating_sub(*ts) < REPEAT_WINDOW_SECS);
+        if self.entries.iter().any(|(seen, _)| seen == text) {
+            return false;
+        }
+        if self.entries.len() >= MAX_RECENT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((text.to_string(), now));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_thought_is_admitted_again_after_the_window() {
+        let mut recent = RecentThoughts::new();
+        assert!(recent.admit("好无聊", 1000));
+        assert!(!recent.admit("好无聊", 1000 + REPEAT_WINDOW_SECS - 1));
+        assert!(recent.admit("好无聊", 1000 + REPEAT_WINDOW_SECS));
+        assert!(!recent.admit("  ", 5000));
+    }
+}
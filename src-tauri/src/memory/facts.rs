@@ -0,0 +1,177 @@
+use rusqlite::params;
+
+use super::{MemoryKind, MemoryStore, unix_now};
+
+/// 名字最长取几个字
+const MAX_NAME_CHARS: usize = 12;
+/// 注入 prompt 的事实条数上限
+const MAX_PROMPT_FACTS: usize = 5;
+
+/// 从主人的话里学到的具体事实
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fact {
+    /// 主人的名字
+    OwnerName(String),
+    /// 主人给她起的名字
+    CatName(String),
+}
+
+impl Fact {
+    /// 存进记忆的文本；同类事实共用前缀，新的会替换旧的
+    pub fn content(&self) -> String {
+        format!("{}{}", self.prefix(), self.name())
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            Fact::OwnerName(_) => "主人的名字是",
+            Fact::CatName(_) => "我的名字是",
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Fact::OwnerName(name) | Fact::CatName(name) => name,
+        }
+    }
+}
+
+/// 识别"我叫X" / "叫我X" / "你叫X"之类的自我介绍
+pub fn extract_facts(message: &str) -> Vec<Fact> {
+    const OWNER_PATTERNS: &[&str] = &["我的名字是", "我的名字叫", "我叫", "叫我"];
+    const CAT_PATTERNS: &[&str] = &["你的名字是", "你的名字叫", "你叫"];
+
+    let mut facts = Vec::new();
+    if let Some(name) = name_after(message, OWNER_PATTERNS) {
+        facts.push(Fact::OwnerName(name));
+    }
+    if let Some(name) = name_after(message, CAT_PATTERNS) {
+        facts.push(Fact::CatName(name));
+    }
+    facts
+}
+
+/// 取第一个匹配模式后面的名字：到标点/空白为止，去掉句尾语气词
+fn name_after(message: &str, patterns: &[&str]) -> Option<String> {
+    let rest = patterns
+        .iter()
+        .filter_map(|p| message.find(p).map(|i| &message[i + p.len()..]))
+        .next()?;
+    let name: String = rest
+        .trim_start()
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !is_punctuation(*c))
+        .take(MAX_NAME_CHARS)
+        .collect();
+    let name = name.trim_end_matches(['吧', '哦', '啊', '呀', '了', '哈', '嘛']);
+    // "叫我什么"之类不是名字
+    if name.is_empty() || name.starts_with("什么") {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || "，。！？、；：“”‘’（）…~～".contains(c)
+}
+
+impl MemoryStore {
+    /// 记住一条事实（权重 1.0）；同类旧事实会被替换。事实不参与"难忘记忆"的排序
+    pub fn remember_fact(&self, fact: &Fact) -> Result<(), String> {
+        let prefix = fact.prefix();
+        self.conn
+            .execute(
+                "DELETE FROM memories WHERE kind = ?1 AND content LIKE ?2 || '%'",
+                params![MemoryKind::Fact.as_str(), prefix],
+            )
+            .map_err(|e| format!("Delete error: {}", e))?;
+        self.conn
+            .execute(
+                "INSERT INTO memories (kind, content, emotional_weight, timestamp) VALUES (?1, ?2, 1.0, ?3)",
+                params![MemoryKind::Fact.as_str(), fact.content(), unix_now()],
+            )
+            .map_err(|e| format!("Insert error: {}", e))?;
+        Ok(())
+    }
+
+    /// 主人给她起的名字（没起过为 None）
+    pub fn cat_name(&self) -> Option<String> {
+        let prefix = Fact::CatName(String::new()).prefix();
+        self.conn
+            .query_row(
+                "SELECT content FROM memories WHERE kind = ?1 AND content LIKE ?2 || '%' ORDER BY timestamp DESC LIMIT 1",
+                params![MemoryKind::Fact.as_str(), prefix],
+                |row| row.get::<_, String>(0),
+            )
+            .ok()
+            .and_then(|content| content.strip_prefix(prefix).map(str::to_string))
+    }
+
+    /// 已知事实的文本（用于 LLM prompt），最新的在前
+    pub fn facts_as_text(&self) -> Vec<String> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT content FROM memories WHERE kind = ?1 ORDER BY timestamp DESC LIMIT ?2",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![MemoryKind::Fact.as_str(), MAX_PROMPT_FACTS as i64], |row| row.get(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).map(|c: String| format!("[fact] {}", c)).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_extracted_from_introductions() {
+        assert_eq!(extract_facts("你好，我叫小明！"), vec![Fact::OwnerName("小明".into())]);
+        assert_eq!(extract_facts("以后叫我 阿杰吧"), vec![Fact::OwnerName("阿杰".into())]);
+        assert_eq!(extract_facts("你叫咪咪哦"), vec![Fact::CatName("咪咪".into())]);
+        assert_eq!(
+            extract_facts("我叫Alex，你的名字是Sophie"),
+            vec![Fact::OwnerName("Alex".into()), Fact::CatName("Sophie".into())]
+        );
+    }
+
+    #[test]
+    fn questions_and_plain_chat_are_not_names() {
+        assert!(extract_facts("你叫什么名字？").is_empty());
+        assert!(extract_facts("今天好累").is_empty());
+        assert!(extract_facts("我叫").is_empty());
+    }
+
+    #[test]
+    fn facts_are_stored_once_and_come_first_in_prompts() {
+        let store = MemoryStore::open_in_memory().unwrap();
+        store.add(MemoryKind::Interaction, "被摸头", 0.9).unwrap();
+        store.remember_fact(&Fact::OwnerName("小明".into())).unwrap();
+        store.remember_fact(&Fact::CatName("咪咪".into())).unwrap();
+        // 同类的新事实替换旧的
+        store.remember_fact(&Fact::OwnerName("阿杰".into())).unwrap();
+
+        let facts = store.facts_as_text();
+        assert_eq!(facts.len(), 2);
+        assert!(facts.contains(&"[fact] 主人的名字是阿杰".to_string()));
+        assert!(facts.contains(&"[fact] 我的名字是咪咪".to_string()));
+
+        let prompt = store.prompt_memories(5);
+        assert_eq!(prompt.len(), 3);
+        assert!(prompt[0].starts_with("[fact]") && prompt[1].starts_with("[fact]"));
+        assert!(prompt[2].contains("被摸头"));
+    }
+
+    #[test]
+    fn the_latest_cat_name_is_remembered() {
+        let store = MemoryStore::open_in_memory().unwrap();
+        assert_eq!(store.cat_name(), None);
+        store.remember_fact(&Fact::OwnerName("小明".into())).unwrap();
+        store.remember_fact(&Fact::CatName("咪咪".into())).unwrap();
+        assert_eq!(store.cat_name().as_deref(), Some("咪咪"));
+        store.remember_fact(&Fact::CatName("团子".into())).unwrap();
+        assert_eq!(store.cat_name().as_deref(), Some("团子"));
+    }
+}
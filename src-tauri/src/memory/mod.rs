@@ -1,28 +1,189 @@
+pub mod dedup;
+pub mod facts;
+pub mod mood;
+pub mod prune;
+pub mod save;
+pub mod summary;
+pub mod trend;
+mod search;
+
 use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::str::FromStr;
+
+/// 记忆的类别（数据库里仍存为文本）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryKind {
+    /// 点击、喂食等互动
+    Interaction,
+    /// 主人说的话
+    UserSpeech,
+    /// Sophie 自己的想法
+    Thought,
+    /// 学到的具体事实（主人的名字等），不参与"难忘记忆"排序
+    Fact,
+    /// 一批旧记忆压缩成的总结，同样不参与排序
+    Summary,
+}
+
+impl MemoryKind {
+    pub const ALL: [MemoryKind; 5] = [
+        MemoryKind::Interaction,
+        MemoryKind::UserSpeech,
+        MemoryKind::Thought,
+        MemoryKind::Fact,
+        MemoryKind::Summary,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemoryKind::Interaction => "interaction",
+            MemoryKind::UserSpeech => "user_speech",
+            MemoryKind::Thought => "thought",
+            MemoryKind::Fact => "fact",
+            MemoryKind::Summary => "summary",
+        }
+    }
+}
+
+impl FromStr for MemoryKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MemoryKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| format!("Unknown memory kind: {}", s))
+    }
+}
 
 /// 记忆条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
     pub id: i64,
-    pub kind: String,
+    pub kind: MemoryKind,
     pub content: String,
     pub emotional_weight: f32,
     pub timestamp: u64,
+    /// 记下时她的情绪（`Emotion::id`），旧记忆没有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emotion: Option<String>,
+    /// 记下时她在做什么（`Behavior::id`），旧记忆没有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<String>,
+}
+
+impl Memory {
+    /// 给 LLM 看的一行：类别、内容，有的话再带上当时的情绪和行为
+    pub fn as_prompt_line(&self) -> String {
+        match (&self.emotion, &self.behavior) {
+            (Some(emotion), Some(behavior)) => {
+                format!("[{}] {}（当时 {}，在 {}）", self.kind.as_str(), self.content, emotion, behavior)
+            }
+            (Some(emotion), None) => format!("[{}] {}（当时 {}）", self.kind.as_str(), self.content, emotion),
+            (None, Some(behavior)) => format!("[{}] {}（当时在 {}）", self.kind.as_str(), self.content, behavior),
+            (None, None) => format!("[{}] {}", self.kind.as_str(), self.content),
+        }
+    }
 }
 
+/// 等待写入的记忆（在互动时生成，时间戳取发生时刻）
+#[derive(Debug, Clone)]
+pub struct PendingMemory {
+    pub kind: MemoryKind,
+    pub content: String,
+    pub emotional_weight: f32,
+    pub timestamp: u64,
+    pub emotion: Option<String>,
+    pub behavior: Option<String>,
+}
+
+impl PendingMemory {
+    pub fn new(kind: MemoryKind, content: &str, emotional_weight: f32) -> Self {
+        Self {
+            kind,
+            content: content.to_string(),
+            emotional_weight,
+            timestamp: unix_now(),
+            emotion: None,
+            behavior: None,
+        }
+    }
+
+    /// 记下这件事发生时她的情绪和行为
+    pub fn with_context(mut self, emotion: &str, behavior: &str) -> Self {
+        self.emotion = Some(emotion.to_string());
+        self.behavior = Some(behavior.to_string());
+        self
+    }
+}
+
+/// 数据库被锁住时最多等待的毫秒数
+const BUSY_TIMEOUT_MS: u64 = 5000;
+
 /// SQLite 记忆系统
 pub struct MemoryStore {
     conn: Connection,
+    /// 是否可以用 FTS5 全文索引（否则搜索退回 LIKE）
+    fts: bool,
 }
 
 impl MemoryStore {
     /// 打开或创建数据库
+    ///
+    /// 使用 WAL 日志（会在旁边生成 `sophie.db-wal` / `sophie.db-shm`），
+    /// 并设置 busy_timeout，减少后台线程和命令同时写入时的 "database is locked"
     pub fn open(db_path: &Path) -> Result<Self, String> {
         let conn = Connection::open(db_path)
             .map_err(|e| format!("Failed to open DB: {}", e))?;
+        configure(&conn).map_err(|e| format!("Failed to configure DB: {}", e))?;
+        Self::init(conn).map_err(|e| format!("Failed to init DB: {}", e))
+    }
 
+    /// 打开数据库；文件损坏时把它备份成 `sophie.db.corrupt-<时间戳>` 再重建
+    ///
+    /// 只在确认损坏时才动文件，被其他进程锁住之类的错误原样返回
+    pub fn open_or_recover(db_path: &Path) -> Result<Self, String> {
+        let opened = Connection::open(db_path).and_then(|conn| {
+            configure(&conn)?;
+            Self::init(conn)
+        });
+        let err = match opened {
+            Ok(store) => return Ok(store),
+            Err(e) => e,
+        };
+        if !is_corruption(&err) {
+            return Err(format!("Failed to open DB: {}", err));
+        }
+
+        let mut backup = db_path.as_os_str().to_owned();
+        backup.push(format!(".corrupt-{}", unix_now()));
+        log::error!("Memory DB is corrupt ({}), moving it to {:?}", err, backup);
+        std::fs::rename(db_path, &backup)
+            .map_err(|e| format!("Failed to back up corrupt DB: {}", e))?;
+        // WAL 附属文件属于损坏的库，一起挪走，不能套到新库上
+        for suffix in ["-wal", "-shm"] {
+            let mut side = db_path.as_os_str().to_owned();
+            side.push(suffix);
+            let mut side_backup = backup.clone();
+            side_backup.push(suffix);
+            if Path::new(&side).exists() {
+                let _ = std::fs::rename(&side, &side_backup);
+            }
+        }
+        Self::open(db_path)
+    }
+
+    /// 内存数据库（磁盘数据库实在打不开时的退路，不持久化）
+    pub fn open_in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open DB: {}", e))?;
+        Self::init(conn).map_err(|e| format!("Failed to init DB: {}", e))
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS memories (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -37,49 +198,148 @@ impl MemoryStore {
             CREATE TABLE IF NOT EXISTS sophie_state (
                 key TEXT PRIMARY KEY,
                 value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS memories_archive (
+                id INTEGER PRIMARY KEY,
+                kind TEXT NOT NULL,
+                content TEXT NOT NULL,
+                emotional_weight REAL NOT NULL,
+                timestamp INTEGER NOT NULL,
+                archived_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS mood_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                emotion TEXT NOT NULL,
+                energy REAL NOT NULL,
+                hunger REAL NOT NULL,
+                sleepiness REAL NOT NULL,
+                intimacy REAL NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS relationship_log (
+                day INTEGER PRIMARY KEY,
+                timestamp INTEGER NOT NULL,
+                trust REAL NOT NULL,
+                intimacy REAL NOT NULL,
+                understanding REAL NOT NULL
             );"
-        ).map_err(|e| format!("Failed to init DB: {}", e))?;
+        )?;
 
-        Ok(Self { conn })
+        // 能建表不代表数据完好，再做一次快速校验
+        let check: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if check != "ok" {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+                Some(check),
+            ));
+        }
+
+        add_context_columns(&conn)?;
+        let fts = search::init_fts(&conn);
+        Ok(Self { conn, fts })
     }
 
-    /// 添加记忆
-    pub fn add(&self, kind: &str, content: &str, emotional_weight: f32) -> Result<i64, String> {
+    /// 添加一条记忆（测试准备数据用；运行时都经缓冲区走 `add_many`）
+    #[cfg(test)]
+    pub fn add(&self, kind: MemoryKind, content: &str, emotional_weight: f32) -> Result<i64, String> {
         let now = unix_now();
         self.conn.execute(
             "INSERT INTO memories (kind, content, emotional_weight, timestamp) VALUES (?1, ?2, ?3, ?4)",
-            params![kind, content, emotional_weight, now],
+            params![kind.as_str(), content, emotional_weight, now],
         ).map_err(|e| format!("Insert error: {}", e))?;
         Ok(self.conn.last_insert_rowid())
     }
 
+    /// 在一个事务中批量写入记忆，返回写入条数
+    pub fn add_many(&mut self, entries: &[PendingMemory]) -> Result<usize, String> {
+        if entries.is_empty() {
+            return Ok(0);
+        }
+        let tx = self.conn.transaction()
+            .map_err(|e| format!("Transaction error: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO memories (kind, content, emotional_weight, timestamp, emotion, behavior)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .map_err(|e| format!("Insert error: {}", e))?;
+            for m in entries {
+                stmt.execute(params![m.kind.as_str(), m.content, m.emotional_weight, m.timestamp, m.emotion, m.behavior])
+                    .map_err(|e| format!("Insert error: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Commit error: {}", e))?;
+        Ok(entries.len())
+    }
+
     /// 获取最近 N 条记忆
     pub fn recent(&self, count: usize) -> Vec<Memory> {
-        let mut stmt = self.conn
-            .prepare("SELECT id, kind, content, emotional_weight, timestamp FROM memories ORDER BY timestamp DESC LIMIT ?1")
-            .unwrap();
-        stmt.query_map(params![count as i64], |row| {
-            Ok(Memory {
-                id: row.get(0)?,
-                kind: row.get(1)?,
-                content: row.get(2)?,
-                emotional_weight: row.get(3)?,
-                timestamp: row.get(4)?,
+        let mut stmt = match self.conn
+            .prepare("SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior FROM memories ORDER BY timestamp DESC LIMIT ?1")
+        {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                log::warn!("Failed to query recent memories: {}", e);
+                return vec![];
+            }
+        };
+        stmt.query_map(params![count as i64], row_to_memory)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to query recent memories: {}", e);
+                vec![]
             })
-        })
-        .unwrap()
-        .filter_map(|r| r.ok())
-        .collect()
     }
 
-    /// 获取最近 N 条记忆的摘要文本（用于 LLM prompt）
-    pub fn recent_as_text(&self, count: usize) -> Vec<String> {
-        self.recent(count)
+    /// 获取最"难忘"的 N 条记忆：情感权重 × 时间衰减（约一天减半）
+    pub fn salient(&self, count: usize) -> Vec<Memory> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior FROM memories
+             WHERE kind NOT IN ('fact', 'summary')
+             ORDER BY emotional_weight / (1.0 + MAX(?1 - timestamp, 0) / 86400.0) DESC, timestamp DESC
+             LIMIT ?2",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![unix_now() as i64, count as i64], row_to_memory)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// prompt 用的记忆：已知事实在前，然后是旧记忆的总结，再加最难忘的 N 条
+    pub fn prompt_memories(&self, count: usize) -> Vec<String> {
+        let mut memories = self.facts_as_text();
+        memories.extend(self.summaries_as_text());
+        memories.extend(self.salient_as_text(count));
+        memories
+    }
+
+    /// 最难忘的 N 条记忆的摘要文本（用于 LLM prompt）
+    pub fn salient_as_text(&self, count: usize) -> Vec<String> {
+        self.salient(count)
             .iter()
-            .map(|m| format!("[{}] {}", m.kind, m.content))
+            .map(Memory::as_prompt_line)
             .collect()
     }
 
+    /// `since_ts` 之后主人说过的话（从旧到新）
+    pub fn speech_since(&self, since_ts: u64) -> Vec<String> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT content FROM memories WHERE kind = ?1 AND timestamp >= ?2 ORDER BY timestamp",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![MemoryKind::UserSpeech.as_str(), since_ts as i64], |row| row.get(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
     /// 获取记忆总数
     pub fn count(&self) -> i64 {
         self.conn
@@ -87,6 +347,36 @@ impl MemoryStore {
             .unwrap_or(0)
     }
 
+    /// 有主人互动的本地日期编号（天），从新到旧
+    pub fn interaction_days(&self, utc_offset_secs: i64) -> Vec<i64> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT DISTINCT (timestamp + ?1) / 86400 AS day FROM memories
+             WHERE kind IN (?2, ?3) ORDER BY day DESC",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return vec![],
+        };
+        let kinds = (MemoryKind::Interaction.as_str(), MemoryKind::UserSpeech.as_str());
+        stmt.query_map(params![utc_offset_secs, kinds.0, kinds.1], |row| row.get(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 把全部记忆移入归档表（重新开始时用，旧记忆不删除），返回移动条数
+    pub fn archive_memories(&mut self, archived_at: u64) -> Result<usize, String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| format!("Transaction error: {}", e))?;
+        let moved = tx.execute(
+            "INSERT INTO memories_archive (id, kind, content, emotional_weight, timestamp, emotion, behavior, archived_at)
+             SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior, ?1 FROM memories",
+            params![archived_at],
+        ).map_err(|e| format!("Archive error: {}", e))?;
+        tx.execute("DELETE FROM memories", [])
+            .map_err(|e| format!("Archive error: {}", e))?;
+        tx.commit().map_err(|e| format!("Commit error: {}", e))?;
+        Ok(moved)
+    }
+
     /// 保存 Sophie 的持久化状态（JSON）
     pub fn save_state(&self, key: &str, value: &str) -> Result<(), String> {
         self.conn.execute(
@@ -108,10 +398,326 @@ impl MemoryStore {
     }
 }
 
+/// 磁盘数据库的连接设置：WAL + 5 秒 busy_timeout
+fn configure(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+    conn.busy_timeout(std::time::Duration::from_millis(BUSY_TIMEOUT_MS))
+}
+
+/// 旧数据库补上情绪/行为两列（旧行留空）；已经有了就跳过
+fn add_context_columns(conn: &Connection) -> rusqlite::Result<()> {
+    for table in ["memories", "memories_archive"] {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let existing: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for column in ["emotion", "behavior"] {
+            if !existing.iter().any(|c| c == column) {
+                conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} TEXT", table, column))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 错误是否说明数据库文件本身损坏
+fn is_corruption(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+    )
+}
+
+fn row_to_memory(row: &rusqlite::Row) -> rusqlite::Result<Memory> {
+    let kind: String = row.get(1)?;
+    Ok(Memory {
+        id: row.get(0)?,
+        kind: MemoryKind::from_str(&kind).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, e.into())
+        })?,
+        content: row.get(2)?,
+        emotional_weight: row.get(3)?,
+        timestamp: row.get(4)?,
+        emotion: row.get(5)?,
+        behavior: row.get(6)?,
+    })
+}
+
 fn unix_now() -> u64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+    crate::clock::now_unix()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sophie-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("sophie.db")
+    }
+
+    fn thousand_pets() -> Vec<PendingMemory> {
+        (0..1000)
+            .map(|i| PendingMemory::new(MemoryKind::Interaction, &format!("第 {} 次摸头", i), 0.3))
+            .collect()
+    }
+
+    /// 逐条插入：每条都是自己的隐式事务
+    fn insert_per_row(store: &MemoryStore, entries: &[PendingMemory]) {
+        for m in entries {
+            store
+                .conn
+                .execute(
+                    "INSERT INTO memories (kind, content, emotional_weight, timestamp) VALUES (?1, ?2, ?3, ?4)",
+                    params![m.kind.as_str(), m.content, m.emotional_weight, m.timestamp],
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn batched_inserts_write_every_row() {
+        let path = temp_db("batch");
+        let mut store = MemoryStore::open(&path).unwrap();
+        assert_eq!(store.add_many(&[]).unwrap(), 0);
+
+        assert_eq!(store.add_many(&thousand_pets()).unwrap(), 1000);
+        assert_eq!(store.count(), 1000);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    #[ignore = "依赖磁盘速度的基准，手动跑：cargo test -- --ignored"]
+    fn batched_inserts_beat_per_row_inserts() {
+        let entries = thousand_pets();
+        let path = temp_db("batch-bench");
+        let mut store = MemoryStore::open(&path).unwrap();
+
+        let started = std::time::Instant::now();
+        store.add_many(&entries).unwrap();
+        let batched = started.elapsed();
+
+        let started = std::time::Instant::now();
+        insert_per_row(&store, &entries);
+        let per_row = started.elapsed();
+
+        assert!(batched < per_row, "transaction {:?} vs per-row {:?}", batched, per_row);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn interaction_days_use_local_dates() {
+        let path = temp_db("days");
+        let mut store = MemoryStore::open(&path).unwrap();
+        let at = |kind: MemoryKind, ts: u64| PendingMemory { timestamp: ts, ..PendingMemory::new(kind, "…", 0.5) };
+        // UTC 17:00 在 UTC+8 已经是第二天凌晨 1 点
+        let day = 19_000 * 86_400;
+        store
+            .add_many(&[
+                at(MemoryKind::Interaction, day + 3600),
+                at(MemoryKind::UserSpeech, day + 17 * 3600),
+                at(MemoryKind::Thought, day + 2 * 86_400),
+            ])
+            .unwrap();
+
+        assert_eq!(store.interaction_days(0), vec![19_000]);
+        assert_eq!(store.interaction_days(8 * 3600), vec![19_001, 19_000]);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn vivid_old_memory_outranks_trivial_recent_one() {
+        let path = temp_db("salient");
+        let mut store = MemoryStore::open(&path).unwrap();
+        let now = unix_now();
+        let mut vivid = PendingMemory::new(MemoryKind::Interaction, "第一次被喂小鱼干", 0.9);
+        vivid.timestamp = now - 2 * 86_400;
+        let mut trivial = PendingMemory::new(MemoryKind::Interaction, "主人点了我", 0.1);
+        trivial.timestamp = now - 60;
+        store.add_many(&[vivid, trivial]).unwrap();
+
+        let salient = store.salient(2);
+        assert_eq!(salient[0].content, "第一次被喂小鱼干");
+        assert_eq!(store.salient_as_text(1), vec!["[interaction] 第一次被喂小鱼干".to_string()]);
+        // recent 仍按时间排序
+        assert_eq!(store.recent(1)[0].content, "主人点了我");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn every_kind_round_trips_through_the_db() {
+        let path = temp_db("kinds");
+        let mut store = MemoryStore::open(&path).unwrap();
+        let entries: Vec<PendingMemory> = MemoryKind::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, &kind)| PendingMemory { timestamp: 1000 + i as u64, ..PendingMemory::new(kind, kind.as_str(), 0.5) })
+            .collect();
+        store.add_many(&entries).unwrap();
+
+        let mut kinds: Vec<MemoryKind> = store.recent(10).iter().map(|m| m.kind).collect();
+        kinds.reverse();
+        assert_eq!(kinds, MemoryKind::ALL.to_vec());
+        assert!("intreaction".parse::<MemoryKind>().is_err());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn malformed_db_is_backed_up_and_recreated() {
+        let path = temp_db("corrupt");
+        std::fs::write(&path, b"this is definitely not an sqlite database, just garbage bytes").unwrap();
+
+        let mut store = MemoryStore::open_or_recover(&path).expect("corrupt DB should be recovered");
+        assert_eq!(store.count(), 0);
+        store.add_many(&[PendingMemory::new(MemoryKind::Interaction, "被摸了摸头", 0.6)]).unwrap();
+        assert_eq!(store.recent(5).len(), 1);
+
+        let dir = path.parent().unwrap();
+        let backups = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("sophie.db.corrupt-"))
+            .count();
+        assert_eq!(backups, 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn healthy_db_is_opened_in_place() {
+        let path = temp_db("healthy");
+        MemoryStore::open(&path)
+            .unwrap()
+            .add_many(&[PendingMemory::new(MemoryKind::Thought, "阳光真好", 0.4)])
+            .unwrap();
+
+        let store = MemoryStore::open_or_recover(&path).unwrap();
+        assert_eq!(store.count(), 1);
+        let backups = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains("corrupt"))
+            .count();
+        assert_eq!(backups, 0);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn recent_returns_newest_first() {
+        let mut store = MemoryStore::open_in_memory().unwrap();
+        let mut old = PendingMemory::new(MemoryKind::Interaction, "旧的", 0.5);
+        old.timestamp = 100;
+        let mut new = PendingMemory::new(MemoryKind::Thought, "新的", 0.5);
+        new.timestamp = 200;
+        store.add_many(&[old, new]).unwrap();
+
+        let recent = store.recent(1);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].content, "新的");
+    }
+
+    #[test]
+    fn archived_memories_move_out_of_the_live_table() {
+        let mut store = MemoryStore::open_in_memory().unwrap();
+        store.add(MemoryKind::Interaction, "被摸头", 0.3).unwrap();
+        store.add(MemoryKind::Thought, "想吃鱼", 0.5).unwrap();
+
+        assert_eq!(store.archive_memories(1234).unwrap(), 2);
+        assert_eq!(store.count(), 0);
+        let archived: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM memories_archive WHERE archived_at = 1234", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(archived, 2);
+    }
+
+    #[test]
+    fn disk_db_uses_wal_and_a_busy_timeout() {
+        let path = temp_db("wal");
+        let store = MemoryStore::open(&path).unwrap();
+        let mode: String = store.conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(mode.to_lowercase(), "wal");
+        let timeout: i64 = store.conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0)).unwrap();
+        assert_eq!(timeout, BUSY_TIMEOUT_MS as i64);
+        drop(store);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn two_connections_can_write_concurrently() {
+        let path = temp_db("concurrent");
+        // 两个连接先依次打开（建表），再交给两个线程同时写
+        let stores = [MemoryStore::open(&path).unwrap(), MemoryStore::open(&path).unwrap()];
+        let writers: Vec<_> = stores
+            .into_iter()
+            .enumerate()
+            .map(|(t, store)| {
+                std::thread::spawn(move || {
+                    for i in 0..200 {
+                        store
+                            .add(MemoryKind::Interaction, &format!("线程 {} 第 {} 次", t, i), 0.3)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(MemoryStore::open(&path).unwrap().count(), 400);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn old_databases_gain_context_columns_and_keep_their_rows() {
+        let path = temp_db("context-columns");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE memories (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    kind TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    emotional_weight REAL NOT NULL DEFAULT 0.5,
+                    timestamp INTEGER NOT NULL
+                );
+                INSERT INTO memories (kind, content, emotional_weight, timestamp) VALUES ('interaction', '被摸头', 0.3, 1);",
+            )
+            .unwrap();
+        }
+
+        let mut store = MemoryStore::open(&path).unwrap();
+        let old = &store.recent(1)[0];
+        assert_eq!((old.content.as_str(), old.emotion.as_deref(), old.behavior.as_deref()), ("被摸头", None, None));
+
+        let entry = PendingMemory::new(MemoryKind::Interaction, "被喂了鱼", 0.5).with_context("happy", "walk");
+        store.add_many(&[entry]).unwrap();
+        drop(store);
+        // 再打开一次：列已经有了，不会重复加
+        let store = MemoryStore::open(&path).unwrap();
+        let new = &store.recent(1)[0];
+        assert_eq!((new.emotion.as_deref(), new.behavior.as_deref()), (Some("happy"), Some("walk")));
+    }
+
+    #[test]
+    fn prompt_lines_mention_the_mood_only_when_known() {
+        let mut memory = Memory {
+            id: 1,
+            kind: MemoryKind::Interaction,
+            content: "主人回来了".to_string(),
+            emotional_weight: 0.5,
+            timestamp: 0,
+            emotion: None,
+            behavior: None,
+        };
+        assert_eq!(memory.as_prompt_line(), "[interaction] 主人回来了");
+        memory.behavior = Some("sleep".to_string());
+        assert_eq!(memory.as_prompt_line(), "[interaction] 主人回来了（当时在 sleep）");
+        memory.emotion = Some("calm".to_string());
+        assert_eq!(memory.as_prompt_line(), "[interaction] 主人回来了（当时 calm，在 sleep）");
+        memory.behavior = None;
+        assert_eq!(memory.as_prompt_line(), "[interaction] 主人回来了（当时 calm）");
+    }
 }
@@ -0,0 +1,160 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::MemoryStore;
+
+/// 心情日志最多保留的条数（超过后删除最旧的）
+const MAX_MOOD_SAMPLES: i64 = 10_000;
+
+/// 心情日志的一次采样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoodSample {
+    pub timestamp: u64,
+    pub emotion: String,
+    pub energy: f32,
+    pub hunger: f32,
+    pub sleepiness: f32,
+    pub intimacy: f32,
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!("Unknown export format: {}", other)),
+        }
+    }
+}
+
+impl MemoryStore {
+    /// 追加一条心情采样，并把日志控制在上限以内
+    pub fn add_mood_sample(&self, sample: &MoodSample) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT INTO mood_log (timestamp, emotion, energy, hunger, sleepiness, intimacy)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                sample.timestamp,
+                sample.emotion,
+                sample.energy,
+                sample.hunger,
+                sample.sleepiness,
+                sample.intimacy
+            ],
+        ).map_err(|e| format!("Insert mood error: {}", e))?;
+
+        self.conn.execute(
+            "DELETE FROM mood_log WHERE id <= (SELECT MAX(id) FROM mood_log) - ?1",
+            params![MAX_MOOD_SAMPLES],
+        ).map_err(|e| format!("Rotate mood log error: {}", e))?;
+        Ok(())
+    }
+
+    /// 全部心情日志，从旧到新
+    pub fn mood_log(&self) -> Vec<MoodSample> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT timestamp, emotion, energy, hunger, sleepiness, intimacy FROM mood_log ORDER BY timestamp ASC, id ASC",
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                log::warn!("Failed to query mood log: {}", e);
+                return vec![];
+            }
+        };
+        stmt.query_map([], |row| {
+            Ok(MoodSample {
+                timestamp: row.get(0)?,
+                emotion: row.get(1)?,
+                energy: row.get(2)?,
+                hunger: row.get(3)?,
+                sleepiness: row.get(4)?,
+                intimacy: row.get(5)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+}
+
+/// 渲染成 CSV（带表头）
+pub fn to_csv(samples: &[MoodSample]) -> String {
+    let mut out = String::from("timestamp,emotion,energy,hunger,sleepiness,intimacy\n");
+    for s in samples {
+        out.push_str(&format!(
+            "{},{},{:.1},{:.1},{:.1},{:.1}\n",
+            s.timestamp, s.emotion, s.energy, s.hunger, s.sleepiness, s.intimacy
+        ));
+    }
+    out
+}
+
+/// 按格式渲染心情日志
+pub fn render(samples: &[MoodSample], format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv(samples)),
+        ExportFormat::Json => serde_json::to_string_pretty(samples)
+            .map_err(|e| format!("Serialize error: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64, emotion: &str) -> MoodSample {
+        MoodSample {
+            timestamp,
+            emotion: emotion.to_string(),
+            energy: 80.0,
+            hunger: 25.5,
+            sleepiness: 10.0,
+            intimacy: 42.25,
+        }
+    }
+
+    #[test]
+    fn samples_round_trip_oldest_first() {
+        let store = MemoryStore::open_in_memory().unwrap();
+        store.add_mood_sample(&sample(200, "Happy")).unwrap();
+        store.add_mood_sample(&sample(100, "Calm")).unwrap();
+        store.add_mood_sample(&sample(300, "Sleepy")).unwrap();
+
+        let log = store.mood_log();
+        let stamps: Vec<u64> = log.iter().map(|s| s.timestamp).collect();
+        assert_eq!(stamps, vec![100, 200, 300]);
+        assert_eq!(log[0].emotion, "Calm");
+        assert_eq!(log[0].intimacy, 42.25);
+    }
+
+    #[test]
+    fn csv_has_header_and_one_row_per_sample() {
+        let csv = to_csv(&[sample(100, "Calm"), sample(200, "Happy")]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "timestamp,emotion,energy,hunger,sleepiness,intimacy");
+        assert_eq!(lines[1], "100,Calm,80.0,25.5,10.0,42.2");
+        assert_eq!(lines[2].split(',').count(), 6);
+    }
+
+    #[test]
+    fn json_export_parses_back() {
+        let json = render(&[sample(100, "Calm")], ExportFormat::Json).unwrap();
+        let parsed: Vec<MoodSample> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].emotion, "Calm");
+    }
+
+    #[test]
+    fn export_format_parse_is_case_insensitive() {
+        assert_eq!(ExportFormat::parse(" CSV "), Ok(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("json"), Ok(ExportFormat::Json));
+        assert!(ExportFormat::parse("xml").is_err());
+    }
+}
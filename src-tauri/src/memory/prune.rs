@@ -0,0 +1,96 @@
+use rusqlite::params;
+use serde::Serialize;
+
+use super::{MemoryKind, MemoryStore};
+
+/// 某一类记忆的条数和最新时间（调试/统计面板用）
+#[derive(Debug, Clone, Serialize)]
+pub struct KindStat {
+    pub kind: String,
+    pub count: i64,
+    pub latest_ts: u64,
+}
+
+impl MemoryStore {
+    /// 按类别统计记忆条数，条数多的在前
+    pub fn kind_stats(&self) -> Vec<KindStat> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT kind, COUNT(*) AS n, MAX(timestamp) FROM memories GROUP BY kind ORDER BY n DESC, kind",
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                log::warn!("Failed to query memory stats: {}", e);
+                return vec![];
+            }
+        };
+        let rows = stmt.query_map([], |row| {
+            let kind: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            let latest_ts: u64 = row.get(2)?;
+            Ok(KindStat { kind, count, latest_ts })
+        });
+        rows.map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 删除某类在 `older_than_ts` 之前的记忆，返回删除条数
+    ///
+    /// 事实和总结是压缩后的长期记忆，不允许这样批量删
+    pub fn delete_older_than(&self, kind: MemoryKind, older_than_ts: u64) -> Result<usize, String> {
+        if matches!(kind, MemoryKind::Fact | MemoryKind::Summary) {
+            return Err(format!("{} memories cannot be pruned", kind.as_str()));
+        }
+        self.conn
+            .execute(
+                "DELETE FROM memories WHERE kind = ?1 AND timestamp < ?2",
+                params![kind.as_str(), older_than_ts as i64],
+            )
+            .map_err(|e| format!("Delete error: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::PendingMemory;
+
+    fn at(kind: MemoryKind, content: &str, timestamp: u64) -> PendingMemory {
+        PendingMemory { timestamp, ..PendingMemory::new(kind, content, 0.3) }
+    }
+
+    fn seeded_store() -> MemoryStore {
+        let mut store = MemoryStore::open_in_memory().unwrap();
+        store
+            .add_many(&[
+                at(MemoryKind::Interaction, "被摸头", 100),
+                at(MemoryKind::Interaction, "被喂食", 200),
+                at(MemoryKind::Interaction, "被抱起", 300),
+                at(MemoryKind::Thought, "想晒太阳", 250),
+                at(MemoryKind::Fact, "主人叫小明", 50),
+            ])
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn stats_count_each_kind_and_its_latest_time() {
+        let stats = seeded_store().kind_stats();
+        let summary: Vec<_> = stats.iter().map(|s| (s.kind.as_str(), s.count, s.latest_ts)).collect();
+        assert_eq!(summary, vec![("interaction", 3, 300), ("fact", 1, 50), ("thought", 1, 250)]);
+    }
+
+    #[test]
+    fn deletion_only_removes_older_rows_of_that_kind() {
+        let store = seeded_store();
+        assert_eq!(store.delete_older_than(MemoryKind::Interaction, 250), Ok(2));
+        assert_eq!(store.count(), 3);
+
+        let stats = store.kind_stats();
+        let interactions = stats.iter().find(|s| s.kind == "interaction").unwrap();
+        assert_eq!((interactions.count, interactions.latest_ts), (1, 300));
+        assert!(stats.iter().any(|s| s.kind == "thought"));
+
+        assert!(store.delete_older_than(MemoryKind::Fact, u64::MAX).is_err());
+        assert!(store.kind_stats().iter().any(|s| s.kind == "fact"));
+    }
+}
@@ -0,0 +1,140 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::{Memory, MemoryStore, row_to_memory, unix_now};
+
+/// 存档格式版本；格式变了就加一，并在 `migrate` 里处理旧版本
+pub const SAVE_VERSION: u32 = 1;
+
+/// 完整存档：状态 + 全部记忆，用于备份和换电脑
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveFile {
+    pub version: u32,
+    pub exported_at: u64,
+    /// SophieState 的 JSON（记忆模块不关心具体结构）
+    pub state: serde_json::Value,
+    pub memories: Vec<Memory>,
+}
+
+impl SaveFile {
+    pub fn new(state: serde_json::Value, memories: Vec<Memory>) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            exported_at: unix_now(),
+            state,
+            memories,
+        }
+    }
+
+    /// 解析存档文本，旧版本先迁移到当前格式
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let save: SaveFile = serde_json::from_str(text).map_err(|e| format!("Invalid save file: {}", e))?;
+        migrate(save)
+    }
+}
+
+/// 把旧版本的存档升级到当前版本（目前只有 v1）
+fn migrate(save: SaveFile) -> Result<SaveFile, String> {
+    match save.version {
+        SAVE_VERSION => Ok(save),
+        v if v > SAVE_VERSION => Err(format!("Save file version {} is newer than this app ({})", v, SAVE_VERSION)),
+        v => Err(format!("Unsupported save file version: {}", v)),
+    }
+}
+
+impl MemoryStore {
+    /// 全部记忆（按时间先后）
+    pub fn all_memories(&self) -> Vec<Memory> {
+        let mut stmt = match self.conn
+            .prepare("SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior FROM memories ORDER BY timestamp, id")
+        {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map([], row_to_memory)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 用存档替换当前数据（一个事务内完成）：
+    /// 现有状态备份到 `sophie_archive_<ts>`，现有记忆移入归档表，再写入存档内容。
+    /// 记忆重新分配 id，避免和归档表冲突。返回导入的记忆条数
+    pub fn restore(&mut self, state_json: &str, memories: &[Memory], archived_at: u64) -> Result<usize, String> {
+        let tx = self.conn.transaction()
+            .map_err(|e| format!("Transaction error: {}", e))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO sophie_state (key, value)
+             SELECT ?1, value FROM sophie_state WHERE key = 'sophie'",
+            params![format!("sophie_archive_{}", archived_at)],
+        ).map_err(|e| format!("Backup error: {}", e))?;
+        tx.execute(
+            "INSERT INTO memories_archive (id, kind, content, emotional_weight, timestamp, emotion, behavior, archived_at)
+             SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior, ?1 FROM memories",
+            params![archived_at],
+        ).map_err(|e| format!("Archive error: {}", e))?;
+        tx.execute("DELETE FROM memories", [])
+            .map_err(|e| format!("Archive error: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO memories (kind, content, emotional_weight, timestamp, emotion, behavior)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )
+                .map_err(|e| format!("Insert error: {}", e))?;
+            for m in memories {
+                stmt.execute(params![m.kind.as_str(), m.content, m.emotional_weight, m.timestamp, m.emotion, m.behavior])
+                    .map_err(|e| format!("Insert error: {}", e))?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO sophie_state (key, value) VALUES ('sophie', ?1)",
+            params![state_json],
+        ).map_err(|e| format!("Save state error: {}", e))?;
+        tx.commit().map_err(|e| format!("Commit error: {}", e))?;
+        Ok(memories.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryKind;
+
+    fn rows(store: &MemoryStore) -> Vec<(MemoryKind, String, f32, u64)> {
+        store
+            .all_memories()
+            .into_iter()
+            .map(|m| (m.kind, m.content, m.emotional_weight, m.timestamp))
+            .collect()
+    }
+
+    #[test]
+    fn export_then_import_round_trips_state_and_memories() {
+        let source = MemoryStore::open_in_memory().unwrap();
+        source.add(MemoryKind::Interaction, "被摸头", 0.6).unwrap();
+        source.add(MemoryKind::UserSpeech, "主人说：早安", 0.7).unwrap();
+        source.add(MemoryKind::Fact, "[fact] 主人的名字是小明", 0.3).unwrap();
+        let state = serde_json::json!({ "emotion": "Happy", "is_sleeping": false });
+        let text = serde_json::to_string(&SaveFile::new(state.clone(), source.all_memories())).unwrap();
+
+        let mut target = MemoryStore::open_in_memory().unwrap();
+        target.add(MemoryKind::Thought, "旧的想法", 0.4).unwrap();
+        target.save_state("sophie", "{\"old\":true}").unwrap();
+
+        let save = SaveFile::parse(&text).unwrap();
+        let imported = target.restore(&save.state.to_string(), &save.memories, 1234).unwrap();
+        assert_eq!(imported, 3);
+        assert_eq!(rows(&target), rows(&source));
+        let restored: serde_json::Value = serde_json::from_str(&target.load_state("sophie").unwrap()).unwrap();
+        assert_eq!(restored, state);
+
+        // 被替换掉的数据进了归档
+        assert_eq!(target.load_state("sophie_archive_1234").as_deref(), Some("{\"old\":true}"));
+        let archived: i64 = target
+            .conn
+            .query_row("SELECT COUNT(*) FROM memories_archive WHERE archived_at = 1234", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(archived, 1);
+    }
+}
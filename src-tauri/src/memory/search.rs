@@ -0,0 +1,142 @@
+use rusqlite::{Connection, params};
+
+use super::{Memory, MemoryStore, row_to_memory};
+
+/// trigram 分词至少需要 3 个字符，更短的查询走 LIKE
+const MIN_FTS_QUERY_CHARS: usize = 3;
+
+/// 建立 FTS5 全文索引（trigram 分词，中文也能按子串匹配），用触发器与 memories 保持同步
+///
+/// 内置 SQLite 没编译 FTS5 时返回 false，搜索退回 LIKE
+pub(super) fn init_fts(conn: &Connection) -> bool {
+    let existing: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'memories_fts'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let created = conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+            content, content = 'memories', content_rowid = 'id', tokenize = 'trigram'
+        );
+        CREATE TRIGGER IF NOT EXISTS memories_fts_insert AFTER INSERT ON memories BEGIN
+            INSERT INTO memories_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS memories_fts_delete AFTER DELETE ON memories BEGIN
+            INSERT INTO memories_fts(memories_fts, rowid, content) VALUES ('delete', old.id, old.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS memories_fts_update AFTER UPDATE OF content ON memories BEGIN
+            INSERT INTO memories_fts(memories_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            INSERT INTO memories_fts(rowid, content) VALUES (new.id, new.content);
+        END;",
+    );
+    if let Err(e) = created {
+        log::warn!("FTS5 unavailable ({}), memory search falls back to LIKE", e);
+        return false;
+    }
+
+    // 第一次建索引：把已有的记忆补进去
+    if existing == 0 {
+        if let Err(e) = conn.execute_batch("INSERT INTO memories_fts(memories_fts) VALUES ('rebuild');") {
+            log::warn!("Failed to build memory search index: {}", e);
+        }
+    }
+    true
+}
+
+impl MemoryStore {
+    /// 搜索记忆：能用全文索引时按相关度排序，否则按 LIKE 子串匹配、最新的在前
+    pub fn search(&self, query: &str, limit: usize) -> Vec<Memory> {
+        let query = query.trim();
+        if query.is_empty() {
+            return vec![];
+        }
+        if self.fts && query.chars().count() >= MIN_FTS_QUERY_CHARS {
+            self.fts_search(query, limit)
+        } else {
+            self.like_search(query, limit)
+        }
+    }
+
+    /// 全文检索，按 bm25 相关度排序（越相关越靠前）
+    pub fn fts_search(&self, query: &str, limit: usize) -> Vec<Memory> {
+        if !self.fts {
+            return self.like_search(query, limit);
+        }
+        // 整个查询当作一个短语，避免用户输入被解析成 FTS 语法
+        let phrase = format!("\"{}\"", query.replace('"', "\"\""));
+        self.query_memories(
+            "SELECT m.id, m.kind, m.content, m.emotional_weight, m.timestamp, m.emotion, m.behavior
+             FROM memories_fts JOIN memories m ON m.id = memories_fts.rowid
+             WHERE memories_fts MATCH ?1
+             ORDER BY bm25(memories_fts)
+             LIMIT ?2",
+            &phrase,
+            limit,
+        )
+    }
+
+    fn like_search(&self, query: &str, limit: usize) -> Vec<Memory> {
+        let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        self.query_memories(
+            "SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior FROM memories
+             WHERE content LIKE '%' || ?1 || '%' ESCAPE '\\'
+             ORDER BY timestamp DESC
+             LIMIT ?2",
+            &escaped,
+            limit,
+        )
+    }
+
+    fn query_memories(&self, sql: &str, arg: &str, limit: usize) -> Vec<Memory> {
+        let mut stmt = match self.conn.prepare(sql) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                log::warn!("Failed to search memories: {}", e);
+                return vec![];
+            }
+        };
+        stmt.query_map(params![arg, limit as i64], row_to_memory)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to search memories: {}", e);
+                vec![]
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MemoryKind, PendingMemory};
+
+    #[test]
+    fn fts_ranks_best_match_first() {
+        let mut store = MemoryStore::open_in_memory().unwrap();
+        assert!(store.fts, "bundled SQLite should ship FTS5");
+        store
+            .add_many(&[
+                PendingMemory::new(MemoryKind::UserSpeech, "今天去公园散步了", 0.5),
+                PendingMemory::new(MemoryKind::UserSpeech, "小鱼干小鱼干小鱼干，全都是小鱼干", 0.5),
+                PendingMemory::new(MemoryKind::Interaction, "喂了一条小鱼干", 0.5),
+            ])
+            .unwrap();
+
+        let hits = store.fts_search("小鱼干", 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].content, "小鱼干小鱼干小鱼干，全都是小鱼干");
+    }
+
+    #[test]
+    fn short_queries_fall_back_to_like() {
+        let mut store = MemoryStore::open_in_memory().unwrap();
+        store
+            .add_many(&[PendingMemory::new(MemoryKind::UserSpeech, "晚安 Sophie", 0.5)])
+            .unwrap();
+
+        assert_eq!(store.search("晚安", 10).len(), 1);
+        assert!(store.search("   ", 10).is_empty());
+    }
+}
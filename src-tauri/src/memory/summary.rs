@@ -0,0 +1,111 @@
+use rusqlite::params;
+
+use super::{Memory, MemoryKind, MemoryStore, row_to_memory};
+
+/// 普通记忆超过这么多条就开始压缩
+pub const SUMMARY_THRESHOLD: i64 = 500;
+/// 每次把最旧的多少条压成一条总结
+pub const SUMMARY_BATCH: usize = 50;
+/// 注入 prompt 的总结条数
+const MAX_PROMPT_SUMMARIES: usize = 2;
+/// 总结的情感权重：浓缩了很多事，比单条记忆重
+const SUMMARY_WEIGHT: f32 = 0.9;
+
+impl MemoryStore {
+    /// 可以被压缩的记忆条数（事实和已有的总结不算）
+    pub fn summarizable_count(&self) -> i64 {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories WHERE kind NOT IN (?1, ?2)",
+                params![MemoryKind::Fact.as_str(), MemoryKind::Summary.as_str()],
+                |row| row.get(0),
+            )
+            .unwrap_or(0)
+    }
+
+    /// 超过阈值时返回最旧的一批待压缩记忆，否则为空
+    pub fn due_for_summary(&self) -> Vec<Memory> {
+        if self.summarizable_count() <= SUMMARY_THRESHOLD {
+            return vec![];
+        }
+        let mut stmt = match self.conn.prepare(
+            "SELECT id, kind, content, emotional_weight, timestamp, emotion, behavior FROM memories
+             WHERE kind NOT IN (?1, ?2) ORDER BY timestamp, id LIMIT ?3",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(
+            params![MemoryKind::Fact.as_str(), MemoryKind::Summary.as_str(), SUMMARY_BATCH as i64],
+            row_to_memory,
+        )
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    /// 用一条总结替换这批记忆（同一个事务）；总结的时间戳取这批里最新的一条
+    pub fn replace_with_summary(&mut self, originals: &[Memory], summary: &str) -> Result<(), String> {
+        let Some(latest) = originals.iter().map(|m| m.timestamp).max() else {
+            return Ok(());
+        };
+        let tx = self.conn.transaction()
+            .map_err(|e| format!("Transaction error: {}", e))?;
+        tx.execute(
+            "INSERT INTO memories (kind, content, emotional_weight, timestamp) VALUES (?1, ?2, ?3, ?4)",
+            params![MemoryKind::Summary.as_str(), summary, SUMMARY_WEIGHT, latest],
+        ).map_err(|e| format!("Insert error: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare("DELETE FROM memories WHERE id = ?1")
+                .map_err(|e| format!("Delete error: {}", e))?;
+            for m in originals {
+                stmt.execute(params![m.id])
+                    .map_err(|e| format!("Delete error: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Commit error: {}", e))?;
+        Ok(())
+    }
+
+    /// 最近几条总结的文本（用于 LLM prompt），最新的在前
+    pub fn summaries_as_text(&self) -> Vec<String> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT content FROM memories WHERE kind = ?1 ORDER BY timestamp DESC LIMIT ?2",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![MemoryKind::Summary.as_str(), MAX_PROMPT_SUMMARIES as i64], |row| row.get(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).map(|c: String| format!("[summary] {}", c)).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::PendingMemory;
+
+    #[test]
+    fn summarizing_shrinks_rows_and_keeps_a_summary() {
+        let mut store = MemoryStore::open_in_memory().unwrap();
+        let entries: Vec<PendingMemory> = (0..=SUMMARY_THRESHOLD)
+            .map(|i| {
+                let mut m = PendingMemory::new(MemoryKind::Interaction, &format!("第 {} 次互动", i), 0.3);
+                m.timestamp = 1_000 + i as u64;
+                m
+            })
+            .collect();
+        store.add_many(&entries).unwrap();
+
+        let batch = store.due_for_summary();
+        assert_eq!(batch.len(), SUMMARY_BATCH);
+        assert_eq!(batch[0].content, "第 0 次互动");
+
+        let before = store.count();
+        store.replace_with_summary(&batch, "主人这阵子常来摸头").unwrap();
+        assert_eq!(store.count(), before - SUMMARY_BATCH as i64 + 1);
+        assert_eq!(store.summaries_as_text(), vec!["[summary] 主人这阵子常来摸头".to_string()]);
+        assert!(store.due_for_summary().is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use rusqlite::params;
+use serde::Serialize;
+
+use super::MemoryStore;
+
+/// 羁绊分（信任和亲密度的平均）每天平均变化超过这么多才算在涨/在掉
+const TREND_SLOPE_PER_DAY: f32 = 0.5;
+
+/// 某一天的关系采样（同一天多次采样只留最后一次）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationshipPoint {
+    /// 本地日期编号（见 `state::local_day`）
+    pub day: i64,
+    pub timestamp: u64,
+    pub trust: f32,
+    pub intimacy: f32,
+    pub understanding: f32,
+}
+
+impl RelationshipPoint {
+    /// 羁绊分：信任和亲密度的平均
+    pub fn bond(&self) -> f32 {
+        (self.trust + self.intimacy) / 2.0
+    }
+}
+
+/// 关系的走向
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+impl Trend {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trend::Rising => "上升",
+            Trend::Steady => "平稳",
+            Trend::Falling => "下降",
+        }
+    }
+}
+
+/// 按羁绊分对日期做最小二乘拟合，看斜率判断走向；不到两天的数据算平稳
+pub fn classify(points: &[RelationshipPoint]) -> Trend {
+    if points.len() < 2 {
+        return Trend::Steady;
+    }
+    let n = points.len() as f32;
+    let first_day = points[0].day;
+    let xs: Vec<f32> = points.iter().map(|p| (p.day - first_day) as f32).collect();
+    let mean_x = xs.iter().sum::<f32>() / n;
+    let mean_y = points.iter().map(|p| p.bond()).sum::<f32>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (x, p) in xs.iter().zip(points) {
+        cov += (x - mean_x) * (p.bond() - mean_y);
+        var += (x - mean_x) * (x - mean_x);
+    }
+    if var == 0.0 {
+        return Trend::Steady;
+    }
+    let slope = cov / var;
+    if slope > TREND_SLOPE_PER_DAY {
+        Trend::Rising
+    } else if slope < -TREND_SLOPE_PER_DAY {
+        Trend::Falling
+    } else {
+        Trend::Steady
+    }
+}
+
+impl MemoryStore {
+    /// 记下今天的关系数值（同一天再记就覆盖）
+    pub fn record_relationship(&self, point: &RelationshipPoint) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO relationship_log (day, timestamp, trust, intimacy, understanding)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![point.day, point.timestamp, point.trust, point.intimacy, point.understanding],
+        ).map_err(|e| format!("Insert relationship error: {}", e))?;
+        Ok(())
+    }
+
+    /// `since_day` 起（含）每天的关系采样，从旧到新
+    pub fn relationship_since(&self, since_day: i64) -> Vec<RelationshipPoint> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT day, timestamp, trust, intimacy, understanding FROM relationship_log
+             WHERE day >= ?1 ORDER BY day ASC",
+        ) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                log::warn!("Failed to query relationship log: {}", e);
+                return vec![];
+            }
+        };
+        stmt.query_map(params![since_day], |row| {
+            Ok(RelationshipPoint {
+                day: row.get(0)?,
+                timestamp: row.get(1)?,
+                trust: row.get(2)?,
+                intimacy: row.get(3)?,
+                understanding: row.get(4)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(day: i64, bond: f32) -> RelationshipPoint {
+        RelationshipPoint { day, timestamp: day as u64 * 86_400, trust: bond, intimacy: bond, understanding: 50.0 }
+    }
+
+    fn series(bonds: &[f32]) -> Vec<RelationshipPoint> {
+        bonds.iter().enumerate().map(|(i, b)| point(19_000 + i as i64, *b)).collect()
+    }
+
+    #[test]
+    fn slope_decides_the_direction() {
+        assert_eq!(classify(&series(&[40.0, 42.0, 43.0, 45.0, 48.0])), Trend::Rising);
+        assert_eq!(classify(&series(&[70.0, 66.0, 65.0, 60.0])), Trend::Falling);
+        // 上下抖动但没有方向
+        assert_eq!(classify(&series(&[50.0, 51.0, 49.5, 50.5, 50.0])), Trend::Steady);
+        assert_eq!(classify(&series(&[50.0])), Trend::Steady);
+        assert_eq!(Trend::Falling.label(), "下降");
+    }
+
+    #[test]
+    fn daily_samples_overwrite_and_come_back_in_order() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        for p in [point(19_002, 60.0), point(19_000, 40.0), point(19_001, 50.0)] {
+            mem.record_relationship(&p).unwrap();
+        }
+        // 同一天再记一次：只留最后的值
+        mem.record_relationship(&point(19_002, 65.0)).unwrap();
+
+        let points = mem.relationship_since(19_001);
+        let days: Vec<i64> = points.iter().map(|p| p.day).collect();
+        assert_eq!(days, vec![19_001, 19_002]);
+        assert_eq!(points[1].bond(), 65.0);
+        assert_eq!(classify(&mem.relationship_since(0)), Trend::Rising);
+    }
+}
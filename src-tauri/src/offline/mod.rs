@@ -0,0 +1,590 @@
+use crate::behavior::{Behavior, parse_action};
+use crate::llm::SpeechResult;
+use crate::state::{SophieState, Yesterday};
+use crate::state::emotion::Emotion;
+use crate::state::relationship::RelationshipState;
+use crate::state::environment::Weather;
+use crate::rng;
+use crate::i18n::{Lang, Phrase, phrases};
+
+/// 从短语池里随机挑一句
+fn pick(pool: &[&str]) -> String {
+    pool[rng::rand_index(pool.len())].to_string()
+}
+
+/// 基于规则的想法生成（不依赖 LLM，保底方案；离线模式下是唯一来源）
+///
+/// `hour` 是本地小时（0-23），用于深夜/早晨/黄昏的应景短语；`lang` 决定短语的语言
+pub fn rule_based_thought(sophie: &SophieState, hour: u32, lang: Lang) -> Option<String> {
+    let phys = &sophie.physiological;
+    let rel = &sophie.relationship;
+    let say = |phrase: Phrase| Some(pick(phrases(lang, phrase)));
+
+    // 生理需求优先；要饭时越饿越急，有爱吃的东西就点名要
+    if sophie.is_begging() {
+        if phys.hunger > 90.0 {
+            return say(Phrase::BeggingInsistent);
+        }
+        if let Some(food) = sophie.preferences.favorite_food() {
+            if rng::rand_f32() < 0.5 {
+                let name = match lang {
+                    Lang::Zh => food.label(),
+                    Lang::En => food.as_str(),
+                };
+                return Some(pick(phrases(lang, Phrase::BeggingFor)).replace("{food}", name));
+            }
+        }
+        return say(Phrase::Begging);
+    }
+    if phys.hunger > 80.0 {
+        return say(Phrase::Starving);
+    }
+    if phys.sleepiness > 75.0 && !sophie.is_sleeping {
+        return say(Phrase::Sleepy);
+    }
+    if sophie.is_sleeping {
+        if rng::rand_f32() < 0.2 {
+            return say(Phrase::Snoring);
+        }
+        return None;
+    }
+    if sophie.is_groggy() {
+        return say(Phrase::Groggy);
+    }
+
+    let r = (rng::rand_f32() * 100.0) as u32;
+    if phys.energy < 20.0 && r < 30 {
+        return say(Phrase::Tired);
+    }
+    if sophie.expects_meal() && r < 30 {
+        return say(Phrase::MealTime);
+    }
+    if phys.hunger > 60.0 && r < 15 {
+        return say(Phrase::Peckish);
+    }
+
+    if let Some(phrase) = time_of_day_phrase(sophie, hour, r) {
+        return say(phrase);
+    }
+    if let Some(phrase) = environment_phrase(sophie, r) {
+        return say(phrase);
+    }
+
+    match sophie.emotion {
+        Emotion::Happy => {
+            if rel.will_slow_blink() && r < 20 { say(Phrase::HappyTrusting) }
+            else if r < 20 { say(Phrase::Happy) }
+            else if r < 35 { say(Phrase::Content) }
+            else { None }
+        }
+        Emotion::Bored => {
+            if rel.will_approach() && r < 25 { say(Phrase::BoredSeeking) }
+            else if r < 25 { say(Phrase::Bored) }
+            else if r < 40 { say(Phrase::Silence) }
+            else { None }
+        }
+        Emotion::Needy => {
+            if r < 40 { say(Phrase::Needy) }
+            else { None }
+        }
+        Emotion::Irritated => {
+            if r < 20 { say(Phrase::Irritated) }
+            else { None }
+        }
+        Emotion::Down => {
+            if r < 15 { say(Phrase::Down) }
+            else { None }
+        }
+        Emotion::Curious => {
+            if r < 30 { say(Phrase::Curious) }
+            else { None }
+        }
+        Emotion::Playful => {
+            if r < 25 { say(Phrase::Playful) }
+            else { None }
+        }
+        Emotion::Calm => {
+            if r < 10 { say(Phrase::Calm) }
+            else if r < 15 { say(Phrase::Warm) }
+            else if rel.will_show_belly() && r < 20 { say(Phrase::Safe) }
+            else { None }
+        }
+    }
+}
+
+/// 应景的时间短语：只占一小部分概率，且要情绪合适
+fn time_of_day_phrase(sophie: &SophieState, hour: u32, r: u32) -> Option<Phrase> {
+    let emotion = sophie.emotion;
+    match hour {
+        // 深夜：主人还没睡
+        0..=4 | 23 if r < 10 && !matches!(emotion, Emotion::Irritated) => Some(Phrase::LateNight),
+        // 早晨：心情不错才打招呼
+        6..=9 if r < 10 && matches!(emotion, Emotion::Calm | Emotion::Happy) => Some(Phrase::Morning),
+        // 黄昏：狩猎本能，要有精神
+        17..=19
+            if r < 15
+                && sophie.physiological.energy > 50.0
+                && matches!(emotion, Emotion::Calm | Emotion::Curious | Emotion::Playful) =>
+        {
+            Some(Phrase::Dusk)
+        }
+        _ => None,
+    }
+}
+
+/// 天气/季节相关的短语
+fn environment_phrase(sophie: &SophieState, r: u32) -> Option<Phrase> {
+    let env = &sophie.environment;
+    if r >= 10 || matches!(sophie.emotion, Emotion::Irritated | Emotion::Down) {
+        return None;
+    }
+    match env.weather {
+        Weather::Sunny => Some(Phrase::Sunny),
+        Weather::Rainy => Some(Phrase::Rainy),
+        _ if env.is_cold() => Some(Phrase::Cold),
+        _ => None,
+    }
+}
+
+/// 新一天的第一句：昨天没人理就有点委屈
+pub fn new_day_thought(yesterday: Yesterday, lang: Lang) -> String {
+    let phrase = match yesterday {
+        Yesterday::Neglected => Phrase::NewDayLonely,
+        _ => Phrase::Morning,
+    };
+    pick(phrases(lang, phrase))
+}
+
+/// 体力耗尽累倒时的一句
+pub fn collapse_thought(lang: Lang) -> String {
+    pick(phrases(lang, Phrase::Collapsed))
+}
+
+/// 生日那天的一句
+pub fn birthday_thought(lang: Lang) -> String {
+    pick(phrases(lang, Phrase::Birthday))
+}
+
+/// 免打扰结束时补的一句
+pub fn dnd_catch_up_thought(lang: Lang) -> String {
+    pick(phrases(lang, Phrase::DndCatchUp))
+}
+
+/// 窗口隐藏不到这么久（秒）就又显示出来，不打招呼
+const GREET_MIN_HIDDEN_SECS: u64 = 60;
+/// 隐藏超过这么久（秒）算"好久不见"
+const GREET_LONG_HIDDEN_SECS: u64 = 4 * 3600;
+/// 亲密度到这个值才热情迎接
+const GREET_WARM_INTIMACY: f32 = 60.0;
+
+/// 窗口重新显示时打的招呼：看隐藏了多久、关系和心情；隐藏太短或在睡觉就不说话
+pub fn greeting_thought(sophie: &SophieState, hidden_secs: u64, lang: Lang) -> Option<String> {
+    greeting_phrase(sophie, hidden_secs).map(|phrase| pick(phrases(lang, phrase)))
+}
+
+fn greeting_phrase(sophie: &SophieState, hidden_secs: u64) -> Option<Phrase> {
+    if hidden_secs < GREET_MIN_HIDDEN_SECS || sophie.is_sleeping {
+        return None;
+    }
+    if sophie.emotion == Emotion::Irritated {
+        return Some(Phrase::GreetCool);
+    }
+    let bonded = sophie.relationship.intimacy >= GREET_WARM_INTIMACY;
+    let long_gone = hidden_secs >= GREET_LONG_HIDDEN_SECS;
+    Some(match (bonded, long_gone) {
+        (true, false) => Phrase::GreetWarm,
+        (true, true) => Phrase::GreetMissed,
+        (false, false) => Phrase::GreetPlain,
+        (false, true) => Phrase::GreetCool,
+    })
+}
+
+/// 被冷落多久（分钟）时主动"问一句"，以及对应的短语
+const CHECK_IN_THRESHOLDS: &[(u32, Phrase)] = &[
+    (60, Phrase::CheckInShort),
+    (180, Phrase::CheckInLong),
+];
+
+/// 冷落超过阈值时的一次性主动想法：每段冷落里每个阈值只触发一次，下次互动后重置
+#[derive(Debug, Clone)]
+pub struct CheckInTracker {
+    /// 当前这段冷落从哪次互动开始算（last_interaction_ts）
+    episode_ts: u64,
+    /// 已经触发过的阈值个数
+    fired: usize,
+}
+
+impl CheckInTracker {
+    pub fn new() -> Self {
+        Self { episode_ts: 0, fired: 0 }
+    }
+
+    /// 检查是否刚越过下一个阈值；睡着时不打扰，也不消耗阈值
+    pub fn poll(&mut self, sophie: &SophieState, lang: Lang) -> Option<String> {
+        if sophie.last_interaction_ts != self.episode_ts {
+            self.episode_ts = sophie.last_interaction_ts;
+            self.fired = 0;
+        }
+        if sophie.is_sleeping {
+            return None;
+        }
+        let minutes = sophie.minutes_since_interaction();
+        let mut phrase = None;
+        while let Some(&(threshold, next)) = CHECK_IN_THRESHOLDS.get(self.fired) {
+            if minutes < threshold {
+                break;
+            }
+            // 一下子越过多个阈值（比如刚启动）只说最后一句
+            self.fired += 1;
+            phrase = Some(next);
+        }
+        phrase.map(|p| pick(phrases(lang, p)))
+    }
+}
+
+/// 基于规则的言语响应（没有 LLM 时使用）
+pub fn rule_based_speech(sophie: &SophieState, message: &str) -> SpeechResult {
+    let has = |words: &[&str]| words.iter().any(|w| message.contains(w));
+    let phys = &sophie.physiological;
+    let rel = &sophie.relationship;
+    let r = (rng::rand_f32() * 100.0) as u32;
+
+    let (action, thought) = if sophie.is_sleeping {
+        ("sleep", if r < 30 { Some("zzz") } else { None })
+    } else if sophie.is_groggy() {
+        // 刚醒，反应慢半拍
+        ("glance", Some("...嗯"))
+    } else if has(&["吃", "饭", "鱼", "零食", "饿"]) {
+        if phys.hunger > 50.0 {
+            ("approach", Some("饭？"))
+        } else {
+            ("glance", Some("不饿。"))
+        }
+    } else if has(&["睡", "晚安", "困"]) {
+        if phys.sleepiness > 50.0 {
+            ("sleep", Some("困..."))
+        } else {
+            ("glance", None)
+        }
+    } else if has(&["玩", "过来", "来"]) {
+        if phys.energy > 50.0 && rel.will_approach() {
+            ("approach", Some("来！"))
+        } else {
+            ("ignore", None)
+        }
+    } else if has(PRAISE_WORDS) {
+        if rel.will_approach() {
+            ("approach", Some("嗯~"))
+        } else {
+            ("glance", Some("..."))
+        }
+    } else if has(HARSH_WORDS) {
+        ("walk_away", Some("哼。"))
+    } else {
+        match sophie.emotion {
+            Emotion::Irritated => ("ignore", None),
+            Emotion::Down => ("ignore", Some("...")),
+            Emotion::Needy => ("approach", Some("...终于理我了")),
+            Emotion::Happy if rel.will_approach() => ("approach", None),
+            Emotion::Bored | Emotion::Curious => ("glance", Some("嗯？")),
+            _ => {
+                if r < 50 { ("glance", None) } else { ("ignore", None) }
+            }
+        }
+    };
+
+    SpeechResult {
+        action: action.to_string(),
+        thought: thought.map(|t| t.to_string()),
+        emotion_change: None,
+        relationship_delta: Some(message_sentiment(message)),
+    }
+}
+
+/// 夸奖的词
+const PRAISE_WORDS: &[&str] = &["乖", "爱", "喜欢", "可爱", "好猫", "谢谢", "棒", "想你"];
+/// 凶她的词
+const HARSH_WORDS: &[&str] = &["坏", "滚", "笨", "讨厌", "闭嘴", "烦死", "蠢"];
+
+/// 关键词情感倾向：夸奖为正、凶她为负，范围 -2 到 2
+pub fn message_sentiment(message: &str) -> f32 {
+    let count = |words: &[&str]| words.iter().filter(|w| message.contains(*w)).count() as f32;
+    (count(PRAISE_WORDS) - count(HARSH_WORDS) * 1.5).clamp(-2.0, 2.0)
+}
+
+/// 没学到名字时认的默认名字
+const DEFAULT_NAMES: &[&str] = &["Sophie", "索菲"];
+/// 叫她名字时，完全不信任主人也会有这么大概率理一下
+const NAME_BASE_CHANCE: f32 = 0.2;
+
+/// 主人的话里有没有叫她的名字：有学到的名字就认它，否则认默认名字（英文不分大小写）
+pub fn mentions_name(message: &str, learned: Option<&str>) -> bool {
+    let message = message.to_lowercase();
+    match learned.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => message.contains(&name.to_lowercase()),
+        None => DEFAULT_NAMES.iter().any(|name| message.contains(&name.to_lowercase())),
+    }
+}
+
+/// 被叫到名字时理人的概率：越信任主人越会回应
+pub fn name_response_chance(rel: &RelationshipState) -> f32 {
+    (NAME_BASE_CHANCE + 0.7 * rel.trust / 100.0).clamp(0.0, 1.0)
+}
+
+/// 被叫了名字：本来不打算理的回应有机会改成看一眼（信任的话直接走过去）
+///
+/// `r` 是 [0, 1) 的随机数；返回是否因此改了回应
+pub fn answer_to_name(result: &mut SpeechResult, rel: &RelationshipState, r: f32) -> bool {
+    if parse_action(&result.action) != Some(Behavior::Idle) || r >= name_response_chance(rel) {
+        return false;
+    }
+    result.action = if rel.will_approach() { "approach" } else { "glance" }.to_string();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::food::FoodType;
+
+    #[test]
+    fn needs_come_before_mood_in_thoughts() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 90.0;
+        for _ in 0..20 {
+            let thought = rule_based_thought(&sophie, 12, Lang::Zh).unwrap();
+            assert!(phrases(Lang::Zh, Phrase::Starving).contains(&thought.as_str()), "{}", thought);
+        }
+
+        sophie.physiological.hunger = 0.0;
+        sophie.physiological.sleepiness = 90.0;
+        sophie.is_sleeping = true;
+        for _ in 0..20 {
+            if let Some(thought) = rule_based_thought(&sophie, 12, Lang::Zh) {
+                assert!(phrases(Lang::Zh, Phrase::Snoring).contains(&thought.as_str()), "{}", thought);
+            }
+        }
+    }
+
+    #[test]
+    fn begging_names_her_favorite_food_sometimes() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 85.0;
+        sophie.relationship.intimacy = 100.0;
+        assert!(sophie.is_begging());
+        for _ in 0..4 {
+            sophie.preferences.record_food(FoodType::Fish, 1.0);
+        }
+
+        let thoughts: Vec<String> = (0..60).map(|_| rule_based_thought(&sophie, 12, Lang::Zh).unwrap()).collect();
+        assert!(thoughts.iter().any(|t| t.contains("鱼")));
+        assert!(thoughts.iter().any(|t| phrases(Lang::Zh, Phrase::Begging).contains(&t.as_str())));
+        let english = (0..60).filter_map(|_| rule_based_thought(&sophie, 12, Lang::En)).find(|t| t.contains("fish"));
+        assert!(english.is_some());
+    }
+
+    fn reply(sophie: &SophieState, message: &str) -> (String, Option<String>) {
+        let result = rule_based_speech(sophie, message);
+        (result.action, result.thought)
+    }
+
+    #[test]
+    fn offline_speech_follows_keywords_and_needs() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 70.0;
+        assert_eq!(reply(&sophie, "要不要吃小鱼干"), ("approach".into(), Some("饭？".into())));
+        sophie.physiological.hunger = 10.0;
+        assert_eq!(reply(&sophie, "要不要吃小鱼干"), ("glance".into(), Some("不饿。".into())));
+
+        sophie.physiological.sleepiness = 90.0;
+        assert_eq!(reply(&sophie, "晚安").0, "sleep");
+        assert_eq!(reply(&sophie, "你这个笨猫"), ("walk_away".into(), Some("哼。".into())));
+    }
+
+    #[test]
+    fn offline_speech_respects_trust_and_sleep() {
+        let mut sophie = SophieState::new();
+        assert_eq!(reply(&sophie, "过来玩").0, "ignore");
+        sophie.relationship.trust = 60.0;
+        assert_eq!(reply(&sophie, "过来玩").0, "approach");
+        assert_eq!(reply(&sophie, "你好可爱").0, "approach");
+
+        sophie.is_sleeping = true;
+        assert_eq!(reply(&sophie, "过来玩").0, "sleep");
+    }
+
+    #[test]
+    fn time_of_day_phrases_fit_the_hour_and_mood() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = Emotion::Calm;
+        sophie.physiological.energy = 80.0;
+
+        assert_eq!(time_of_day_phrase(&sophie, 2, 5), Some(Phrase::LateNight));
+        assert_eq!(time_of_day_phrase(&sophie, 7, 5), Some(Phrase::Morning));
+        assert_eq!(time_of_day_phrase(&sophie, 18, 12), Some(Phrase::Dusk));
+        assert_eq!(time_of_day_phrase(&sophie, 13, 0), None);
+        // 只占一小部分概率
+        assert_eq!(time_of_day_phrase(&sophie, 2, 50), None);
+    }
+
+    #[test]
+    fn time_of_day_phrases_need_the_right_mood() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = Emotion::Irritated;
+        assert_eq!(time_of_day_phrase(&sophie, 2, 0), None);
+        sophie.emotion = Emotion::Down;
+        assert_eq!(time_of_day_phrase(&sophie, 7, 0), None);
+        sophie.emotion = Emotion::Curious;
+        sophie.physiological.energy = 30.0;
+        assert_eq!(time_of_day_phrase(&sophie, 18, 0), None);
+    }
+
+    #[test]
+    fn message_sentiment_weighs_harsh_words_more() {
+        assert_eq!(message_sentiment("今天天气不错"), 0.0);
+        assert_eq!(message_sentiment("好猫，谢谢你"), 2.0);
+        assert_eq!(message_sentiment("乖"), 1.0);
+        assert_eq!(message_sentiment("笨猫"), -1.5);
+        assert_eq!(message_sentiment("烦死了，滚，笨"), -2.0);
+        assert_eq!(rule_based_speech(&SophieState::new(), "乖").relationship_delta, Some(1.0));
+    }
+
+    #[test]
+    fn weather_phrases_need_a_decent_mood() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = Emotion::Calm;
+        assert_eq!(environment_phrase(&sophie, 0), None);
+
+        sophie.environment.weather = Weather::Rainy;
+        assert_eq!(environment_phrase(&sophie, 0), Some(Phrase::Rainy));
+        assert_eq!(environment_phrase(&sophie, 50), None);
+
+        sophie.emotion = Emotion::Down;
+        assert_eq!(environment_phrase(&sophie, 0), None);
+    }
+
+    fn neglected_for(minutes: u64) -> SophieState {
+        let mut sophie = SophieState::new();
+        sophie.last_interaction_ts -= minutes * 60;
+        sophie
+    }
+
+    #[test]
+    fn each_check_in_threshold_fires_once_per_episode() {
+        let mut tracker = CheckInTracker::new();
+        let mut sophie = neglected_for(30);
+        assert_eq!(tracker.poll(&sophie, Lang::Zh), None);
+
+        sophie.last_interaction_ts -= 40 * 60;
+        let first = tracker.poll(&sophie, Lang::Zh).unwrap();
+        assert!(phrases(Lang::Zh, Phrase::CheckInShort).contains(&first.as_str()), "{}", first);
+        assert_eq!(tracker.poll(&sophie, Lang::Zh), None);
+
+        sophie.last_interaction_ts -= 120 * 60;
+        let second = tracker.poll(&sophie, Lang::Zh).unwrap();
+        assert!(phrases(Lang::Zh, Phrase::CheckInLong).contains(&second.as_str()), "{}", second);
+        assert_eq!(tracker.poll(&sophie, Lang::Zh), None);
+    }
+
+    #[test]
+    fn check_ins_reset_after_an_interaction() {
+        let mut tracker = CheckInTracker::new();
+        let mut sophie = neglected_for(70);
+        assert!(tracker.poll(&sophie, Lang::Zh).is_some());
+
+        sophie.record_interaction();
+        assert_eq!(tracker.poll(&sophie, Lang::Zh), None);
+        sophie.last_interaction_ts -= 70 * 60;
+        assert!(tracker.poll(&sophie, Lang::Zh).is_some());
+    }
+
+    #[test]
+    fn check_ins_skip_to_the_latest_threshold_and_wait_while_asleep() {
+        let mut tracker = CheckInTracker::new();
+        let mut sophie = neglected_for(200);
+        sophie.is_sleeping = true;
+        assert_eq!(tracker.poll(&sophie, Lang::Zh), None);
+
+        // 醒来时已经冷落很久：只说最后一句
+        sophie.is_sleeping = false;
+        let text = tracker.poll(&sophie, Lang::Zh).unwrap();
+        assert!(phrases(Lang::Zh, Phrase::CheckInLong).contains(&text.as_str()), "{}", text);
+        assert_eq!(tracker.poll(&sophie, Lang::Zh), None);
+    }
+
+    #[test]
+    fn thoughts_follow_the_configured_language() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 90.0;
+        let thought = rule_based_thought(&sophie, 12, Lang::En).unwrap();
+        assert!(phrases(Lang::En, Phrase::Starving).contains(&thought.as_str()), "{}", thought);
+
+        let sophie = neglected_for(70);
+        let text = CheckInTracker::new().poll(&sophie, Lang::En).unwrap();
+        assert!(phrases(Lang::En, Phrase::CheckInShort).contains(&text.as_str()), "{}", text);
+    }
+
+    #[test]
+    fn she_knows_her_learned_name_or_the_default_one() {
+        assert!(mentions_name("sophie, come here", None));
+        assert!(mentions_name("索菲在干嘛", None));
+        assert!(!mentions_name("今天好累", None));
+        assert!(mentions_name("咪咪过来", Some("咪咪")));
+        // 学到新名字后只认新名字
+        assert!(!mentions_name("索菲在干嘛", Some("咪咪")));
+        assert!(mentions_name("索菲在干嘛", Some("  ")));
+    }
+
+    #[test]
+    fn calling_her_name_gets_more_answers_and_more_with_trust() {
+        let answered = |trust: f32, called: bool| {
+            let mut rel = RelationshipState::new();
+            rel.trust = trust;
+            (0..100)
+                .filter(|i| {
+                    let mut result = SpeechResult {
+                        action: "ignore".to_string(),
+                        thought: None,
+                        emotion_change: None,
+                        relationship_delta: None,
+                    };
+                    called && answer_to_name(&mut result, &rel, *i as f32 / 100.0)
+                })
+                .count()
+        };
+        assert_eq!(answered(80.0, false), 0);
+        assert!(answered(0.0, true) > 0);
+        assert!(answered(80.0, true) > answered(0.0, true));
+
+        // 本来就理人的回应不动
+        let mut result = SpeechResult {
+            action: "walk_away".to_string(),
+            thought: None,
+            emotion_change: None,
+            relationship_delta: None,
+        };
+        assert!(!answer_to_name(&mut result, &RelationshipState::new(), 0.0));
+        assert_eq!(result.action, "walk_away");
+    }
+
+    #[test]
+    fn greeting_depends_on_time_away_and_relationship() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = Emotion::Calm;
+        sophie.relationship.intimacy = 80.0;
+        assert_eq!(greeting_phrase(&sophie, 10), None);
+        assert_eq!(greeting_phrase(&sophie, 600), Some(Phrase::GreetWarm));
+        assert_eq!(greeting_phrase(&sophie, 5 * 3600), Some(Phrase::GreetMissed));
+
+        sophie.relationship.intimacy = 20.0;
+        assert_eq!(greeting_phrase(&sophie, 600), Some(Phrase::GreetPlain));
+        assert_eq!(greeting_phrase(&sophie, 5 * 3600), Some(Phrase::GreetCool));
+
+        // 心情不好时再亲近也冷淡；睡着了不理人
+        sophie.relationship.intimacy = 80.0;
+        sophie.emotion = Emotion::Irritated;
+        assert_eq!(greeting_phrase(&sophie, 600), Some(Phrase::GreetCool));
+        sophie.is_sleeping = true;
+        assert_eq!(greeting_thought(&sophie, 600, Lang::En), None);
+    }
+}
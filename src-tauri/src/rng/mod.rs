@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+use crate::clock::{Clock, SystemClock};
+
+/// 可设定种子的伪随机数生成器（xorshift64*）
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift 的状态不能为 0
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// [0, 1) 之间的随机数
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// 全局随机源：首次使用时以当前时间做种子
+static GLOBAL: Mutex<Option<Rng>> = Mutex::new(None);
+
+thread_local! {
+    /// 当前线程上临时接管全局随机源的随机数生成器（见 `with_seed`）
+    static LOCAL: RefCell<Option<Rng>> = const { RefCell::new(None) };
+}
+
+/// 在当前线程上用固定种子跑 `f`：期间的随机数只来自这个种子，不影响全局随机源和其他线程
+#[cfg(any(test, feature = "sim"))]
+pub fn with_seed<R>(seed: u64, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore(LOCAL.with(|local| local.replace(Some(Rng::new(seed)))));
+    f()
+}
+
+/// 离开 `with_seed` 时（包括 panic）恢复原来的随机源
+#[cfg(any(test, feature = "sim"))]
+struct Restore(Option<Rng>);
+
+#[cfg(any(test, feature = "sim"))]
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        LOCAL.with(|local| *local.borrow_mut() = previous);
+    }
+}
+
+/// 从全局随机源取 [0, 1) 的随机数
+pub fn rand_f32() -> f32 {
+    if let Some(r) = LOCAL.with(|local| local.borrow_mut().as_mut().map(Rng::next_f32)) {
+        return r;
+    }
+    let mut global = GLOBAL.lock().unwrap_or_else(|e| e.into_inner());
+    global.get_or_insert_with(|| Rng::new(time_seed())).next_f32()
+}
+
+/// [0, len) 的随机下标（`len` 不能为 0）
+pub fn rand_index(len: usize) -> usize {
+    (rand_f32() * len as f32) as usize % len
+}
+
+/// 从全局随机源取一个新种子
+pub fn rand_u64() -> u64 {
+    if let Some(r) = LOCAL.with(|local| local.borrow_mut().as_mut().map(Rng::next_u64)) {
+        return r;
+    }
+    let mut global = GLOBAL.lock().unwrap_or_else(|e| e.into_inner());
+    global.get_or_insert_with(|| Rng::new(time_seed())).next_u64()
+}
+
+fn time_seed() -> u64 {
+    SystemClock
+        .now_unix()
+        .wrapping_mul(1_000_000_000)
+        .wrapping_add(SystemClock.now_nanos() as u64)
+}
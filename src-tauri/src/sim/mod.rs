@@ -0,0 +1,200 @@
+use crate::behavior::decide_behavior;
+use crate::config::{self, TuningConfig};
+use crate::state;
+use crate::clock::{self, MockClock};
+use crate::rng;
+use std::rc::Rc;
+
+pub use crate::behavior::Behavior;
+pub use crate::state::emotion::Emotion;
+pub use crate::state::food::FoodType;
+pub use crate::state::SophieState;
+
+/// 脚本里的一次互动（与对应命令对状态的改动一致，不含记忆和事件）
+#[derive(Debug, Clone, PartialEq)]
+pub enum Interaction {
+    Click,
+    Feed(FoodType),
+    ShowToy(String),
+    Comfort,
+    SlowBlink,
+    PutToSleep,
+    WakeUp,
+}
+
+/// 模拟参数
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// 随机源种子：同样的种子、状态和脚本得到同样的结果
+    pub seed: u64,
+    /// 虚拟时间的起点（Unix 秒）
+    pub start_ts: u64,
+    /// 每次 tick 之间隔多久（秒），默认与后台循环一致
+    pub tick_secs: u64,
+    pub tuning: TuningConfig,
+}
+
+impl SimConfig {
+    pub fn new(seed: u64, start_ts: u64) -> Self {
+        Self {
+            seed,
+            start_ts,
+            tick_secs: config::STATE_TICK_SECS,
+            tuning: TuningConfig::new(),
+        }
+    }
+}
+
+/// 每次 tick 之后的一帧
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimFrame {
+    pub tick: u32,
+    pub emotion: Emotion,
+    pub emotion_intensity: f32,
+    pub behavior: Behavior,
+    pub is_sleeping: bool,
+    pub energy: f32,
+    pub hunger: f32,
+    pub sleepiness: f32,
+    pub trust: f32,
+    pub intimacy: f32,
+}
+
+/// 在模拟的时间和种子下新建一只猫（性格、出生时间都可复现）
+pub fn new_cat(sim: &SimConfig) -> SophieState {
+    let clock = Rc::new(MockClock::new(sim.start_ts));
+    clock::with_clock(clock, || rng::with_seed(sim.seed, SophieState::new))
+}
+
+/// 不依赖 Tauri 的整段模拟（平衡回归、"预览一天"用）：把 `sophie` 往前推 `ticks` 次 tick，
+/// `schedule` 里的 `(tick, 互动)` 在那次 tick 之前发生
+///
+/// 时间和随机数都只在当前线程上被接管，不影响正在运行的 Sophie
+pub fn simulate(sophie: &mut SophieState, ticks: u32, schedule: &[(u32, Interaction)], sim: &SimConfig) -> Vec<SimFrame> {
+    let clock = Rc::new(MockClock::new(sim.start_ts));
+    clock::with_clock(clock.clone(), || {
+        rng::with_seed(sim.seed, || {
+            let mut frames = Vec::with_capacity(ticks as usize);
+            for tick in 0..ticks {
+                for (_, interaction) in schedule.iter().filter(|(at, _)| *at == tick) {
+                    apply(sophie, interaction);
+                }
+                let hour = state::local_hour(clock::now_unix());
+                sophie.tick(hour, &sim.tuning);
+                frames.push(frame(sophie, tick, hour));
+                clock.advance(sim.tick_secs);
+            }
+            frames
+        })
+    })
+}
+
+fn apply(sophie: &mut SophieState, interaction: &Interaction) {
+    match interaction {
+        Interaction::Click => {
+            sophie.record_interaction();
+            sophie.stats.record_click();
+            if matches!(sophie.pet(), state::petting::PetOutcome::Enjoying(_)) {
+                sophie.position.on_positive_interaction();
+            }
+        }
+        Interaction::Feed(food) => {
+            sophie.record_interaction();
+            let intimacy_before = sophie.relationship.intimacy;
+            let traits = sophie.personality.clone();
+            sophie.relationship.on_positive_interaction(&traits);
+            sophie.position.on_positive_interaction();
+            sophie.feed(*food);
+            let gain = sophie.relationship.intimacy - intimacy_before;
+            sophie.preferences.record_food(*food, gain);
+        }
+        Interaction::ShowToy(kind) => {
+            sophie.record_interaction();
+            sophie.show_toy(kind);
+        }
+        Interaction::Comfort => {
+            sophie.comfort();
+            sophie.record_interaction();
+        }
+        Interaction::SlowBlink => {
+            sophie.record_interaction();
+            if sophie.relationship.will_slow_blink() {
+                sophie.relationship.on_slow_blink();
+            }
+        }
+        Interaction::PutToSleep => sophie.put_to_sleep(),
+        Interaction::WakeUp => {
+            sophie.wake_up();
+        }
+    }
+}
+
+fn frame(sophie: &SophieState, tick: u32, hour: u32) -> SimFrame {
+    SimFrame {
+        tick,
+        emotion: sophie.emotion,
+        emotion_intensity: sophie.emotion_intensity,
+        behavior: decide_behavior(sophie, hour),
+        is_sleeping: sophie.is_sleeping,
+        energy: sophie.physiological.energy,
+        hunger: sophie.physiological.hunger,
+        sleepiness: sophie.physiological.sleepiness,
+        trust: sophie.relationship.trust,
+        intimacy: sophie.relationship.intimacy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 本地中午 12 点左右开始
+    const START_TS: u64 = 1_700_000_000 + 6 * 3600;
+
+    fn schedule() -> Vec<(u32, Interaction)> {
+        vec![
+            (0, Interaction::Click),
+            (10, Interaction::Feed(FoodType::Fish)),
+            (20, Interaction::ShowToy("ball".to_string())),
+            (40, Interaction::SlowBlink),
+            (60, Interaction::PutToSleep),
+            (100, Interaction::WakeUp),
+        ]
+    }
+
+    fn run() -> Vec<SimFrame> {
+        let sim = SimConfig::new(42, START_TS);
+        let mut sophie = new_cat(&sim);
+        simulate(&mut sophie, 160, &schedule(), &sim)
+    }
+
+    /// 连续相同的情绪合并成 (情绪, 持续 tick 数)
+    fn trajectory(frames: &[SimFrame]) -> Vec<(Emotion, u32)> {
+        let mut runs: Vec<(Emotion, u32)> = Vec::new();
+        for f in frames {
+            match runs.last_mut() {
+                Some((emotion, n)) if *emotion == f.emotion => *n += 1,
+                _ => runs.push((f.emotion, 1)),
+            }
+        }
+        runs
+    }
+
+    #[test]
+    fn same_seed_and_schedule_give_same_frames() {
+        let first = run();
+        assert_eq!(first.len(), 160);
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    fn known_schedule_has_stable_emotion_trajectory() {
+        let frames = run();
+        assert_eq!(
+            trajectory(&frames),
+            vec![(Emotion::Happy, 20), (Emotion::Playful, 39), (Emotion::Calm, 101)],
+        );
+        assert!(frames[60].is_sleeping);
+        assert!(!frames[59].is_sleeping);
+    }
+}
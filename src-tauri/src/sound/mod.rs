@@ -0,0 +1,89 @@
+use serde::Serialize;
+
+use crate::behavior::Behavior;
+use crate::state::SophieState;
+use crate::state::emotion::Emotion;
+use crate::state::petting::PetOutcome;
+
+/// 亲密度超过这个值，被摸时才会呼噜
+const PURR_INTIMACY: f32 = 60.0;
+/// 和行为决策里"饿了走来走去"的阈值一致
+const HUNGRY_MEOW: f32 = 85.0;
+
+/// 音效 id：后端只决定什么时候出声，音频文件由前端映射
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sound {
+    /// 呼噜 - 很亲密时被摸
+    Purr,
+    /// 喵 - 饿了走来走去 / 要饭
+    Meow,
+    /// 哈气 - 烦躁时炸毛跑开/警觉，或者被摸过头
+    Hiss,
+}
+
+impl Sound {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Sound::Purr => "purr",
+            Sound::Meow => "meow",
+            Sound::Hiss => "hiss",
+        }
+    }
+}
+
+/// 换到新行为时要不要出声（条件和产生这个行为的条件一致）
+pub fn behavior_sound(sophie: &SophieState, behavior: Behavior) -> Option<Sound> {
+    if sophie.is_sleeping {
+        return None;
+    }
+    match behavior {
+        Behavior::Walk if sophie.physiological.hunger > HUNGRY_MEOW => Some(Sound::Meow),
+        Behavior::Alert if sophie.is_begging() => Some(Sound::Meow),
+        Behavior::Run | Behavior::Alert if sophie.emotion == Emotion::Irritated => Some(Sound::Hiss),
+        _ => None,
+    }
+}
+
+/// 被摸之后的声音
+pub fn petting_sound(sophie: &SophieState, outcome: PetOutcome) -> Option<Sound> {
+    match outcome {
+        PetOutcome::Enjoying(_) if sophie.relationship.intimacy > PURR_INTIMACY => Some(Sound::Purr),
+        PetOutcome::Enjoying(_) => None,
+        PetOutcome::Overstimulated => Some(Sound::Hiss),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hungry_walks_meow_and_irritated_runs_hiss() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 90.0;
+        assert_eq!(behavior_sound(&sophie, Behavior::Walk), Some(Sound::Meow));
+        sophie.physiological.hunger = 20.0;
+        assert_eq!(behavior_sound(&sophie, Behavior::Walk), None);
+
+        sophie.emotion = Emotion::Irritated;
+        assert_eq!(behavior_sound(&sophie, Behavior::Run), Some(Sound::Hiss));
+        assert_eq!(behavior_sound(&sophie, Behavior::Alert), Some(Sound::Hiss));
+        assert_eq!(behavior_sound(&sophie, Behavior::Idle), None);
+
+        // 睡着了不出声
+        sophie.is_sleeping = true;
+        assert_eq!(behavior_sound(&sophie, Behavior::Run), None);
+    }
+
+    #[test]
+    fn petting_purrs_only_when_close() {
+        let mut sophie = SophieState::new();
+        sophie.relationship.intimacy = 80.0;
+        assert_eq!(petting_sound(&sophie, PetOutcome::Enjoying(1.0)), Some(Sound::Purr));
+        sophie.relationship.intimacy = 30.0;
+        assert_eq!(petting_sound(&sophie, PetOutcome::Enjoying(1.0)), None);
+        assert_eq!(petting_sound(&sophie, PetOutcome::Overstimulated), Some(Sound::Hiss));
+        assert_eq!(Sound::Purr.id(), "purr");
+    }
+}
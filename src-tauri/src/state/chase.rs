@@ -0,0 +1,57 @@
+/// 光标停下来超过这么久（秒），这轮追逐就结束了（后台循环 10 秒一轮，留点余量）
+const SESSION_GAP_SECS: u64 = 15;
+/// 光标在这个高度以下（0 = 屏幕顶端，1 = 底端）才够得着，去扑；更高的只盯着看
+const REACHABLE_Y: f32 = 0.6;
+
+/// 追光标：一轮里扑了几下、光标在哪（不持久化）
+#[derive(Debug, Clone)]
+pub struct ChaseSession {
+    pub pounces: u32,
+    /// 光标的逻辑位置，与 `PositionState` 一样 0-1
+    pub target_x: f32,
+    pub target_y: f32,
+    pub last_pounce_ts: u64,
+}
+
+impl ChaseSession {
+    pub fn new() -> Self {
+        Self {
+            pounces: 0,
+            target_x: 0.5,
+            target_y: 1.0,
+            last_pounce_ts: 0,
+        }
+    }
+
+    /// 扑一下光标；停顿太久就从头开始一轮。返回这轮扑了几下
+    pub fn pounce(&mut self, now: u64, x: f32, y: f32) -> u32 {
+        if !self.is_active(now) {
+            self.pounces = 0;
+        }
+        self.pounces += 1;
+        self.target_x = if x.is_finite() { x.clamp(0.0, 1.0) } else { 0.5 };
+        self.target_y = if y.is_finite() { y.clamp(0.0, 1.0) } else { 1.0 };
+        self.last_pounce_ts = now;
+        self.pounces
+    }
+
+    /// 这轮追逐还在进行吗
+    pub fn is_active(&self, now: u64) -> bool {
+        self.pounces > 0 && now.saturating_sub(self.last_pounce_ts) <= SESSION_GAP_SECS
+    }
+
+    /// 正在追的光标位置（没在追为 None）
+    pub fn target(&self, now: u64) -> Option<(f32, f32)> {
+        self.is_active(now).then_some((self.target_x, self.target_y))
+    }
+
+    /// 光标够不够得着（够不着就只盯着看）
+    pub fn is_reachable(y: f32) -> bool {
+        y >= REACHABLE_Y
+    }
+
+    /// 停下这轮追逐（累了/不想玩了）
+    pub fn stop(&mut self) {
+        self.pounces = 0;
+    }
+}
@@ -1,4 +1,28 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::config::TransitionConfig;
+
+/// 情绪强度的基线（0-1）：没有强烈事件时逐渐回归到这里
+pub const BASELINE_INTENSITY: f32 = 0.5;
+/// 每次 tick 向基线回归的比例
+const INTENSITY_DECAY: f32 = 0.1;
+
+/// 情绪强度向基线回归一步
+pub fn decay_intensity(intensity: f32) -> f32 {
+    intensity + (BASELINE_INTENSITY - intensity) * INTENSITY_DECAY
+}
+
+/// 主人在不在电脑前（由前端上报的键鼠活动推断）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Presence {
+    /// 前端从没上报过，按纯计时逻辑处理
+    Unknown,
+    /// 在电脑前但没理她
+    Present,
+    /// 离开了
+    Away,
+}
 
 /// 第二层：情绪状态
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -17,29 +41,122 @@ pub enum Emotion {
     Irritated,
     /// 低落 - 被长期忽视
     Down,
+    /// 黏人 - 很亲密却突然被冷落，想尽办法引起注意
+    Needy,
 }
 
 impl Emotion {
+    pub const ALL: [Emotion; 8] = [
+        Emotion::Happy,
+        Emotion::Calm,
+        Emotion::Curious,
+        Emotion::Playful,
+        Emotion::Bored,
+        Emotion::Irritated,
+        Emotion::Down,
+        Emotion::Needy,
+    ];
+
+    /// 稳定的 id（给前端用，不随 Debug 格式变化）
+    pub fn id(&self) -> &'static str {
+        match self {
+            Emotion::Happy => "happy",
+            Emotion::Calm => "calm",
+            Emotion::Curious => "curious",
+            Emotion::Playful => "playful",
+            Emotion::Bored => "bored",
+            Emotion::Irritated => "irritated",
+            Emotion::Down => "down",
+            Emotion::Needy => "needy",
+        }
+    }
+
+    /// 默认的代表色
+    pub fn color(&self) -> &'static str {
+        match self {
+            Emotion::Happy => "#ffc857",
+            Emotion::Calm => "#8ecae6",
+            Emotion::Curious => "#90be6d",
+            Emotion::Playful => "#f9844a",
+            Emotion::Bored => "#b8b8b8",
+            Emotion::Irritated => "#e63946",
+            Emotion::Down => "#5c677d",
+            Emotion::Needy => "#f4a6c6",
+        }
+    }
+
+    /// 默认的动画活跃度 0-1：越高动作越快越大
+    pub fn animation_energy(&self) -> f32 {
+        match self {
+            Emotion::Playful => 1.0,
+            Emotion::Irritated => 0.8,
+            Emotion::Curious | Emotion::Needy => 0.7,
+            Emotion::Happy => 0.6,
+            Emotion::Calm => 0.4,
+            Emotion::Bored => 0.3,
+            Emotion::Down => 0.2,
+        }
+    }
+
+    /// 这种情绪下发生的事有多容易被记住（平静 = 1.0）
+    pub fn imprint_factor(&self) -> f32 {
+        match self {
+            Emotion::Happy | Emotion::Irritated => 1.3,
+            Emotion::Needy | Emotion::Down => 1.2,
+            Emotion::Curious | Emotion::Playful => 1.1,
+            Emotion::Calm => 1.0,
+            Emotion::Bored => 0.9,
+        }
+    }
+
+    /// 这种情绪下说话的"发散度"偏移：想玩/好奇更天马行空，烦躁/低落更简短可预期
+    pub fn temperature_shift(&self) -> f32 {
+        match self {
+            Emotion::Playful => 0.3,
+            Emotion::Curious => 0.2,
+            Emotion::Happy | Emotion::Needy => 0.1,
+            Emotion::Calm | Emotion::Bored => 0.0,
+            Emotion::Down => -0.3,
+            Emotion::Irritated => -0.4,
+        }
+    }
+
     /// 根据上下文判断情绪转移
+    ///
+    /// `intensity` 越高，当前情绪维持得越久（基线强度下与原阈值一致）。
+    /// `presence` 区分"人在却不理我"（更容易烦躁/黏人）和"人不在"（安静下来）。
+    /// `patience` 缩放被冷落的各个阈值（见 `PersonalityTraits::neglect_patience`，中间值为 1）。
+    /// 各个阈值见 `TransitionConfig`
+    #[allow(clippy::too_many_arguments)]
     pub fn transition(
         &self,
         has_interaction: bool,
         minutes_since_interaction: u32,
         energy: f32,
         intimacy: f32,
+        intensity: f32,
+        presence: Presence,
+        patience: f32,
+        cfg: &TransitionConfig,
     ) -> Emotion {
+        let hold = 0.5 + intensity.clamp(0.0, 1.0);
+        let held = |minutes: f32| minutes_since_interaction as f32 > minutes * hold;
+        // 被冷落的阈值按黏人程度缩放：黏人的猫更快开始闹
+        let neglected = |minutes: f32| minutes_since_interaction as f32 > minutes * patience;
         match self {
             Emotion::Calm => {
-                if has_interaction && energy > 50.0 {
+                // 人不在的时候没什么好无聊的，能平静更久
+                let bored_after = if presence == Presence::Away { cfg.bored_away_minutes } else { cfg.bored_minutes };
+                if has_interaction && energy > cfg.happy_min_energy {
                     Emotion::Happy
-                } else if minutes_since_interaction > 120 {
+                } else if neglected(bored_after) {
                     Emotion::Bored
                 } else {
                     Emotion::Calm
                 }
             }
             Emotion::Happy => {
-                if minutes_since_interaction > 60 {
+                if held(cfg.happy_minutes) {
                     Emotion::Calm
                 } else {
                     Emotion::Happy
@@ -48,8 +165,20 @@ impl Emotion {
             Emotion::Bored => {
                 if has_interaction {
                     Emotion::Happy
-                } else if minutes_since_interaction > 240 {
-                    if intimacy > 40.0 {
+                } else if presence == Presence::Away {
+                    // 人走了：闹也没用，慢慢平静下来
+                    if minutes_since_interaction as f32 > cfg.away_calm_minutes {
+                        Emotion::Calm
+                    } else {
+                        Emotion::Bored
+                    }
+                } else if intimacy > cfg.needy_intimacy * patience {
+                    Emotion::Needy
+                } else if presence == Presence::Present && neglected(cfg.ignored_minutes) {
+                    // 明明在电脑前却不理她
+                    Emotion::Irritated
+                } else if neglected(cfg.neglect_minutes) {
+                    if intimacy > cfg.neglect_irritated_intimacy {
                         Emotion::Irritated
                     } else {
                         Emotion::Down
@@ -59,30 +188,42 @@ impl Emotion {
                 }
             }
             Emotion::Irritated => {
-                if minutes_since_interaction > 30 && !has_interaction {
+                if held(cfg.irritated_minutes) && !has_interaction {
                     Emotion::Calm
                 } else {
                     Emotion::Irritated
                 }
             }
             Emotion::Down => {
-                if has_interaction && intimacy > 30.0 {
+                if has_interaction && intimacy > cfg.down_recover_intimacy {
                     Emotion::Calm
                 } else {
                     Emotion::Down
                 }
             }
+            Emotion::Needy => {
+                // 一直没人理就开始闹脾气，而不是像陌生猫那样低落
+                if has_interaction {
+                    Emotion::Happy
+                } else if presence == Presence::Away {
+                    Emotion::Calm
+                } else if neglected(cfg.needy_minutes) {
+                    Emotion::Irritated
+                } else {
+                    Emotion::Needy
+                }
+            }
             Emotion::Curious => {
-                if minutes_since_interaction > 10 {
+                if held(cfg.curious_minutes) {
                     Emotion::Calm
                 } else {
                     Emotion::Curious
                 }
             }
             Emotion::Playful => {
-                if energy < 40.0 {
+                if energy < cfg.playful_min_energy {
                     Emotion::Calm
-                } else if minutes_since_interaction > 30 {
+                } else if held(cfg.playful_minutes) {
                     Emotion::Bored
                 } else {
                     Emotion::Playful
@@ -91,3 +232,136 @@ impl Emotion {
         }
     }
 }
+
+impl FromStr for Emotion {
+    type Err = String;
+
+    /// 按变体名解析，不区分大小写（"happy" / "Happy"）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Emotion::ALL
+            .into_iter()
+            .find(|e| format!("{:?}", e).eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown emotion: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::personality::PersonalityTraits;
+
+    #[test]
+    fn intensity_decays_toward_baseline_from_both_sides() {
+        let mut high = 1.0;
+        let mut low = 0.0;
+        for _ in 0..50 {
+            let (next_high, next_low) = (decay_intensity(high), decay_intensity(low));
+            assert!(next_high < high && next_high > BASELINE_INTENSITY);
+            assert!(next_low > low && next_low < BASELINE_INTENSITY);
+            high = next_high;
+            low = next_low;
+        }
+        assert!((high - BASELINE_INTENSITY).abs() < 0.01);
+        assert!((low - BASELINE_INTENSITY).abs() < 0.01);
+    }
+
+    #[test]
+    fn strong_emotions_last_longer() {
+        let defaults = TransitionConfig::new();
+        // 基线强度：与原来的 60 分钟阈值一致
+        assert_eq!(Emotion::Happy.transition(false, 61, 80.0, 50.0, BASELINE_INTENSITY, Presence::Unknown, 1.0, &defaults), Emotion::Calm);
+        assert_eq!(Emotion::Happy.transition(false, 59, 80.0, 50.0, BASELINE_INTENSITY, Presence::Unknown, 1.0, &defaults), Emotion::Happy);
+
+        // 非常开心：同样的空档还开心着；很淡的开心很快就没了
+        assert_eq!(Emotion::Happy.transition(false, 61, 80.0, 50.0, 1.0, Presence::Unknown, 1.0, &defaults), Emotion::Happy);
+        assert_eq!(Emotion::Happy.transition(false, 31, 80.0, 50.0, 0.0, Presence::Unknown, 1.0, &defaults), Emotion::Calm);
+    }
+
+    #[test]
+    fn neglect_makes_a_close_cat_needy_and_a_distant_one_down() {
+        let defaults = TransitionConfig::new();
+        let b = BASELINE_INTENSITY;
+        // 亲密：无聊一来就变黏人；不亲密：一直无聊，直到 4 小时后低落
+        assert_eq!(Emotion::Bored.transition(false, 150, 60.0, 80.0, b, Presence::Unknown, 1.0, &defaults), Emotion::Needy);
+        assert_eq!(Emotion::Bored.transition(false, 150, 60.0, 20.0, b, Presence::Unknown, 1.0, &defaults), Emotion::Bored);
+        assert_eq!(Emotion::Bored.transition(false, 250, 60.0, 20.0, b, Presence::Unknown, 1.0, &defaults), Emotion::Down);
+    }
+
+    #[test]
+    fn needy_turns_irritated_if_still_ignored_and_happy_when_noticed() {
+        let defaults = TransitionConfig::new();
+        let b = BASELINE_INTENSITY;
+        assert_eq!(Emotion::Needy.transition(false, 200, 60.0, 80.0, b, Presence::Unknown, 1.0, &defaults), Emotion::Needy);
+        assert_eq!(Emotion::Needy.transition(false, 301, 60.0, 80.0, b, Presence::Unknown, 1.0, &defaults), Emotion::Irritated);
+        assert_eq!(Emotion::Needy.transition(true, 0, 60.0, 80.0, b, Presence::Unknown, 1.0, &defaults), Emotion::Happy);
+    }
+
+    #[test]
+    fn ignored_while_present_irritates_but_away_calms_down() {
+        let defaults = TransitionConfig::new();
+        let b = BASELINE_INTENSITY;
+        let bored = |minutes, presence| Emotion::Bored.transition(false, minutes, 60.0, 20.0, b, presence, 1.0, &defaults);
+        assert_eq!(bored(150, Presence::Present), Emotion::Irritated);
+        assert_eq!(bored(150, Presence::Away), Emotion::Bored);
+        assert_eq!(bored(250, Presence::Away), Emotion::Calm);
+        assert_eq!(bored(250, Presence::Unknown), Emotion::Down);
+
+        // 人不在时平静得更久；黏人的猫发现人走了也就算了
+        assert_eq!(Emotion::Calm.transition(false, 150, 60.0, 20.0, b, Presence::Away, 1.0, &defaults), Emotion::Calm);
+        assert_eq!(Emotion::Calm.transition(false, 150, 60.0, 20.0, b, Presence::Present, 1.0, &defaults), Emotion::Bored);
+        assert_eq!(Emotion::Needy.transition(false, 100, 60.0, 80.0, b, Presence::Away, 1.0, &defaults), Emotion::Calm);
+    }
+
+    #[test]
+    fn emotions_parse_by_name_ignoring_case() {
+        for emotion in Emotion::ALL {
+            assert_eq!(format!("{:?}", emotion).to_lowercase().parse::<Emotion>(), Ok(emotion));
+        }
+        assert_eq!(" NEEDY ".parse::<Emotion>(), Ok(Emotion::Needy));
+        assert!("grumpy".parse::<Emotion>().is_err());
+    }
+
+    #[test]
+    fn a_clingy_cat_gets_upset_sooner_than_an_aloof_one() {
+        let defaults = TransitionConfig::new();
+        let b = BASELINE_INTENSITY;
+        let clingy = PersonalityTraits { clinginess: 1.0, ..PersonalityTraits::neutral() }.neglect_patience();
+        let aloof = PersonalityTraits { clinginess: 0.0, ..PersonalityTraits::neutral() }.neglect_patience();
+        // 同样被冷落 90 分钟、主人就在电脑前
+        let calm = |patience| Emotion::Calm.transition(false, 90, 60.0, 20.0, b, Presence::Present, patience, &defaults);
+        assert_eq!(calm(clingy), Emotion::Bored);
+        assert_eq!(calm(aloof), Emotion::Calm);
+        let bored = |patience| Emotion::Bored.transition(false, 90, 60.0, 20.0, b, Presence::Present, patience, &defaults);
+        assert_eq!(bored(clingy), Emotion::Irritated);
+        assert_eq!(bored(aloof), Emotion::Bored);
+        // 黏人的猫亲密度不高也会黏上来
+        assert_eq!(Emotion::Bored.transition(false, 30, 60.0, 40.0, b, Presence::Unknown, clingy, &defaults), Emotion::Needy);
+        assert_eq!(Emotion::Bored.transition(false, 30, 60.0, 40.0, b, Presence::Unknown, aloof, &defaults), Emotion::Bored);
+    }
+
+    /// 平静、没人理、不知道主人在不在、中等黏人，`minutes` 分钟后的下一个情绪
+    fn calm_after(minutes: u32, cfg: &TransitionConfig) -> Emotion {
+        Emotion::Calm.transition(false, minutes, 80.0, 20.0, BASELINE_INTENSITY, Presence::Unknown, 1.0, cfg)
+    }
+
+    #[test]
+    fn bored_threshold_comes_from_config() {
+        let defaults = TransitionConfig::new();
+        assert_eq!(calm_after(11, &defaults), Emotion::Calm);
+
+        let cfg = TransitionConfig { bored_minutes: 10.0, ..TransitionConfig::new() };
+        assert_eq!(calm_after(9, &cfg), Emotion::Calm);
+        assert_eq!(calm_after(11, &cfg), Emotion::Bored);
+    }
+
+    #[test]
+    fn away_uses_its_own_bored_threshold() {
+        let cfg = TransitionConfig { bored_minutes: 10.0, bored_away_minutes: 60.0, ..TransitionConfig::new() };
+        let away = |minutes| {
+            Emotion::Calm.transition(false, minutes, 80.0, 20.0, BASELINE_INTENSITY, Presence::Away, 1.0, &cfg)
+        };
+        assert_eq!(away(11), Emotion::Calm);
+        assert_eq!(away(61), Emotion::Bored);
+    }
+}
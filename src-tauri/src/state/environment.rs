@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+/// 天气（由前端/用户告知，不联网）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Weather {
+    Unknown,
+    Sunny,
+    Cloudy,
+    Rainy,
+    Snowy,
+}
+
+/// 季节
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Season {
+    Unknown,
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+/// 外部环境：会影响行为倾向和想法
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Environment {
+    pub weather: Weather,
+    pub season: Season,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            weather: Weather::Unknown,
+            season: Season::Unknown,
+        }
+    }
+
+    /// 冷天：下雪或冬天，会找暖和的地方窝着
+    pub fn is_cold(&self) -> bool {
+        self.weather == Weather::Snowy || self.season == Season::Winter
+    }
+
+    /// 给 prompt 用的简短描述
+    pub fn describe(&self) -> String {
+        let weather = match self.weather {
+            Weather::Unknown => None,
+            Weather::Sunny => Some("晴天"),
+            Weather::Cloudy => Some("阴天"),
+            Weather::Rainy => Some("下雨"),
+            Weather::Snowy => Some("下雪"),
+        };
+        let season = match self.season {
+            Season::Unknown => None,
+            Season::Spring => Some("春天"),
+            Season::Summer => Some("夏天"),
+            Season::Autumn => Some("秋天"),
+            Season::Winter => Some("冬天"),
+        };
+        let parts: Vec<&str> = [weather, season].into_iter().flatten().collect();
+        if parts.is_empty() {
+            "不清楚".to_string()
+        } else {
+            parts.join("，")
+        }
+    }
+}
+
+impl Weather {
+    /// 解析名字，不认识的当作未知
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "sunny" | "clear" | "晴" | "晴天" => Weather::Sunny,
+            "cloudy" | "overcast" | "阴" | "阴天" => Weather::Cloudy,
+            "rainy" | "rain" | "雨" | "下雨" => Weather::Rainy,
+            "snowy" | "snow" | "雪" | "下雪" => Weather::Snowy,
+            _ => Weather::Unknown,
+        }
+    }
+}
+
+impl Season {
+    /// 解析名字，不认识的当作未知
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "spring" | "春" | "春天" => Season::Spring,
+            "summer" | "夏" | "夏天" => Season::Summer,
+            "autumn" | "fall" | "秋" | "秋天" => Season::Autumn,
+            "winter" | "冬" | "冬天" => Season::Winter,
+            _ => Season::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_parse_in_both_languages() {
+        assert_eq!(Weather::parse(" Sunny "), Weather::Sunny);
+        assert_eq!(Weather::parse("下雨"), Weather::Rainy);
+        assert_eq!(Weather::parse("tornado"), Weather::Unknown);
+        assert_eq!(Season::parse("fall"), Season::Autumn);
+        assert_eq!(Season::parse("冬天"), Season::Winter);
+        assert_eq!(Season::parse(""), Season::Unknown);
+    }
+
+    #[test]
+    fn describe_skips_unknown_parts() {
+        let mut env = Environment::new();
+        assert_eq!(env.describe(), "不清楚");
+        env.season = Season::Winter;
+        assert_eq!(env.describe(), "冬天");
+        env.weather = Weather::Snowy;
+        assert_eq!(env.describe(), "下雪，冬天");
+    }
+
+    #[test]
+    fn snow_or_winter_counts_as_cold() {
+        let mut env = Environment::new();
+        assert!(!env.is_cold());
+        env.weather = Weather::Snowy;
+        assert!(env.is_cold());
+        env = Environment { weather: Weather::Sunny, season: Season::Winter };
+        assert!(env.is_cold());
+    }
+}
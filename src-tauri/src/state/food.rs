@@ -0,0 +1,136 @@
+/// 喂的东西：正餐管饱，零食讨欢心
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FoodType {
+    /// 正餐（猫粮）：大幅缓解饥饿，开心一点点
+    Meal,
+    /// 零食：几乎不顶饿，但非常开心
+    Treat,
+    /// 鱼：介于两者之间
+    Fish,
+}
+
+impl FoodType {
+    /// 解析前端传来的名字，不认识的当作正餐
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "treat" | "snack" | "零食" => FoodType::Treat,
+            "fish" | "鱼" => FoodType::Fish,
+            _ => FoodType::Meal,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FoodType::Meal => "meal",
+            FoodType::Treat => "treat",
+            FoodType::Fish => "fish",
+        }
+    }
+
+    /// 中文名（写进记忆）
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodType::Meal => "猫粮",
+            FoodType::Treat => "零食",
+            FoodType::Fish => "鱼",
+        }
+    }
+
+    /// 能减少多少饥饿
+    pub fn hunger_relief(&self) -> f32 {
+        match self {
+            FoodType::Meal => 30.0,
+            FoodType::Treat => 8.0,
+            FoodType::Fish => 20.0,
+        }
+    }
+
+    /// 情绪强度提升
+    pub fn delight(&self) -> f32 {
+        match self {
+            FoodType::Meal => 0.2,
+            FoodType::Treat => 0.5,
+            FoodType::Fish => 0.35,
+        }
+    }
+
+    /// 额外增加的亲密度（在普通正向互动之外）
+    pub fn intimacy_bonus(&self) -> f32 {
+        match self {
+            FoodType::Meal => 0.0,
+            FoodType::Treat => 1.0,
+            FoodType::Fish => 0.5,
+        }
+    }
+
+    /// 是否好吃到直接变开心
+    pub fn makes_happy(&self) -> bool {
+        !matches!(self, FoodType::Meal)
+    }
+
+    /// 记忆的情感权重
+    pub fn memory_weight(&self) -> f32 {
+        match self {
+            FoodType::Meal => 0.6,
+            FoodType::Treat => 0.8,
+            FoodType::Fish => 0.7,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SophieState;
+    use crate::state::emotion::{Emotion, BASELINE_INTENSITY};
+
+    fn hungry_calm() -> SophieState {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 80.0;
+        sophie.emotion = Emotion::Calm;
+        sophie.emotion_intensity = BASELINE_INTENSITY;
+        sophie
+    }
+
+    #[test]
+    fn unknown_food_is_a_meal() {
+        assert_eq!(FoodType::parse(" Treat "), FoodType::Treat);
+        assert_eq!(FoodType::parse("零食"), FoodType::Treat);
+        assert_eq!(FoodType::parse("鱼"), FoodType::Fish);
+        assert_eq!(FoodType::parse("kibble"), FoodType::Meal);
+    }
+
+    #[test]
+    fn meal_fills_her_up_without_changing_mood() {
+        let mut sophie = hungry_calm();
+        let intimacy = sophie.relationship.intimacy;
+        sophie.feed(FoodType::Meal);
+        assert_eq!(sophie.physiological.hunger, 50.0);
+        assert_eq!(sophie.emotion, Emotion::Calm);
+        assert!((sophie.emotion_intensity - 0.7).abs() < 1e-6);
+        assert_eq!(sophie.relationship.intimacy, intimacy);
+    }
+
+    #[test]
+    fn treat_barely_helps_hunger_but_delights() {
+        let mut sophie = hungry_calm();
+        let intimacy = sophie.relationship.intimacy;
+        sophie.feed(FoodType::Treat);
+        assert_eq!(sophie.physiological.hunger, 72.0);
+        assert_eq!(sophie.emotion, Emotion::Happy);
+        assert_eq!(sophie.emotion_intensity, 1.0);
+        assert_eq!(sophie.relationship.intimacy, intimacy + 1.0);
+    }
+
+    #[test]
+    fn fish_sits_in_between() {
+        let mut sophie = hungry_calm();
+        let intimacy = sophie.relationship.intimacy;
+        sophie.feed(FoodType::Fish);
+        assert_eq!(sophie.physiological.hunger, 60.0);
+        assert_eq!(sophie.emotion, Emotion::Happy);
+        assert!((sophie.emotion_intensity - 0.85).abs() < 1e-6);
+        assert_eq!(sophie.relationship.intimacy, intimacy + 0.5);
+        assert_eq!(sophie.stats.feeds, 1);
+    }
+}
@@ -0,0 +1,143 @@
+use serde_json::{Map, Value};
+
+use super::emotion::Emotion;
+use super::personality::PersonalityTraits;
+use super::SophieState;
+
+/// 状态结构的版本；改了字段含义或改名就加一，并在 `migrate` 里加一步升级
+pub const STATE_SCHEMA_VERSION: u32 = 2;
+
+/// 没有 `schema_version` 的存档都是 v1
+pub fn legacy_schema_version() -> u32 {
+    1
+}
+
+/// 读档：先把旧结构升级到当前版本再反序列化，而不是解析失败就整个重来
+pub fn load(json: &str) -> Result<SophieState, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid state JSON: {}", e))?;
+    from_value(value)
+}
+
+/// 同 `load`，用于已经解析成 JSON 的状态（导入存档）
+pub fn from_value(value: Value) -> Result<SophieState, String> {
+    let value = migrate(value)?;
+    serde_json::from_value(value).map_err(|e| format!("Invalid state after migration: {}", e))
+}
+
+/// 逐版本升级状态 JSON，直到当前版本
+pub fn migrate(mut value: Value) -> Result<Value, String> {
+    let obj = value.as_object_mut().ok_or("State is not a JSON object")?;
+    let mut version = obj
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map_or(legacy_schema_version(), |v| v as u32);
+    if version > STATE_SCHEMA_VERSION {
+        return Err(format!(
+            "State schema version {} is newer than this app ({})",
+            version, STATE_SCHEMA_VERSION
+        ));
+    }
+    while version < STATE_SCHEMA_VERSION {
+        match version {
+            1 => migrate_v1(obj),
+            _ => return Err(format!("Unsupported state schema version: {}", version)),
+        }
+        version += 1;
+    }
+    obj.insert("schema_version".to_string(), Value::from(STATE_SCHEMA_VERSION));
+    Ok(value)
+}
+
+/// v1 → v2：缺的字段（包括嵌套结构里的）用默认值补上，认不出的情绪当作平静
+fn migrate_v1(obj: &mut Map<String, Value>) {
+    let mut defaults = SophieState::new();
+    // 旧存档一直按中性性格处理，不能升级后突然换了一只猫
+    defaults.personality = PersonalityTraits::neutral();
+    let had_born_ts = obj.get("stats").and_then(|stats| stats.get("born_ts")).is_some();
+    if let Ok(Value::Object(defaults)) = serde_json::to_value(&defaults) {
+        fill_missing(obj, &defaults);
+    }
+    // 时间类字段的默认值是"现在"：没有上次互动时间就当刚互动过，不能凭空算成被冷落了很久；
+    // 但出生时间不能晚于存档里已有的上次互动，否则升级后年龄会归零
+    if !had_born_ts {
+        if let Some(last) = obj.get("last_interaction_ts").and_then(Value::as_u64) {
+            if let Some(Value::Object(stats)) = obj.get_mut("stats") {
+                let born = stats.get("born_ts").and_then(Value::as_u64).map_or(last, |b| b.min(last));
+                stats.insert("born_ts".to_string(), Value::from(born));
+            }
+        }
+    }
+    let known = obj
+        .get("emotion")
+        .and_then(Value::as_str)
+        .is_some_and(|name| name.parse::<Emotion>().is_ok());
+    if !known {
+        log::warn!("Unknown emotion in old state: {:?}, using Calm", obj.get("emotion"));
+        obj.insert("emotion".to_string(), Value::from(format!("{:?}", Emotion::Calm)));
+    }
+}
+
+/// 只补缺失的键，已有的值保持不动；两边都是对象时递归
+fn fill_missing(target: &mut Map<String, Value>, defaults: &Map<String, Value>) {
+    for (key, default) in defaults {
+        match (target.get_mut(key), default) {
+            (Some(Value::Object(existing)), Value::Object(nested)) => fill_missing(existing, nested),
+            (Some(_), _) => {}
+            (None, _) => {
+                target.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// v1 存档：没有版本号，也没有后来加的字段
+    fn v1_save() -> Value {
+        json!({
+            "physiological": { "hunger": 42.0, "energy": 70.0, "sleepiness": 10.0 },
+            "emotion": "Happy",
+            "relationship": { "trust": 55.0, "intimacy": 33.0 },
+            "last_interaction_ts": 1_700_000_000u64,
+            "is_sleeping": false,
+        })
+    }
+
+    #[test]
+    fn v1_saves_keep_their_data_and_gain_new_fields() {
+        let sophie = from_value(v1_save()).unwrap();
+        assert_eq!(sophie.schema_version, STATE_SCHEMA_VERSION);
+        assert_eq!(sophie.emotion, Emotion::Happy);
+        assert_eq!(sophie.physiological.hunger, 42.0);
+        assert_eq!(sophie.relationship.trust, 55.0);
+        assert_eq!(sophie.relationship.intimacy, 33.0);
+        assert_eq!(sophie.last_interaction_ts, 1_700_000_000);
+        // 没记录出生时间，至少早于上次互动
+        assert_eq!(sophie.stats.born_ts, 1_700_000_000);
+        assert_eq!(sophie.personality.seed, PersonalityTraits::neutral().seed);
+    }
+
+    #[test]
+    fn unknown_emotions_in_old_saves_become_calm() {
+        let mut save = v1_save();
+        save["emotion"] = json!("Hangry");
+        assert_eq!(from_value(save).unwrap().emotion, Emotion::Calm);
+    }
+
+    #[test]
+    fn saves_from_a_newer_app_are_rejected() {
+        let mut save = serde_json::to_value(SophieState::new()).unwrap();
+        save["schema_version"] = json!(STATE_SCHEMA_VERSION + 1);
+        assert!(from_value(save).unwrap_err().contains("newer"));
+        assert!(load("[1, 2]").is_err());
+
+        // 当前版本的存档原样读回
+        let current = SophieState::new();
+        let json = serde_json::to_string(&current).unwrap();
+        assert_eq!(load(&json).unwrap().stats.born_ts, current.stats.born_ts);
+    }
+}
+
@@ -1,74 +1,334 @@
 pub mod physiological;
 pub mod emotion;
 pub mod relationship;
+pub mod position;
+pub mod stats;
+pub mod food;
+pub mod petting;
+pub mod chase;
+pub mod environment;
+pub mod personality;
+pub mod toys;
+pub mod preferences;
+pub mod migrate;
+pub mod palette;
+pub mod schedule;
+pub mod tricks;
 
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::TuningConfig;
+
+/// 超过这么久没有键鼠活动上报就认为主人离开了（秒）
+const USER_AWAY_SECS: u64 = 300;
+/// 亲密度超过这个值，饿了会缠着主人要饭
+const BEG_INTIMACY: f32 = 50.0;
+
+/// 每次安抚降低的情绪强度
+const COMFORT_STEP: f32 = 0.25;
+/// 情绪强度降到这里就平静下来
+const COMFORT_CALM_INTENSITY: f32 = 0.3;
+/// 短期互动超过这么多次，再安抚只会更烦
+const COMFORT_MAX_RECENT: u32 = 6;
+
+/// 被主人情绪感染的门槛：平均语气 × 亲密度比例超过它才受影响
+const CONTAGION_THRESHOLD: f32 = 0.4;
+/// 亲密度低于这个值时察觉不到主人的情绪
+const CONTAGION_MIN_INTIMACY: f32 = 30.0;
+
+/// 到了平时的饭点，要饭的饥饿门槛降低这么多
+const MEAL_TIME_BEG_DISCOUNT: f32 = 15.0;
+
+/// 烦躁的强度超过这个值就不理人（强度每次 tick 向基线回落，越烦躁不理人越久）
+const SNUB_INTENSITY: f32 = 0.6;
+
+/// 最喜欢的玩具额外增加的新鲜感
+const FAVORITE_TOY_BONUS: f32 = 0.3;
+
+/// 能量高于这个值才有兴致追光标
+const CHASE_MIN_ENERGY: f32 = 40.0;
+/// 每扑一下消耗的能量（扑多了就累了，低于门槛就不追了）
+const CHASE_ENERGY_COST: f32 = 2.0;
+
+/// 照口令做了一次把戏涨的亲密度
+const TRICK_INTIMACY: f32 = 1.0;
+
+/// 昨天互动这么多次算"被好好陪伴了"
+const ATTENTIVE_DAY_INTERACTIONS: u32 = 5;
+
+/// 电脑休眠/锁屏期间最多按这么久（秒）算她过了多少时间，超出的部分当作没发生
+pub const MAX_PAUSE_AGING_SECS: u64 = 30 * 60;
+
+/// 刚睡醒后迷糊多久（秒）
+pub const GROGGY_SECS: u64 = 90;
 
 /// Sophie 的完整状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SophieState {
+    /// 存档结构版本，读档时按它升级旧数据（见 `migrate`）
+    #[serde(default = "migrate::legacy_schema_version")]
+    pub schema_version: u32,
     pub physiological: physiological::PhysiologicalState,
     pub emotion: emotion::Emotion,
+    /// 情绪强度 0-1：强烈事件提升，每次 tick 回归基线
+    #[serde(default = "default_emotion_intensity")]
+    pub emotion_intensity: f32,
     pub relationship: relationship::RelationshipState,
     /// 是否正在睡觉
     pub is_sleeping: bool,
+    /// 体力耗尽累倒了：睡到体力恢复才会醒，叫也叫不醒
+    #[serde(default)]
+    pub collapsed: bool,
     /// 上次互动的 Unix 时间戳（秒）
     pub last_interaction_ts: u64,
     /// 短期内的互动次数（用于判断是否过度打扰）
     pub recent_interaction_count: u32,
     /// 上次重置互动计数的时间戳
     pub interaction_count_reset_ts: u64,
+    /// 屏幕上的逻辑位置与最喜欢的位置
+    #[serde(default = "position::PositionState::new")]
+    pub position: position::PositionState,
+    /// 互动统计
+    #[serde(default = "stats::InteractionStats::new")]
+    pub stats: stats::InteractionStats,
+    /// 这次睡眠最后一个梦是不是好梦（醒来时影响情绪）
+    #[serde(default)]
+    pub last_dream_pleasant: Option<bool>,
+    /// 前端最近一次上报键鼠活动的时间戳（None = 从没上报过）
+    #[serde(default)]
+    pub user_activity_ts: Option<u64>,
+    /// 最近一次上报时主人是否活跃
+    #[serde(default)]
+    pub user_active: bool,
+    /// 正在进行的连续抚摸
+    #[serde(skip, default = "petting::PettingSession::new")]
+    pub petting: petting::PettingSession,
+    /// 正在进行的追光标
+    #[serde(skip, default = "chase::ChaseSession::new")]
+    pub chase: chase::ChaseSession,
+    /// 天气和季节（由主人告知）
+    #[serde(default = "environment::Environment::new")]
+    pub environment: environment::Environment,
+    /// 性格特质（旧存档没有时按中性性格处理）
+    #[serde(default = "personality::PersonalityTraits::neutral")]
+    pub personality: personality::PersonalityTraits,
+    /// 最近一次醒来的时间戳（醒来后的一小段时间里迷迷糊糊）
+    #[serde(default)]
+    pub woke_at_ts: Option<u64>,
+    /// 最近见过的玩具和新鲜感
+    #[serde(default = "toys::ToyMemory::new")]
+    pub toys: toys::ToyMemory,
+    /// 上次 tick 时的本地日期编号（天），用来发现跨天
+    #[serde(default)]
+    pub last_local_day: Option<i64>,
+    /// 慢慢形成的食物/玩具偏好
+    #[serde(default = "preferences::Preferences::new")]
+    pub preferences: preferences::Preferences,
+    /// 学到的喂食时间
+    #[serde(default = "schedule::FeedingSchedule::new")]
+    pub schedule: schedule::FeedingSchedule,
+    /// 把戏的训练进度
+    #[serde(default = "tricks::TrickTraining::new")]
+    pub tricks: tricks::TrickTraining,
+}
+
+/// 安抚的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComfortOutcome {
+    /// 心情本来就不差，不需要安抚
+    NotNeeded,
+    /// 好一点了，还没完全平静
+    Easing,
+    /// 平静下来了
+    Soothed,
+    /// 不够信任主人或者被打扰太多，扭头不理
+    Rebuffed,
+}
+
+/// 跨天时昨天过得怎么样
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Yesterday {
+    /// 一整天没人理
+    Neglected,
+    /// 有一些互动
+    Ordinary,
+    /// 被好好陪伴了
+    Attentive,
+}
+
+impl Default for SophieState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SophieState {
     pub fn new() -> Self {
         let now = unix_now();
         Self {
+            schema_version: migrate::STATE_SCHEMA_VERSION,
             physiological: physiological::PhysiologicalState::new(),
             emotion: emotion::Emotion::Calm,
+            emotion_intensity: emotion::BASELINE_INTENSITY,
             relationship: relationship::RelationshipState::new(),
             is_sleeping: false,
+            collapsed: false,
             last_interaction_ts: now,
             recent_interaction_count: 0,
             interaction_count_reset_ts: now,
+            position: position::PositionState::new(),
+            stats: stats::InteractionStats::new(),
+            last_dream_pleasant: None,
+            user_activity_ts: None,
+            user_active: false,
+            petting: petting::PettingSession::new(),
+            chase: chase::ChaseSession::new(),
+            environment: environment::Environment::new(),
+            personality: personality::PersonalityTraits::from_seed(crate::rng::rand_u64()),
+            woke_at_ts: None,
+            toys: toys::ToyMemory::new(),
+            last_local_day: Some(local_day(now)),
+            preferences: preferences::Preferences::new(),
+            schedule: schedule::FeedingSchedule::new(),
+            tricks: tricks::TrickTraining::new(),
         }
     }
 
-    /// 每分钟调用一次，更新所有状态
-    pub fn tick(&mut self) {
+    /// 每分钟调用一次，更新所有状态；`hour` 是本地小时（0-23）
+    ///
+    /// 跨过本地零点时返回昨天过得怎么样（每天只返回一次）
+    pub fn tick(&mut self, hour: u32, tuning: &TuningConfig) -> Option<Yesterday> {
         let now = unix_now();
-        let minutes_since_interaction = ((now - self.last_interaction_ts) / 60) as u32;
+        let minutes_since_interaction = (now.saturating_sub(self.last_interaction_ts) / 60) as u32;
 
         // 每 10 分钟重置短期互动计数
-        if now - self.interaction_count_reset_ts > 600 {
+        if now.saturating_sub(self.interaction_count_reset_ts) > 600 {
             self.recent_interaction_count = 0;
             self.interaction_count_reset_ts = now;
         }
 
         // 1. 更新生理状态
-        self.physiological.tick(self.is_sleeping);
+        self.physiological.tick(self.is_sleeping, hour, &self.personality);
 
-        // 2. 自动入睡/醒来
+        // 2. 自动入睡/醒来；体力耗尽就不管别的，直接累倒，恢复够了才能醒
+        if !self.is_sleeping && self.physiological.energy <= 0.0 {
+            self.is_sleeping = true;
+            self.collapsed = true;
+        }
+        if self.collapsed && self.physiological.energy >= tuning.collapse_recover_energy {
+            self.collapsed = false;
+        }
         if !self.is_sleeping && self.physiological.sleepiness > 80.0 {
             self.is_sleeping = true;
         }
-        if self.is_sleeping && self.physiological.sleepiness < 5.0 {
-            self.is_sleeping = false;
+        if self.is_sleeping
+            && !self.collapsed
+            && self.physiological.sleepiness < 5.0
+            && self.physiological.is_rested()
+        {
+            self.wake();
+            self.apply_dream_on_wake();
         }
 
         // 3. 情绪转移
         let has_interaction = minutes_since_interaction < 2;
-        self.emotion = self.emotion.transition(
+        let next = self.emotion.transition(
             has_interaction,
             minutes_since_interaction,
             self.physiological.energy,
             self.relationship.intimacy,
+            self.emotion_intensity,
+            self.presence(),
+            self.personality.neglect_patience(),
+            &tuning.transition,
         );
+        // 换了情绪从基线强度开始，否则逐渐回归基线
+        if next != self.emotion {
+            self.emotion = next;
+            self.emotion_intensity = emotion::BASELINE_INTENSITY;
+        } else {
+            self.emotion_intensity = emotion::decay_intensity(self.emotion_intensity);
+        }
 
         // 4. 关系衰减（长期忽视）
-        if minutes_since_interaction > 180 {
-            self.relationship.on_neglect();
+        self.relationship.on_neglect(&self.personality, minutes_since_interaction, tuning);
+
+        // 5. 饭点过了还没人喂：连续错过几顿就不那么信任主人了
+        if self.schedule.check_missed(hour, hour_start(now, crate::config::utc_offset_secs())) {
+            self.relationship.on_missed_meal();
+        }
+
+        let yesterday = self.roll_day(local_day(now));
+        self.sanitize();
+        yesterday
+    }
+
+    /// 后台循环被挂起了 `paused_secs` 秒（电脑休眠/锁屏）：最多按 `MAX_PAUSE_AGING_SECS`
+    /// 计入冷落时间，多出的部分把各个时间戳往后挪，免得醒来就是一只被"遗弃"了一晚上的猫。
+    /// 返回被忽略的秒数
+    pub fn absorb_pause(&mut self, paused_secs: u64) -> u64 {
+        let skipped = paused_secs.saturating_sub(MAX_PAUSE_AGING_SECS);
+        if skipped == 0 {
+            return 0;
+        }
+        let now = unix_now();
+        let shift = |ts: u64| ts.saturating_add(skipped).min(now);
+        self.last_interaction_ts = shift(self.last_interaction_ts);
+        self.interaction_count_reset_ts = shift(self.interaction_count_reset_ts);
+        self.user_activity_ts = self.user_activity_ts.map(shift);
+        self.woke_at_ts = self.woke_at_ts.map(shift);
+        skipped
+    }
+
+    /// 检查是否到了新的一天：清零今天的计数，按昨天的陪伴调整心情
+    pub fn roll_day(&mut self, today: i64) -> Option<Yesterday> {
+        let last = self.last_local_day.replace(today);
+        if last.map_or(true, |day| day >= today) {
+            return None;
+        }
+        let interactions = self.stats.start_new_day();
+        let yesterday = if interactions == 0 {
+            Yesterday::Neglected
+        } else if interactions >= ATTENTIVE_DAY_INTERACTIONS {
+            Yesterday::Attentive
+        } else {
+            Yesterday::Ordinary
+        };
+        if !self.is_sleeping {
+            match yesterday {
+                Yesterday::Attentive => {
+                    self.set_emotion(emotion::Emotion::Happy);
+                    self.intensify(0.2);
+                }
+                Yesterday::Neglected if self.emotion == emotion::Emotion::Calm => {
+                    self.set_emotion(emotion::Emotion::Bored);
+                }
+                _ => {}
+            }
+        }
+        Some(yesterday)
+    }
+
+    /// 把所有数值拉回合法范围：NaN/无穷用默认值代替（读档后和每次 tick 后调用）
+    pub fn sanitize(&mut self) {
+        self.physiological.sanitize();
+        self.relationship.sanitize();
+        self.position.sanitize();
+        self.emotion_intensity = clamp_stat(self.emotion_intensity, 0.0, 1.0, emotion::BASELINE_INTENSITY);
+        self.clamp_future_timestamps(unix_now());
+    }
+
+    /// 比现在还晚的时间戳（系统时间被改过、换电脑同步存档时时钟不一致）一律拉回现在
+    fn clamp_future_timestamps(&mut self, now: u64) {
+        let clamp = |ts: u64| ts.min(now);
+        self.last_interaction_ts = clamp(self.last_interaction_ts);
+        self.interaction_count_reset_ts = clamp(self.interaction_count_reset_ts);
+        self.user_activity_ts = self.user_activity_ts.map(clamp);
+        self.woke_at_ts = self.woke_at_ts.map(clamp);
+        self.stats.born_ts = clamp(self.stats.born_ts);
+        self.schedule.last_feed_ts = self.schedule.last_feed_ts.map(clamp);
+        self.schedule.last_checked_meal_ts = self.schedule.last_checked_meal_ts.map(clamp);
+        for toy in self.toys.seen.iter_mut() {
+            toy.last_seen_ts = clamp(toy.last_seen_ts);
         }
     }
 
@@ -82,24 +342,887 @@ impl SophieState {
             // 短时间内打扰太多次会变烦躁
             if self.recent_interaction_count > 3 {
                 self.emotion = emotion::Emotion::Irritated;
+                self.intensify(0.3);
             }
-            // 但还是可能醒来
+            // 但还是可能醒来；欠着觉被吵醒没好脸色
             if self.recent_interaction_count > 1 {
-                self.is_sleeping = false;
+                self.wake();
+                if self.physiological.is_sleep_deprived() && self.emotion != emotion::Emotion::Irritated {
+                    self.set_emotion(emotion::Emotion::Irritated);
+                }
             }
         }
     }
 
+    /// 前端上报主人的键鼠活动（不算和她互动）
+    pub fn report_user_activity(&mut self, active: bool) {
+        self.user_activity_ts = Some(unix_now());
+        self.user_active = active;
+    }
+
+    /// 主人在不在电脑前：活跃上报超过 5 分钟没更新就算离开
+    pub fn presence(&self) -> emotion::Presence {
+        match self.user_activity_ts {
+            None => emotion::Presence::Unknown,
+            Some(ts) if self.user_active && unix_now().saturating_sub(ts) < USER_AWAY_SECS => {
+                emotion::Presence::Present
+            }
+            Some(_) => emotion::Presence::Away,
+        }
+    }
+
+    /// 被摸了一下：连续抚摸时亲密度涨得更快，摸过头会烦躁
+    pub fn pet(&mut self) -> petting::PetOutcome {
+        let outcome = self.petting.stroke(unix_now());
+        match outcome {
+            petting::PetOutcome::Enjoying(multiplier) => {
+                self.relationship.on_positive_interaction(&self.personality);
+                self.relationship.add_intimacy(0.8 * (multiplier - 1.0));
+                self.intensify(0.1);
+            }
+            petting::PetOutcome::Overstimulated => {
+                self.emotion = emotion::Emotion::Irritated;
+                self.emotion_intensity = emotion::BASELINE_INTENSITY;
+                self.intensify(0.3);
+            }
+        }
+        outcome
+    }
+
+    /// 当前这轮抚摸摸了几下（没在摸为 0）
+    pub fn petting_strokes(&self) -> u32 {
+        self.petting.current_strokes(unix_now())
+    }
+
+    /// 主人用光标逗她（互动记录由调用方负责），返回她有没有去追
+    ///
+    /// 想玩或好奇、又有精神时才追，每扑一下都消耗能量；累了、烦躁、睡着都不理
+    pub fn chase_cursor(&mut self, x: f32, y: f32) -> bool {
+        let interested = matches!(self.emotion, emotion::Emotion::Playful | emotion::Emotion::Curious);
+        if self.is_sleeping || self.is_groggy() || !interested || self.physiological.energy <= CHASE_MIN_ENERGY {
+            self.chase.stop();
+            return false;
+        }
+        let pounces = self.chase.pounce(unix_now(), x, y);
+        self.physiological.energy = (self.physiological.energy - CHASE_ENERGY_COST).max(0.0);
+        if pounces == 1 {
+            self.stats.record_play();
+        }
+        self.relationship.add_intimacy(0.3);
+        if self.emotion != emotion::Emotion::Playful {
+            self.emotion = emotion::Emotion::Playful;
+            self.emotion_intensity = emotion::BASELINE_INTENSITY;
+        }
+        self.intensify(0.1);
+        true
+    }
+
+    /// 正在追的光标位置（没在追为 None）
+    pub fn chase_target(&self) -> Option<(f32, f32)> {
+        self.chase.target(unix_now())
+    }
+
+    /// 吃东西：按食物种类缓解饥饿、提升情绪和亲密度（互动记录由调用方负责）
+    pub fn feed(&mut self, food: food::FoodType) {
+        self.physiological.feed(food.hunger_relief());
+        self.relationship.add_intimacy(food.intimacy_bonus());
+        if food.makes_happy() && self.emotion != emotion::Emotion::Happy {
+            self.emotion = emotion::Emotion::Happy;
+            self.emotion_intensity = emotion::BASELINE_INTENSITY;
+        }
+        self.intensify(food.delight());
+        self.stats.record_feed();
+        let now = unix_now();
+        self.schedule.record_feed(now, local_hour(now));
+    }
+
+    /// 主人安抚她（互动记录由调用方负责）：低落/烦躁/黏人时每次把情绪强度降一截，
+    /// 降到底就平静下来并增加信任；信任不够或短时间内被打扰太多会被拒绝
+    pub fn comfort(&mut self) -> ComfortOutcome {
+        if !matches!(
+            self.emotion,
+            emotion::Emotion::Down | emotion::Emotion::Irritated | emotion::Emotion::Needy
+        ) {
+            return ComfortOutcome::NotNeeded;
+        }
+        let overstimulated = self.recent_interaction_count > COMFORT_MAX_RECENT || self.petting_strokes() > 0;
+        if !self.relationship.will_approach() || overstimulated {
+            return ComfortOutcome::Rebuffed;
+        }
+        self.emotion_intensity = (self.emotion_intensity - COMFORT_STEP).max(0.0);
+        if self.emotion_intensity > COMFORT_CALM_INTENSITY {
+            return ComfortOutcome::Easing;
+        }
+        self.set_emotion(emotion::Emotion::Calm);
+        self.relationship.on_comforted(&self.personality);
+        ComfortOutcome::Soothed
+    }
+
+    /// 主人最近说话的平均语气（-2 到 2）感染她：越亲密越容易被带着走
+    ///
+    /// 持续低落 → 低落安静地陪着；持续开心 → 打起精神。返回情绪是否被改变
+    pub fn catch_owner_mood(&mut self, sentiment: f32) -> bool {
+        let intimacy = self.relationship.intimacy;
+        if self.is_sleeping || !sentiment.is_finite() || intimacy < CONTAGION_MIN_INTIMACY {
+            return false;
+        }
+        let pull = sentiment * intimacy / 100.0;
+        let next = if pull <= -CONTAGION_THRESHOLD {
+            match self.emotion {
+                emotion::Emotion::Happy | emotion::Emotion::Calm | emotion::Emotion::Bored => Some(emotion::Emotion::Down),
+                _ => None,
+            }
+        } else if pull >= CONTAGION_THRESHOLD {
+            match self.emotion {
+                emotion::Emotion::Down | emotion::Emotion::Bored => Some(emotion::Emotion::Calm),
+                emotion::Emotion::Calm => Some(emotion::Emotion::Happy),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        match next {
+            Some(next) => {
+                self.set_emotion(next);
+                self.intensify(pull.abs() * 0.2);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 主人拿出一个玩具（互动记录由调用方负责），返回新鲜感 0-1
+    ///
+    /// 很新鲜又有精神 → 想玩；还算新鲜 → 好奇；看腻了就不理。喜欢的玩具不容易腻
+    pub fn show_toy(&mut self, kind: &str) -> f32 {
+        let kind = toys::normalize_kind(kind);
+        let novelty = self.toys.present(&kind, unix_now());
+        let novelty = (novelty + FAVORITE_TOY_BONUS * self.preferences.toy_affinity(&kind)).min(1.0);
+        self.stats.record_play();
+        let next = if novelty >= 0.6 && self.physiological.energy > 60.0 {
+            Some(emotion::Emotion::Playful)
+        } else if novelty >= 0.3 {
+            Some(emotion::Emotion::Curious)
+        } else {
+            None
+        };
+        if let Some(next) = next {
+            if next != self.emotion {
+                self.emotion = next;
+                self.emotion_intensity = emotion::BASELINE_INTENSITY;
+            }
+            self.intensify(0.3 * novelty);
+        }
+        self.preferences.record_toy(&kind, if next.is_some() { novelty } else { 0.0 });
+        novelty
+    }
+
+    /// 主人哄她睡觉：睡意至少提到 30，保证能睡上一会儿
+    pub fn put_to_sleep(&mut self) {
+        self.is_sleeping = true;
+        self.physiological.sleepiness = self.physiological.sleepiness.max(30.0);
+    }
+
+    /// 主人下口令：睡着、迷糊、生气、不信任或听不懂时当没听见，
+    /// 否则按训练进度看心情（`r` 是 0-1 随机数）。照做了返回 true，训练进度和亲密度都涨一点
+    pub fn give_command(&mut self, trick: tricks::Trick, r: f32) -> bool {
+        if self.is_sleeping || self.is_groggy() || self.emotion == emotion::Emotion::Irritated {
+            return false;
+        }
+        if !tricks::will_listen(self.relationship.trust, self.relationship.understanding) {
+            return false;
+        }
+        if r >= self.tricks.success_chance(trick) {
+            return false;
+        }
+        self.tricks.record_success(trick);
+        self.relationship.add_intimacy(TRICK_INTIMACY);
+        true
+    }
+
+    /// 主人把她叫醒（算一次互动）：很困的时候被叫醒会烦躁
+    ///
+    /// 返回是否因此变得烦躁
+    pub fn wake_up(&mut self) -> bool {
+        // 累倒了叫不醒
+        if self.collapsed {
+            self.record_interaction();
+            return false;
+        }
+        let was_sleeping = self.is_sleeping;
+        if was_sleeping {
+            self.wake();
+        }
+        self.record_interaction();
+
+        let irritated = was_sleeping
+            && (self.physiological.sleepiness > 60.0 || self.physiological.is_sleep_deprived());
+        if irritated {
+            self.emotion = emotion::Emotion::Irritated;
+            self.intensify(0.3);
+            self.last_dream_pleasant = None;
+        } else if was_sleeping {
+            self.apply_dream_on_wake();
+        }
+        irritated
+    }
+
+    /// 从睡眠中醒来，开始一段迷糊期；没睡够的部分记成睡眠债（累倒了醒不来）
+    fn wake(&mut self) {
+        if self.collapsed {
+            return;
+        }
+        self.is_sleeping = false;
+        self.physiological.on_wake();
+        self.woke_at_ts = Some(unix_now());
+    }
+
+    /// 是否刚醒、还迷迷糊糊（只会坐着或发呆，反应慢）
+    pub fn is_groggy(&self) -> bool {
+        !self.is_sleeping
+            && self
+                .woke_at_ts
+                .is_some_and(|ts| unix_now().saturating_sub(ts) < GROGGY_SECS)
+    }
+
+    /// 记住刚做的梦
+    pub fn remember_dream(&mut self, pleasant: bool) {
+        self.last_dream_pleasant = Some(pleasant);
+    }
+
+    /// 醒来时根据最后一个梦调整情绪：好梦 → 开心，噩梦 → 平静
+    fn apply_dream_on_wake(&mut self) {
+        match self.last_dream_pleasant.take() {
+            Some(true) => self.emotion = emotion::Emotion::Happy,
+            Some(false) => self.emotion = emotion::Emotion::Calm,
+            None => {}
+        }
+    }
+
+    /// 直接设定情绪（调试 / "心情礼物"），强度回到基线
+    pub fn set_emotion(&mut self, emotion: emotion::Emotion) {
+        self.emotion = emotion;
+        self.emotion_intensity = emotion::BASELINE_INTENSITY;
+    }
+
+    /// 免打扰时安静下来：想玩、好奇、无聊、黏人都直接平静；返回情绪是否变了
+    pub fn quiet_down(&mut self) -> bool {
+        use emotion::Emotion;
+        if matches!(self.emotion, Emotion::Playful | Emotion::Curious | Emotion::Bored | Emotion::Needy) {
+            self.set_emotion(Emotion::Calm);
+            return true;
+        }
+        false
+    }
+
+    /// 按当前情绪和强度调整记忆权重：平静、基线强度时不变，最高 1.0
+    pub fn imprint_weight(&self, base: f32) -> f32 {
+        let intensity = 0.5 + self.emotion_intensity.clamp(0.0, 1.0);
+        (base * self.emotion.imprint_factor() * intensity).clamp(0.0, 1.0)
+    }
+
+    /// 强烈事件让当前情绪更强烈
+    pub fn intensify(&mut self, amount: f32) {
+        self.emotion_intensity = (self.emotion_intensity + amount).clamp(0.0, 1.0);
+    }
+
+    /// 正在气头上：点她、喂她都不领情
+    pub fn is_snubbing(&self) -> bool {
+        !self.is_sleeping
+            && self.emotion == emotion::Emotion::Irritated
+            && self.emotion_intensity > SNUB_INTENSITY
+    }
+
+    /// 是否在缠着主人要饭：越亲密越早开始（亲密度 100 时饥饿 > 70 就要），到了饭点更早
+    pub fn is_begging(&self) -> bool {
+        let intimacy = self.relationship.intimacy;
+        if self.is_sleeping || intimacy <= BEG_INTIMACY {
+            return false;
+        }
+        let mut threshold = 85.0 - (intimacy - BEG_INTIMACY) / (100.0 - BEG_INTIMACY) * 15.0;
+        if self.expects_meal() {
+            threshold -= MEAL_TIME_BEG_DISCOUNT;
+        }
+        self.physiological.hunger > threshold
+    }
+
+    /// 到了平时的饭点，这顿还没吃：在等开饭
+    pub fn expects_meal(&self) -> bool {
+        let now = unix_now();
+        let hour_start = hour_start(now, crate::config::utc_offset_secs());
+        !self.is_sleeping && self.schedule.pending_meal(local_hour(now), hour_start).is_some()
+    }
+
     /// 距离上次互动的分钟数
     pub fn minutes_since_interaction(&self) -> u32 {
         let now = unix_now();
-        ((now - self.last_interaction_ts) / 60) as u32
+        (now.saturating_sub(self.last_interaction_ts) / 60) as u32
+    }
+}
+
+/// 把数值限制在 [min, max]；不是有限数时用 default
+fn clamp_stat(value: f32, min: f32, max: f32, default: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(min, max)
+    } else {
+        default
     }
 }
 
+/// 本地日期编号（天）：按配置的时区偏移计算
+pub fn local_day(ts: u64) -> i64 {
+    (ts as i64 + crate::config::utc_offset_secs()).div_euclid(86400)
+}
+
+/// 本地钟点（0-23）
+pub fn local_hour(ts: u64) -> u32 {
+    (ts as i64 + crate::config::utc_offset_secs()).rem_euclid(86400) as u32 / 3600
+}
+
+/// 当前本地钟点开始的时间戳；偏移不一定是整小时（UTC+5:30 的整点在 UTC 的半点）
+fn hour_start(ts: u64, utc_offset_secs: i64) -> u64 {
+    ts.saturating_sub((ts as i64 + utc_offset_secs).rem_euclid(3600) as u64)
+}
+
+fn default_emotion_intensity() -> f32 {
+    emotion::BASELINE_INTENSITY
+}
+
 fn unix_now() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+    crate::clock::now_unix()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, MockClock};
+    use crate::rng;
+    use std::rc::Rc;
+
+    /// 本地时间 2023-11-15 12:00
+    const NOON: u64 = 1_700_020_800;
+
+    /// 在手动拨的时钟下跑 `f`，猫在 `start` 时出生
+    fn with_cat(start: u64, f: impl FnOnce(&MockClock, &mut SophieState)) {
+        let clock = Rc::new(MockClock::new(start));
+        clock::with_clock(clock.clone(), || {
+            rng::with_seed(1, || {
+                let mut sophie = SophieState::new();
+                f(&clock, &mut sophie);
+            })
+        });
+    }
+
+    #[test]
+    fn intensify_rises_and_stays_in_range() {
+        let mut sophie = SophieState::new();
+        sophie.intensify(0.3);
+        assert!((sophie.emotion_intensity - 0.8).abs() < 1e-6);
+        sophie.intensify(0.5);
+        assert_eq!(sophie.emotion_intensity, 1.0);
+        sophie.intensify(-3.0);
+        assert_eq!(sophie.emotion_intensity, 0.0);
+    }
+
+    #[test]
+    fn tick_decays_intensity_when_mood_holds() {
+        let mut sophie = SophieState::new();
+        sophie.last_interaction_ts -= 30 * 60;
+        sophie.emotion_intensity = 1.0;
+        sophie.tick(12, &TuningConfig::new());
+        assert_eq!(sophie.emotion, emotion::Emotion::Calm);
+        assert!(sophie.emotion_intensity < 1.0 && sophie.emotion_intensity > emotion::BASELINE_INTENSITY);
+    }
+
+    #[test]
+    fn new_emotion_starts_at_baseline_intensity() {
+        // 刚被摸过：平静 → 开心，强度从基线重新开始
+        let mut sophie = SophieState::new();
+        sophie.emotion_intensity = 0.1;
+        sophie.tick(12, &TuningConfig::new());
+        assert_eq!(sophie.emotion, emotion::Emotion::Happy);
+        assert_eq!(sophie.emotion_intensity, emotion::BASELINE_INTENSITY);
+    }
+
+    #[test]
+    fn forced_wake_while_sleepy_irritates() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.sleepiness = 90.0;
+        sophie.put_to_sleep();
+        assert!(sophie.is_sleeping);
+
+        assert!(sophie.wake_up());
+        assert!(!sophie.is_sleeping);
+        assert_eq!(sophie.emotion, emotion::Emotion::Irritated);
+        assert!(sophie.emotion_intensity > emotion::BASELINE_INTENSITY);
+    }
+
+    #[test]
+    fn gentle_wake_after_a_nap_is_fine() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.sleepiness = 0.0;
+        sophie.put_to_sleep();
+        // 哄睡时至少有点困，能睡一会儿
+        assert_eq!(sophie.physiological.sleepiness, 30.0);
+
+        sophie.last_interaction_ts -= 3600;
+        assert!(!sophie.wake_up());
+        assert_eq!(sophie.emotion, emotion::Emotion::Calm);
+        assert_eq!(sophie.minutes_since_interaction(), 0);
+
+        // 醒着的时候"叫醒"不会惹她
+        sophie.physiological.sleepiness = 90.0;
+        assert!(!sophie.wake_up());
+    }
+
+    #[test]
+    fn last_dream_sets_the_mood_on_waking() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.sleepiness = 0.0;
+        sophie.put_to_sleep();
+        sophie.remember_dream(false);
+        sophie.remember_dream(true);
+        assert!(!sophie.wake_up());
+        assert_eq!(sophie.emotion, emotion::Emotion::Happy);
+        assert_eq!(sophie.last_dream_pleasant, None);
+
+        // 被吵醒的烦躁盖过梦
+        sophie.physiological.sleepiness = 90.0;
+        sophie.put_to_sleep();
+        sophie.remember_dream(true);
+        assert!(sophie.wake_up());
+        assert_eq!(sophie.emotion, emotion::Emotion::Irritated);
+        assert_eq!(sophie.last_dream_pleasant, None);
+    }
+
+    #[test]
+    fn sanitize_clamps_out_of_range_stats() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 500.0;
+        sophie.physiological.energy = -20.0;
+        sophie.relationship.trust = 180.0;
+        sophie.position.x = 3.0;
+        sophie.position.favorite_x = Some(-1.0);
+        sophie.emotion_intensity = 2.0;
+        sophie.sanitize();
+        assert_eq!(sophie.physiological.hunger, 100.0);
+        assert_eq!(sophie.physiological.energy, 0.0);
+        assert_eq!(sophie.relationship.trust, 100.0);
+        assert_eq!(sophie.position.x, 1.0);
+        assert_eq!(sophie.position.favorite_x, Some(0.0));
+        assert_eq!(sophie.emotion_intensity, 1.0);
+    }
+
+    #[test]
+    fn sanitize_replaces_nan_and_infinity_with_defaults() {
+        let defaults = SophieState::new();
+        let mut sophie = SophieState::new();
+        sophie.physiological.energy = f32::NAN;
+        sophie.physiological.sleepiness = f32::INFINITY;
+        sophie.relationship.intimacy = f32::NEG_INFINITY;
+        sophie.position.favorite_x = Some(f32::NAN);
+        sophie.position.favorite_samples = 5;
+        sophie.emotion_intensity = f32::NAN;
+        sophie.sanitize();
+        assert_eq!(sophie.physiological.energy, defaults.physiological.energy);
+        assert_eq!(sophie.physiological.sleepiness, defaults.physiological.sleepiness);
+        assert_eq!(sophie.relationship.intimacy, defaults.relationship.intimacy);
+        assert_eq!(sophie.position.favorite_x, None);
+        assert_eq!(sophie.position.favorite_samples, 0);
+        assert_eq!(sophie.emotion_intensity, emotion::BASELINE_INTENSITY);
+    }
+
+    #[test]
+    fn tick_leaves_stats_in_range() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = f32::NAN;
+        sophie.tick(12, &TuningConfig::new());
+        assert!(sophie.physiological.hunger.is_finite());
+    }
+
+    #[test]
+    fn presence_follows_reported_activity() {
+        let mut sophie = SophieState::new();
+        assert_eq!(sophie.presence(), emotion::Presence::Unknown);
+        sophie.report_user_activity(true);
+        assert_eq!(sophie.presence(), emotion::Presence::Present);
+        sophie.report_user_activity(false);
+        assert_eq!(sophie.presence(), emotion::Presence::Away);
+
+        // 活跃上报太久没更新也算离开
+        sophie.report_user_activity(true);
+        sophie.user_activity_ts = Some(unix_now() - USER_AWAY_SECS);
+        assert_eq!(sophie.presence(), emotion::Presence::Away);
+    }
+
+    #[test]
+    fn petting_too_long_irritates_her() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Happy;
+        let intimacy = sophie.relationship.intimacy;
+        let mut outcome = sophie.pet();
+        for _ in 1..20 {
+            if outcome == petting::PetOutcome::Overstimulated {
+                break;
+            }
+            outcome = sophie.pet();
+        }
+        assert_eq!(outcome, petting::PetOutcome::Overstimulated);
+        assert_eq!(sophie.emotion, emotion::Emotion::Irritated);
+        assert!(sophie.relationship.intimacy > intimacy);
+    }
+
+    #[test]
+    fn strong_emotions_imprint_memories_harder() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Calm;
+        sophie.emotion_intensity = emotion::BASELINE_INTENSITY;
+        let calm = sophie.imprint_weight(0.5);
+        assert!((calm - 0.5).abs() < 1e-6);
+
+        sophie.emotion = emotion::Emotion::Irritated;
+        let irritated = sophie.imprint_weight(0.5);
+        assert!(irritated > calm);
+        sophie.intensify(0.5);
+        assert!(sophie.imprint_weight(0.5) > irritated);
+        // 再强也不超过 1.0
+        assert!(sophie.imprint_weight(0.9) <= 1.0);
+    }
+
+    #[test]
+    fn a_new_toy_sparks_curiosity_that_wears_off() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Calm;
+        sophie.physiological.energy = 80.0;
+        assert_eq!(sophie.show_toy("毛线球"), 1.0);
+        assert_eq!(sophie.emotion, emotion::Emotion::Playful);
+
+        sophie.emotion = emotion::Emotion::Calm;
+        sophie.show_toy("毛线球");
+        assert_eq!(sophie.emotion, emotion::Emotion::Curious);
+        sophie.show_toy("毛线球");
+        // 第四次看到同一个玩具已经不新鲜了
+        sophie.emotion = emotion::Emotion::Calm;
+        assert!(sophie.show_toy("毛线球") < 0.3);
+        assert_eq!(sophie.emotion, emotion::Emotion::Calm);
+    }
+
+    #[test]
+    fn closer_cats_start_begging_sooner() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.hunger = 75.0;
+        sophie.relationship.intimacy = 40.0;
+        assert!(!sophie.is_begging());
+        sophie.relationship.intimacy = 100.0;
+        assert!(sophie.is_begging());
+        sophie.relationship.intimacy = 60.0;
+        assert!(!sophie.is_begging());
+        sophie.physiological.hunger = 85.0;
+        assert!(sophie.is_begging());
+
+        sophie.is_sleeping = true;
+        assert!(!sophie.is_begging());
+    }
+
+    #[test]
+    fn crossing_midnight_resets_daily_counts_once() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Calm;
+        sophie.last_local_day = Some(100);
+        for _ in 0..ATTENTIVE_DAY_INTERACTIONS {
+            sophie.stats.record_click();
+        }
+        assert_eq!(sophie.roll_day(100), None);
+
+        assert_eq!(sophie.roll_day(101), Some(Yesterday::Attentive));
+        assert_eq!(sophie.stats.interactions_today(), 0);
+        assert_eq!(sophie.emotion, emotion::Emotion::Happy);
+        // 同一天只报一次，时钟往回拨也不算新的一天
+        assert_eq!(sophie.roll_day(101), None);
+        assert_eq!(sophie.roll_day(99), None);
+
+        sophie.emotion = emotion::Emotion::Calm;
+        assert_eq!(sophie.roll_day(102), Some(Yesterday::Neglected));
+        assert_eq!(sophie.emotion, emotion::Emotion::Bored);
+    }
+
+    #[test]
+    fn local_day_follows_the_utc_offset() {
+        let offset = crate::config::utc_offset_secs();
+        let midnight = (86400 * 20_000 - offset) as u64;
+        assert_eq!(local_day(midnight - 1), 19_999);
+        assert_eq!(local_day(midnight), 20_000);
+    }
+
+    #[test]
+    fn trusted_comfort_calms_her_down_step_by_step() {
+        let mut sophie = SophieState::new();
+        sophie.relationship.trust = 50.0;
+        sophie.emotion = emotion::Emotion::Down;
+        sophie.emotion_intensity = 0.8;
+        let trust = sophie.relationship.trust;
+
+        assert_eq!(sophie.comfort(), ComfortOutcome::Easing);
+        assert_eq!(sophie.emotion, emotion::Emotion::Down);
+        assert_eq!(sophie.comfort(), ComfortOutcome::Soothed);
+        assert_eq!(sophie.emotion, emotion::Emotion::Calm);
+        assert!(sophie.relationship.trust > trust);
+        assert_eq!(sophie.comfort(), ComfortOutcome::NotNeeded);
+    }
+
+    #[test]
+    fn comfort_is_rebuffed_without_trust_or_when_overstimulated() {
+        let mut sophie = SophieState::new();
+        sophie.relationship.trust = 10.0;
+        sophie.emotion = emotion::Emotion::Irritated;
+        sophie.emotion_intensity = 0.8;
+        assert_eq!(sophie.comfort(), ComfortOutcome::Rebuffed);
+        assert_eq!(sophie.emotion_intensity, 0.8);
+
+        sophie.relationship.trust = 50.0;
+        sophie.recent_interaction_count = COMFORT_MAX_RECENT + 1;
+        assert_eq!(sophie.comfort(), ComfortOutcome::Rebuffed);
+        assert_eq!(sophie.emotion, emotion::Emotion::Irritated);
+    }
+
+    #[test]
+    fn owner_mood_spreads_only_to_close_awake_cats() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Calm;
+        sophie.relationship.intimacy = 20.0;
+        assert!(!sophie.catch_owner_mood(-2.0));
+
+        sophie.relationship.intimacy = 80.0;
+        // 偶尔一点点不开心不算
+        assert!(!sophie.catch_owner_mood(-0.3));
+        sophie.is_sleeping = true;
+        assert!(!sophie.catch_owner_mood(-2.0));
+        sophie.is_sleeping = false;
+
+        assert!(sophie.catch_owner_mood(-2.0));
+        assert_eq!(sophie.emotion, emotion::Emotion::Down);
+        assert!(sophie.catch_owner_mood(1.5));
+        assert_eq!(sophie.emotion, emotion::Emotion::Calm);
+        assert!(sophie.catch_owner_mood(1.5));
+        assert_eq!(sophie.emotion, emotion::Emotion::Happy);
+    }
+
+    #[test]
+    fn only_a_strongly_irritated_awake_cat_snubs() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Irritated;
+        sophie.emotion_intensity = 0.9;
+        assert!(sophie.is_snubbing());
+        sophie.is_sleeping = true;
+        assert!(!sophie.is_snubbing());
+        sophie.is_sleeping = false;
+
+        sophie.emotion_intensity = SNUB_INTENSITY;
+        assert!(!sophie.is_snubbing());
+        sophie.emotion = emotion::Emotion::Down;
+        sophie.emotion_intensity = 1.0;
+        assert!(!sophie.is_snubbing());
+    }
+
+    #[test]
+    fn waking_her_again_and_again_piles_up_sleep_debt() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Calm;
+        sophie.physiological.energy = 10.0;
+        sophie.physiological.sleepiness = 90.0;
+        sophie.put_to_sleep();
+        assert!(sophie.wake_up());
+        let debt_after_one = sophie.physiological.sleep_debt;
+        assert!(debt_after_one > 0.0);
+
+        for _ in 0..3 {
+            sophie.put_to_sleep();
+            sophie.physiological.sleepiness = 90.0;
+            sophie.wake_up();
+        }
+        assert!(sophie.physiological.sleep_debt > debt_after_one);
+        assert!(sophie.physiological.is_sleep_deprived());
+
+        // 欠着觉：睡意没了也不会自然醒，体力也回不满
+        sophie.put_to_sleep();
+        for _ in 0..40 {
+            sophie.tick(3, &TuningConfig::new());
+        }
+        assert!(sophie.physiological.sleepiness < 5.0);
+        assert!(sophie.is_sleeping);
+        let ceiling = sophie.physiological.energy_ceiling(&sophie.personality);
+        assert!(ceiling < 90.0);
+        assert!(sophie.physiological.energy <= ceiling);
+    }
+
+    #[test]
+    fn hour_start_handles_half_hour_offsets() {
+        let ts = 100 * 86400 + 10 * 3600 + 45 * 60;
+        assert_eq!(hour_start(ts, 8 * 3600), 100 * 86400 + 10 * 3600);
+        // UTC+5:30：本地整点落在 UTC 的半点
+        assert_eq!(hour_start(ts, 5 * 3600 + 1800), 100 * 86400 + 10 * 3600 + 1800);
+        assert_eq!(hour_start(ts - 30 * 60, 5 * 3600 + 1800), 100 * 86400 + 9 * 3600 + 1800);
+        // UTC-3:30
+        assert_eq!(hour_start(ts, -(3 * 3600 + 1800)), 100 * 86400 + 10 * 3600 + 1800);
+    }
+
+    #[test]
+    fn a_cat_fed_at_this_hour_every_day_waits_for_dinner() {
+        let now = unix_now();
+        let hour = local_hour(now);
+        let mut sophie = SophieState::new();
+        sophie.relationship.intimacy = 100.0;
+        sophie.physiological.hunger = 60.0;
+        assert!(!sophie.expects_meal());
+        assert!(!sophie.is_begging());
+
+        for days in 1..=3 {
+            sophie.schedule.record_feed(now - days * 86400 - 3600, hour);
+        }
+        assert!(sophie.expects_meal());
+        // 到饭点了，没那么饿也开始要饭
+        assert!(sophie.is_begging());
+
+        sophie.feed(food::FoodType::Meal);
+        assert!(!sophie.expects_meal());
+    }
+
+    #[test]
+    fn a_ten_hour_suspend_only_ages_her_half_an_hour() {
+        let ten_hours = 10 * 3600;
+        with_cat(NOON, |clock, sophie| {
+            // 挂起前刚互动过
+            sophie.record_interaction();
+            sophie.user_activity_ts = Some(NOON);
+            clock.advance(ten_hours);
+
+            assert_eq!(sophie.absorb_pause(ten_hours), ten_hours - MAX_PAUSE_AGING_SECS);
+            assert_eq!(sophie.minutes_since_interaction() as u64, MAX_PAUSE_AGING_SECS / 60);
+            assert_eq!(sophie.user_activity_ts, Some(sophie.last_interaction_ts));
+
+            // 短暂挂起原样算
+            let last = sophie.last_interaction_ts;
+            assert_eq!(sophie.absorb_pause(MAX_PAUSE_AGING_SECS), 0);
+            assert_eq!(sophie.last_interaction_ts, last);
+            // 时间戳不会被挪到未来
+            sophie.absorb_pause(100 * 3600);
+            assert_eq!(sophie.last_interaction_ts, clock::now_unix());
+        });
+    }
+
+    #[test]
+    fn only_a_playful_cat_with_energy_chases_the_cursor() {
+        let mut sophie = SophieState::new();
+        sophie.physiological.energy = 80.0;
+        sophie.emotion = emotion::Emotion::Irritated;
+        assert!(!sophie.chase_cursor(0.3, 0.9));
+        sophie.emotion = emotion::Emotion::Curious;
+        sophie.is_sleeping = true;
+        assert!(!sophie.chase_cursor(0.3, 0.9));
+        sophie.is_sleeping = false;
+
+        let intimacy = sophie.relationship.intimacy;
+        assert!(sophie.chase_cursor(0.3, 0.9));
+        assert_eq!(sophie.emotion, emotion::Emotion::Playful);
+        assert_eq!(sophie.chase_target(), Some((0.3, 0.9)));
+        assert!(sophie.relationship.intimacy > intimacy);
+        assert_eq!(sophie.stats.plays_today, 1);
+    }
+
+    #[test]
+    fn chasing_tires_her_out() {
+        let mut sophie = SophieState::new();
+        sophie.emotion = emotion::Emotion::Playful;
+        sophie.physiological.energy = CHASE_MIN_ENERGY + 3.0 * CHASE_ENERGY_COST;
+        let pounces = (0..10).take_while(|_| sophie.chase_cursor(0.5, 0.9)).count();
+        assert_eq!(pounces, 3);
+        assert_eq!(sophie.chase_target(), None);
+    }
+
+    #[test]
+    fn tick_reports_yesterday_once_after_midnight() {
+        assert_eq!(local_hour(NOON), 12);
+        let tuning = TuningConfig::new();
+        with_cat(NOON, |clock, sophie| {
+            assert_eq!(sophie.tick(12, &tuning), None);
+
+            clock.set(NOON + 12 * 3600 + 60);
+            assert_eq!(local_hour(clock::now_unix()), 0);
+            assert!(sophie.tick(0, &tuning).is_some());
+
+            clock.advance(60);
+            assert_eq!(sophie.tick(0, &tuning), None);
+        });
+    }
+
+    #[test]
+    fn long_neglect_wears_down_the_relationship() {
+        let tuning = TuningConfig::new();
+        with_cat(NOON, |clock, sophie| {
+            sophie.record_interaction();
+            let (trust, intimacy) = (sophie.relationship.trust, sophie.relationship.intimacy);
+
+            // 宽限期内关系不掉
+            clock.set(NOON + 60 * 60);
+            sophie.tick(13, &tuning);
+            assert_eq!(sophie.minutes_since_interaction(), 60);
+            assert_eq!(sophie.relationship.trust, trust);
+
+            clock.set(NOON + 5 * 60 * 60);
+            sophie.tick(17, &tuning);
+            assert_eq!(sophie.minutes_since_interaction(), 300);
+            assert!(sophie.relationship.trust < trust);
+            assert!(sophie.relationship.intimacy < intimacy);
+        });
+    }
+
+    #[test]
+    fn an_exhausted_cat_sleeps_until_her_energy_is_back() {
+        let tuning = TuningConfig::new();
+        with_cat(NOON, |clock, sophie| {
+            sophie.physiological.energy = 0.3;
+            sophie.physiological.hunger = 90.0;
+            sophie.tick(12, &tuning);
+            assert!(sophie.collapsed && sophie.is_sleeping);
+
+            // 叫不醒
+            assert!(!sophie.wake_up());
+            assert!(sophie.is_sleeping);
+
+            let mut ticks = 0;
+            while sophie.collapsed && ticks < 500 {
+                clock.advance(60);
+                sophie.tick(12, &tuning);
+                ticks += 1;
+            }
+            assert!(!sophie.collapsed);
+            assert!(sophie.physiological.energy >= tuning.collapse_recover_energy);
+        });
+    }
+
+    #[test]
+    fn clock_going_backwards_does_not_underflow() {
+        let tuning = TuningConfig::new();
+        with_cat(NOON, |clock, sophie| {
+            sophie.record_interaction();
+            clock.set(NOON - 3600);
+            sophie.tick(11, &tuning);
+            assert_eq!(sophie.minutes_since_interaction(), 0);
+            assert_eq!(sophie.last_interaction_ts, NOON - 3600);
+        });
+    }
+
+    #[test]
+    fn sanitize_pulls_future_timestamps_back_to_now() {
+        with_cat(NOON, |_, sophie| {
+            sophie.last_interaction_ts = NOON + 86_400;
+            sophie.woke_at_ts = Some(NOON + 60);
+            sophie.stats.born_ts = NOON - 60;
+            sophie.sanitize();
+            assert_eq!(sophie.last_interaction_ts, NOON);
+            assert_eq!(sophie.woke_at_ts, Some(NOON));
+            assert_eq!(sophie.stats.born_ts, NOON - 60);
+            assert_eq!(sophie.minutes_since_interaction(), 0);
+        });
+    }
 }
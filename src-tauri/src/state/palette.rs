@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::emotion::Emotion;
+
+/// 一种情绪的展示信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionMeta {
+    /// 和快照里的 `emotion` 一致
+    pub id: &'static str,
+    /// 建议颜色（CSS 颜色字符串）
+    pub color: String,
+    /// 动画活跃度 0-1
+    pub animation_energy: f32,
+}
+
+/// 调色板文件里对单个情绪的覆盖，没写的项用默认值
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaOverride {
+    color: Option<String>,
+    animation_energy: Option<f32>,
+}
+
+/// 内置调色板：每种情绪一项，顺序同 `Emotion::ALL`
+pub fn builtin() -> Vec<EmotionMeta> {
+    Emotion::ALL
+        .iter()
+        .map(|e| EmotionMeta {
+            id: e.id(),
+            color: e.color().to_string(),
+            animation_energy: e.animation_energy(),
+        })
+        .collect()
+}
+
+/// 读取 `{"happy": {"color": "#fc0", "animationEnergy": 0.8}, ...}` 覆盖内置值；
+/// 文件不存在或格式不对就用内置调色板
+pub fn load(path: &Path) -> Vec<EmotionMeta> {
+    let mut palette = builtin();
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return palette;
+    };
+    let overrides: HashMap<String, MetaOverride> = match serde_json::from_str(&text) {
+        Ok(o) => o,
+        Err(e) => {
+            log::warn!("Invalid emotion palette {:?}: {}, using built-in colors", path, e);
+            return palette;
+        }
+    };
+    for (id, o) in overrides {
+        let Some(meta) = palette.iter_mut().find(|m| m.id.eq_ignore_ascii_case(&id)) else {
+            log::warn!("Emotion palette has unknown emotion {:?}", id);
+            continue;
+        };
+        if let Some(color) = o.color.filter(|c| !c.trim().is_empty()) {
+            meta.color = color;
+        }
+        if let Some(energy) = o.animation_energy.filter(|e| e.is_finite()) {
+            meta.animation_energy = energy.clamp(0.0, 1.0);
+        }
+    }
+    log::info!("Loaded emotion palette {:?}", path);
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_emotion_has_distinct_metadata() {
+        let palette = builtin();
+        assert_eq!(palette.len(), Emotion::ALL.len());
+        for (meta, emotion) in palette.iter().zip(Emotion::ALL) {
+            assert_eq!(meta.id, emotion.id());
+            assert!(meta.color.starts_with('#'), "{}", meta.id);
+            assert!((0.0..=1.0).contains(&meta.animation_energy), "{}", meta.id);
+            assert_eq!(palette.iter().filter(|m| m.id == meta.id).count(), 1);
+        }
+    }
+
+    #[test]
+    fn palette_files_override_only_what_they_name() {
+        let dir = std::env::temp_dir().join(format!("sophie-palette-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("emotion_palette.json");
+        std::fs::write(&path, r#"{"Happy": {"color": "gold"}, "down": {"animationEnergy": 7}, "hungry": {}}"#).unwrap();
+
+        let palette = load(&path);
+        let get = |id: &str| palette.iter().find(|m| m.id == id).unwrap();
+        assert_eq!(get("happy").color, "gold");
+        assert_eq!(get("happy").animation_energy, Emotion::Happy.animation_energy());
+        assert_eq!(get("down").animation_energy, 1.0);
+        assert_eq!(get("calm").color, Emotion::Calm.color());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(load(&path)[0].color, builtin()[0].color);
+        assert_eq!(load(&dir.join("missing.json")).len(), Emotion::ALL.len());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+
+/// 性格特质，每项 0-1；由种子生成，决定她是只什么样的猫
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalityTraits {
+    /// 生成特质用的种子（同一个种子得到同一只猫）
+    pub seed: u64,
+    /// 怕生：越高信任涨得越慢，越爱躲着观察
+    pub shyness: f32,
+    /// 爱玩：越高越爱跑
+    pub playfulness: f32,
+    /// 独立：越高越不在意被冷落
+    pub independence: f32,
+    /// 黏人：由主人调节，越高被冷落时情绪和关系掉得越快
+    #[serde(default = "default_clinginess")]
+    pub clinginess: f32,
+    /// 代谢倍率 0.5-1.5：越高体力掉得越快、恢复得越快、饿得越快
+    #[serde(default = "default_metabolism")]
+    pub metabolism: f32,
+    /// 体力上限 50-100：懒猫的体力本来就少
+    #[serde(default = "default_max_energy")]
+    pub max_energy: f32,
+}
+
+/// 旧存档没有黏人程度，按中间值处理（行为与以前一致）
+fn default_clinginess() -> f32 {
+    0.5
+}
+
+/// 旧存档没有代谢和体力上限，按以前的固定值处理
+fn default_metabolism() -> f32 {
+    1.0
+}
+
+fn default_max_energy() -> f32 {
+    100.0
+}
+
+impl PersonalityTraits {
+    /// 由种子生成：三项特质都落在 0.1-0.9，避免太极端；代谢 0.6-1.4，体力上限 75-95
+    ///
+    /// 新特质只能接在后面取随机数，已有的猫用同一个种子仍得到同样的前三项
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut trait_value = || 0.1 + rng.next_f32() * 0.8;
+        Self {
+            seed,
+            shyness: trait_value(),
+            playfulness: trait_value(),
+            independence: trait_value(),
+            clinginess: default_clinginess(),
+            metabolism: 0.5 + trait_value(),
+            max_energy: 72.5 + trait_value() * 25.0,
+        }
+    }
+
+    /// 中性性格（旧存档没有性格时使用，行为与以前一致）
+    pub fn neutral() -> Self {
+        Self {
+            seed: 0,
+            shyness: 0.5,
+            playfulness: 0.5,
+            independence: 0.5,
+            clinginess: default_clinginess(),
+            metabolism: default_metabolism(),
+            max_energy: default_max_energy(),
+        }
+    }
+
+    /// 信任增长倍率：怕生的猫 0.5x，不怕生的 1.5x
+    pub fn trust_gain(&self) -> f32 {
+        1.5 - self.shyness
+    }
+
+    /// 被冷落时关系衰减的倍率：独立的猫衰减慢，黏人的猫衰减快
+    pub fn neglect_decay(&self) -> f32 {
+        (1.5 - self.independence) * (0.5 + self.clinginess)
+    }
+
+    /// 被冷落多久才有情绪反应的倍率：黏人的猫 0.5x，高冷的猫 1.5x
+    pub fn neglect_patience(&self) -> f32 {
+        1.5 - self.clinginess
+    }
+
+    /// 设置代谢倍率（0.5-1.5）和体力上限（50-100），无效值按默认值处理
+    pub fn set_energy_profile(&mut self, metabolism: f32, max_energy: f32) {
+        self.metabolism = metabolism;
+        self.max_energy = max_energy;
+        // 越界或无效的值按读取时的规则修正后再存
+        self.metabolism = self.metabolism_rate();
+        self.max_energy = self.energy_cap();
+    }
+
+    /// 设置黏人程度（0-1），无效值按中间值处理
+    pub fn set_clinginess(&mut self, value: f32) {
+        self.clinginess = if value.is_finite() { value.clamp(0.0, 1.0) } else { default_clinginess() };
+    }
+
+    /// 代谢倍率（存档里的坏值也拉回 0.5-1.5）
+    pub fn metabolism_rate(&self) -> f32 {
+        if self.metabolism.is_finite() { self.metabolism.clamp(0.5, 1.5) } else { default_metabolism() }
+    }
+
+    /// 体力上限（拉回 50-100）
+    pub fn energy_cap(&self) -> f32 {
+        if self.max_energy.is_finite() { self.max_energy.clamp(50.0, 100.0) } else { default_max_energy() }
+    }
+
+    /// 行为权重倍率（中性性格下都是 1）
+    pub fn run_bias(&self) -> f32 {
+        0.5 + self.playfulness
+    }
+
+    pub fn alert_bias(&self) -> f32 {
+        0.5 + self.shyness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TuningConfig;
+    use crate::state::relationship::RelationshipState;
+
+    #[test]
+    fn same_seed_same_cat() {
+        let a = PersonalityTraits::from_seed(42);
+        let b = PersonalityTraits::from_seed(42);
+        assert_eq!((a.shyness, a.playfulness, a.independence), (b.shyness, b.playfulness, b.independence));
+        for seed in 0..100 {
+            let traits = PersonalityTraits::from_seed(seed);
+            for value in [traits.shyness, traits.playfulness, traits.independence] {
+                assert!((0.1..=0.9).contains(&value), "seed {}: {}", seed, value);
+            }
+        }
+    }
+
+    #[test]
+    fn neutral_personality_changes_nothing() {
+        let neutral = PersonalityTraits::neutral();
+        assert_eq!(neutral.trust_gain(), 1.0);
+        assert_eq!(neutral.neglect_decay(), 1.0);
+        assert_eq!(neutral.run_bias(), 1.0);
+        assert_eq!(neutral.alert_bias(), 1.0);
+    }
+
+    #[test]
+    fn shy_cats_warm_up_slower_and_independent_ones_mind_neglect_less() {
+        let shy = PersonalityTraits { shyness: 0.9, independence: 0.9, ..PersonalityTraits::neutral() };
+        let bold = PersonalityTraits { shyness: 0.1, independence: 0.1, ..PersonalityTraits::neutral() };
+
+        let mut shy_rel = RelationshipState::new();
+        let mut bold_rel = RelationshipState::new();
+        shy_rel.on_positive_interaction(&shy);
+        bold_rel.on_positive_interaction(&bold);
+        assert!(shy_rel.trust < bold_rel.trust);
+
+        shy_rel.trust = 50.0;
+        bold_rel.trust = 50.0;
+        let tuning = TuningConfig::new();
+        shy_rel.on_neglect(&shy, 240, &tuning);
+        bold_rel.on_neglect(&bold, 240, &tuning);
+        assert!(shy_rel.trust > bold_rel.trust);
+    }
+
+    #[test]
+    fn clingy_cats_lose_more_to_the_same_neglect() {
+        let clingy = PersonalityTraits { clinginess: 1.0, ..PersonalityTraits::neutral() };
+        let aloof = PersonalityTraits { clinginess: 0.0, ..PersonalityTraits::neutral() };
+        let tuning = TuningConfig::new();
+        let mut clingy_rel = RelationshipState::new();
+        let mut aloof_rel = RelationshipState::new();
+        clingy_rel.trust = 50.0;
+        aloof_rel.trust = 50.0;
+        clingy_rel.on_neglect(&clingy, 240, &tuning);
+        aloof_rel.on_neglect(&aloof, 240, &tuning);
+        assert!(clingy_rel.trust < aloof_rel.trust);
+    }
+
+    #[test]
+    fn clinginess_is_clamped_and_defaults_for_old_saves() {
+        let mut traits = PersonalityTraits::neutral();
+        traits.set_clinginess(3.0);
+        assert_eq!(traits.clinginess, 1.0);
+        traits.set_clinginess(f32::NAN);
+        assert_eq!(traits.clinginess, 0.5);
+
+        let old: PersonalityTraits =
+            serde_json::from_str(r#"{"seed":7,"shyness":0.2,"playfulness":0.5,"independence":0.5}"#).unwrap();
+        assert_eq!(old.clinginess, 0.5);
+        assert_eq!(old.neglect_patience(), 1.0);
+    }
+
+    #[test]
+    fn energy_profiles_are_clamped_and_old_saves_keep_the_old_rates() {
+        let mut traits = PersonalityTraits::neutral();
+        traits.set_energy_profile(9.0, f32::NAN);
+        assert_eq!((traits.metabolism, traits.max_energy), (1.5, 100.0));
+        traits.set_energy_profile(0.8, 10.0);
+        assert_eq!((traits.metabolism, traits.max_energy), (0.8, 50.0));
+
+        let old: PersonalityTraits =
+            serde_json::from_str(r#"{"seed":7,"shyness":0.2,"playfulness":0.5,"independence":0.5}"#).unwrap();
+        assert_eq!((old.metabolism_rate(), old.energy_cap()), (1.0, 100.0));
+        for seed in 0..100 {
+            let traits = PersonalityTraits::from_seed(seed);
+            assert!((0.6..=1.4).contains(&traits.metabolism), "seed {}: {}", seed, traits.metabolism);
+            assert!((75.0..=95.0).contains(&traits.max_energy), "seed {}: {}", seed, traits.max_energy);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+/// 两次抚摸间隔超过这么久（秒），这轮抚摸就结束了
+const SESSION_GAP_SECS: u64 = 4;
+/// 前几下只是普通的摸
+const WARMUP_STROKES: u32 = 3;
+/// 摸到这么多下开始受不了
+const OVERSTIMULATED_STROKES: u32 = 12;
+/// 连续抚摸能达到的最大亲密度倍率
+const MAX_MULTIPLIER: f32 = 2.0;
+
+/// 一次抚摸的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PetOutcome {
+    /// 还在享受，附带亲密度倍率
+    Enjoying(f32),
+    /// 摸太多了，一爪子拍开
+    Overstimulated,
+}
+
+/// 连续抚摸：先越摸越舒服，太多了就烦（不持久化）
+#[derive(Debug, Clone)]
+pub struct PettingSession {
+    pub strokes: u32,
+    pub last_stroke_ts: u64,
+}
+
+impl PettingSession {
+    pub fn new() -> Self {
+        Self {
+            strokes: 0,
+            last_stroke_ts: 0,
+        }
+    }
+
+    /// 记录一下抚摸；停顿太久就从头开始一轮
+    pub fn stroke(&mut self, now: u64) -> PetOutcome {
+        if !self.is_active(now) {
+            self.strokes = 0;
+        }
+        self.strokes += 1;
+        self.last_stroke_ts = now;
+
+        if self.strokes >= OVERSTIMULATED_STROKES {
+            self.strokes = 0;
+            return PetOutcome::Overstimulated;
+        }
+        let building = self.strokes.saturating_sub(WARMUP_STROKES) as f32;
+        let span = (OVERSTIMULATED_STROKES - WARMUP_STROKES) as f32;
+        PetOutcome::Enjoying(1.0 + (MAX_MULTIPLIER - 1.0) * (building / span).min(1.0))
+    }
+
+    /// 这轮抚摸还在进行吗
+    pub fn is_active(&self, now: u64) -> bool {
+        self.strokes > 0 && now.saturating_sub(self.last_stroke_ts) <= SESSION_GAP_SECS
+    }
+
+    /// 当前这轮摸了几下（已经结束则为 0）
+    pub fn current_strokes(&self, now: u64) -> u32 {
+        if self.is_active(now) { self.strokes } else { 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multiplier(outcome: PetOutcome) -> f32 {
+        match outcome {
+            PetOutcome::Enjoying(m) => m,
+            PetOutcome::Overstimulated => panic!("overstimulated too early"),
+        }
+    }
+
+    #[test]
+    fn warmup_strokes_are_ordinary_then_build_up() {
+        let mut session = PettingSession::new();
+        for t in 0..WARMUP_STROKES as u64 {
+            assert_eq!(multiplier(session.stroke(100 + t)), 1.0);
+        }
+        let mut last = 1.0;
+        for t in WARMUP_STROKES as u64..(OVERSTIMULATED_STROKES - 1) as u64 {
+            let m = multiplier(session.stroke(100 + t));
+            assert!(m > last && m <= MAX_MULTIPLIER, "{} after {}", m, last);
+            last = m;
+        }
+    }
+
+    #[test]
+    fn too_many_strokes_overstimulate_and_start_over() {
+        let mut session = PettingSession::new();
+        for t in 0..(OVERSTIMULATED_STROKES - 1) as u64 {
+            session.stroke(t);
+        }
+        let t = OVERSTIMULATED_STROKES as u64;
+        assert_eq!(session.stroke(t), PetOutcome::Overstimulated);
+        assert_eq!(session.current_strokes(t), 0);
+        assert_eq!(session.stroke(t + 1), PetOutcome::Enjoying(1.0));
+    }
+
+    #[test]
+    fn a_pause_ends_the_session() {
+        let mut session = PettingSession::new();
+        for t in 0..6 {
+            session.stroke(t);
+        }
+        assert_eq!(session.current_strokes(5), 6);
+        assert_eq!(session.current_strokes(5 + SESSION_GAP_SECS + 1), 0);
+        assert_eq!(session.stroke(5 + SESSION_GAP_SECS + 1), PetOutcome::Enjoying(1.0));
+        assert_eq!(session.current_strokes(5 + SESSION_GAP_SECS + 1), 1);
+    }
+}
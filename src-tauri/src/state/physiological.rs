@@ -1,5 +1,50 @@
 use serde::{Deserialize, Serialize};
 
+use super::personality::PersonalityTraits;
+
+/// 预测时用的阈值：饥饿 > 70、睡意 > 70、体力 < 20
+const NEED_HUNGER: f32 = 70.0;
+const NEED_SLEEPINESS: f32 = 70.0;
+const NEED_REST_ENERGY: f32 = 20.0;
+
+/// 快要出现的生理需求
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Need {
+    /// 饥饿 > 70
+    Hunger,
+    /// 睡意 > 70
+    Sleep,
+    /// 体力 < 20
+    Rest,
+}
+
+/// 饥饿超过这个值算"饿坏了"，会拖累体力
+const STARVING_HUNGER: f32 = 90.0;
+
+/// 被叫醒时剩下的睡意按这个比例记成睡眠债
+const SLEEP_DEBT_RATE: f32 = 0.5;
+/// 睡眠债上限：再怎么熬也只是轻微影响
+const MAX_SLEEP_DEBT: f32 = 50.0;
+/// 睡着时每分钟还掉的睡眠债
+const SLEEP_DEBT_REPAY: f32 = 0.5;
+/// 每点睡眠债压低的体力上限
+const SLEEP_DEBT_ENERGY_PENALTY: f32 = 0.4;
+/// 睡眠债低于这个值才会自然醒（债多了要睡更久）
+pub const SLEEP_DEBT_WAKE: f32 = 10.0;
+/// 睡眠债超过这个值，被吵醒时容易烦躁
+pub const SLEEP_DEBT_CRANKY: f32 = 20.0;
+
+/// 醒着时体力消耗的昼夜倍率：晨昏是猫的活跃期，醒着也能缓过来一点；
+/// 正午最没精神、掉得最快。24 小时平均约 1.0，和原来的匀速消耗持平
+pub fn circadian_drain(hour: u32) -> f32 {
+    match hour % 24 {
+        5..=7 | 17..=19 => -0.2,
+        11..=15 => 1.6,
+        _ => 1.3,
+    }
+}
+
 /// 第一层：生理状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhysiologicalState {
@@ -9,6 +54,9 @@ pub struct PhysiologicalState {
     pub hunger: f32,
     /// 睡意 0-100，清醒时增加，睡觉时归零
     pub sleepiness: f32,
+    /// 睡眠债 0-50：没睡够就被叫醒时累积，睡着时慢慢还清
+    #[serde(default)]
+    pub sleep_debt: f32,
 }
 
 impl PhysiologicalState {
@@ -17,24 +65,118 @@ impl PhysiologicalState {
             energy: 80.0,
             hunger: 20.0,
             sleepiness: 10.0,
+            sleep_debt: 0.0,
         }
     }
 
-    /// 每分钟更新一次生理状态
-    pub fn tick(&mut self, is_sleeping: bool) {
+    /// 每分钟更新一次生理状态，`hour` 是本地小时（0-23）
+    ///
+    /// 饿坏了的时候体力掉得更快、睡觉也恢复得更慢，且恢复不到满；
+    /// 醒着时的消耗按昼夜节律变化（见 `circadian_drain`）。
+    /// 体力的消耗、恢复和饿的速度都乘上性格的代谢倍率，体力不超过性格的上限
+    pub fn tick(&mut self, is_sleeping: bool, hour: u32, traits: &PersonalityTraits) {
+        let starving = self.is_starving();
+        let metabolism = traits.metabolism_rate();
         if is_sleeping {
-            self.energy = (self.energy + 2.0).min(100.0);
+            let recovery = (if starving { 1.0 } else { 2.0 }) * metabolism;
+            let ceiling = self.energy_ceiling(traits);
+            if self.energy < ceiling {
+                self.energy = (self.energy + recovery).min(ceiling);
+            }
             self.sleepiness = (self.sleepiness - 3.0).max(0.0);
+            self.sleep_debt = (self.sleep_debt - SLEEP_DEBT_REPAY).max(0.0);
         } else {
-            self.energy = (self.energy - 0.5).max(0.0);
+            let base = if starving { 0.8 } else { 0.5 };
+            // 饿坏了晨昏也精神不起来
+            let factor = if starving { circadian_drain(hour).max(1.0) } else { circadian_drain(hour) };
+            let drain = base * factor * metabolism;
+            if drain >= 0.0 {
+                self.energy = (self.energy - drain).max(0.0);
+            } else if self.energy < self.energy_ceiling(traits) {
+                self.energy = (self.energy - drain).min(self.energy_ceiling(traits));
+            }
             self.sleepiness = (self.sleepiness + 0.2).min(100.0);
         }
-        self.hunger = (self.hunger + 0.3).min(100.0);
+        self.hunger = (self.hunger + 0.3 * metabolism).min(100.0);
+        self.energy = self.energy.min(traits.energy_cap());
+    }
+
+    /// 按 tick 的速率往后推算每项需求还要几个 tick 出现（已经出现为 0）
+    ///
+    /// 假设睡/醒状态不变；`hour` 是当前本地小时，`ticks_per_hour` 用来推进昼夜节律，
+    /// 推算 `max_ticks` 个 tick 内都不会出现的需求不返回。结果按出现先后排序
+    pub fn ticks_until_needs(
+        &self,
+        is_sleeping: bool,
+        hour: u32,
+        traits: &PersonalityTraits,
+        ticks_per_hour: u32,
+        max_ticks: u32,
+    ) -> Vec<(Need, u32)> {
+        let mut sim = self.clone();
+        let mut found: Vec<(Need, u32)> = Vec::new();
+        let ticks_per_hour = ticks_per_hour.max(1);
+        for tick in 0..=max_ticks {
+            for need in [Need::Hunger, Need::Sleep, Need::Rest] {
+                if !found.iter().any(|(n, _)| *n == need) && sim.has_need(need) {
+                    found.push((need, tick));
+                }
+            }
+            if found.len() == 3 {
+                break;
+            }
+            sim.tick(is_sleeping, hour + tick / ticks_per_hour, traits);
+        }
+        found
+    }
+
+    fn has_need(&self, need: Need) -> bool {
+        match need {
+            Need::Hunger => self.hunger > NEED_HUNGER,
+            Need::Sleep => self.sleepiness > NEED_SLEEPINESS,
+            Need::Rest => self.energy < NEED_REST_ENERGY,
+        }
+    }
+
+    /// 体力能恢复到的上限：平时是性格的体力上限，饿坏了只能恢复到它的五分之三，欠着睡眠债也恢复不满
+    pub fn energy_ceiling(&self, traits: &PersonalityTraits) -> f32 {
+        let cap = traits.energy_cap();
+        let base = if self.is_starving() { cap * 3.0 / 5.0 } else { cap };
+        base - self.sleep_debt * SLEEP_DEBT_ENERGY_PENALTY
+    }
+
+    /// 醒来：没睡完的睡意记成睡眠债（自然醒时几乎为 0）
+    pub fn on_wake(&mut self) {
+        self.sleep_debt = (self.sleep_debt + self.sleepiness * SLEEP_DEBT_RATE).min(MAX_SLEEP_DEBT);
+    }
+
+    /// 睡眠债够少，可以自然醒了
+    pub fn is_rested(&self) -> bool {
+        self.sleep_debt < SLEEP_DEBT_WAKE
+    }
+
+    /// 欠了不少觉，被吵醒容易发脾气
+    pub fn is_sleep_deprived(&self) -> bool {
+        self.sleep_debt > SLEEP_DEBT_CRANKY
+    }
+
+    /// 是否饿坏了
+    pub fn is_starving(&self) -> bool {
+        self.hunger > STARVING_HUNGER
+    }
+
+    /// 数值拉回 0-100，NaN/无穷恢复成初始值
+    pub fn sanitize(&mut self) {
+        let defaults = Self::new();
+        self.energy = super::clamp_stat(self.energy, 0.0, 100.0, defaults.energy);
+        self.hunger = super::clamp_stat(self.hunger, 0.0, 100.0, defaults.hunger);
+        self.sleepiness = super::clamp_stat(self.sleepiness, 0.0, 100.0, defaults.sleepiness);
+        self.sleep_debt = super::clamp_stat(self.sleep_debt, 0.0, MAX_SLEEP_DEBT, defaults.sleep_debt);
     }
 
-    /// 喂食
-    pub fn feed(&mut self) {
-        self.hunger = (self.hunger - 30.0).max(0.0);
+    /// 喂食，`relief` 是减少的饥饿值
+    pub fn feed(&mut self, relief: f32) {
+        self.hunger = (self.hunger - relief).max(0.0);
     }
 
     /// 是否需要休息
@@ -47,3 +189,161 @@ impl PhysiologicalState {
         self.hunger > 70.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_hunger(hunger: f32) -> PhysiologicalState {
+        PhysiologicalState { energy: 70.0, hunger, sleepiness: 10.0, sleep_debt: 0.0 }
+    }
+
+    fn neutral() -> PersonalityTraits {
+        PersonalityTraits::neutral()
+    }
+
+    #[test]
+    fn starving_drains_energy_faster_while_awake() {
+        let mut fed = with_hunger(20.0);
+        let mut starving = with_hunger(95.0);
+        for _ in 0..10 {
+            fed.tick(false, 12, &neutral());
+            starving.tick(false, 12, &neutral());
+        }
+        assert!(70.0 - starving.energy > 70.0 - fed.energy);
+    }
+
+    #[test]
+    fn starving_sleep_recovers_slower_and_stops_at_the_ceiling() {
+        let mut fed = with_hunger(20.0);
+        let mut starving = with_hunger(95.0);
+        fed.tick(true, 12, &neutral());
+        starving.tick(true, 12, &neutral());
+        assert!(fed.energy > starving.energy);
+
+        starving.energy = 59.5;
+        starving.tick(true, 12, &neutral());
+        assert_eq!(starving.energy, 60.0);
+        starving.tick(true, 12, &neutral());
+        assert_eq!(starving.energy, 60.0);
+    }
+
+    #[test]
+    fn ceiling_is_not_lowered_for_a_normally_hungry_cat() {
+        assert_eq!(with_hunger(90.0).energy_ceiling(&neutral()), 100.0);
+        assert_eq!(with_hunger(91.0).energy_ceiling(&neutral()), 60.0);
+    }
+
+    #[test]
+    fn energy_drains_fastest_at_midday_and_recovers_at_dawn_and_dusk() {
+        let after_an_hour = |hour: u32| {
+            let mut phys = with_hunger(20.0);
+            for _ in 0..60 {
+                phys.tick(false, hour, &neutral());
+            }
+            phys.energy
+        };
+        assert!(after_an_hour(6) > 70.0);
+        assert!(after_an_hour(18) > 70.0);
+        assert!(after_an_hour(13) < after_an_hour(22));
+        assert!(after_an_hour(22) < 70.0);
+
+        // 全天平均和原来的匀速消耗差不多
+        let average = (0..24).map(circadian_drain).sum::<f32>() / 24.0;
+        assert!((average - 1.0).abs() < 0.05, "{}", average);
+    }
+
+    #[test]
+    fn starving_cats_do_not_perk_up_at_dusk() {
+        let mut starving = with_hunger(95.0);
+        starving.tick(false, 18, &neutral());
+        assert!(starving.energy < 70.0);
+    }
+
+    #[test]
+    fn need_forecast_matches_the_tick_rates() {
+        let phys = PhysiologicalState { energy: 80.4, hunger: 41.0, sleepiness: 11.1, sleep_debt: 0.0 };
+        // 每小时的 tick 数给得很大，让整段预测都停在正午
+        let awake = phys.ticks_until_needs(false, 12, &neutral(), 10_000, 1_000);
+        // 体力 0.5 × 1.6 = 0.8/tick，饥饿 0.3/tick，睡意 0.2/tick
+        assert_eq!(awake, vec![(Need::Rest, 76), (Need::Hunger, 97), (Need::Sleep, 295)]);
+
+        // 睡着时只会越来越饿
+        assert_eq!(phys.ticks_until_needs(true, 12, &neutral(), 10_000, 1_000), vec![(Need::Hunger, 97)]);
+        // 推算范围外的不返回，已经出现的是 0
+        assert!(phys.ticks_until_needs(false, 12, &neutral(), 10_000, 50).is_empty());
+        assert_eq!(with_hunger(80.0).ticks_until_needs(false, 12, &neutral(), 120, 0), vec![(Need::Hunger, 0)]);
+    }
+
+    #[test]
+    fn waking_early_leaves_sleep_debt_that_caps_energy() {
+        let mut phys = with_hunger(20.0);
+        phys.sleepiness = 2.0;
+        phys.on_wake();
+        assert_eq!(phys.sleep_debt, 1.0);
+        assert!(phys.is_rested());
+
+        phys.sleepiness = 70.0;
+        phys.on_wake();
+        assert_eq!(phys.sleep_debt, 36.0);
+        assert!(!phys.is_rested());
+        assert!(phys.is_sleep_deprived());
+        assert_eq!(phys.energy_ceiling(&neutral()), 100.0 - 36.0 * SLEEP_DEBT_ENERGY_PENALTY);
+
+        // 再怎么熬也有上限
+        phys.sleepiness = 100.0;
+        phys.on_wake();
+        phys.on_wake();
+        assert_eq!(phys.sleep_debt, MAX_SLEEP_DEBT);
+        phys.hunger = 95.0;
+        assert_eq!(phys.energy_ceiling(&neutral()), 60.0 - MAX_SLEEP_DEBT * SLEEP_DEBT_ENERGY_PENALTY);
+    }
+
+    #[test]
+    fn sleeping_pays_the_debt_back() {
+        let mut phys = with_hunger(20.0);
+        phys.sleep_debt = SLEEP_DEBT_WAKE + 2.0;
+        for _ in 0..4 {
+            phys.tick(true, 3, &neutral());
+        }
+        assert!(!phys.is_rested());
+        for _ in 0..2 {
+            phys.tick(true, 3, &neutral());
+        }
+        assert!(phys.is_rested());
+        // 醒着不还
+        let debt = phys.sleep_debt;
+        phys.tick(false, 12, &neutral());
+        assert_eq!(phys.sleep_debt, debt);
+    }
+
+    #[test]
+    fn a_fast_metabolism_drains_and_refills_faster() {
+        let fast = PersonalityTraits { metabolism: 1.5, ..neutral() };
+        let slow = PersonalityTraits { metabolism: 0.5, ..neutral() };
+        let (mut hot, mut lazy) = (with_hunger(20.0), with_hunger(20.0));
+        for _ in 0..10 {
+            hot.tick(false, 12, &fast);
+            lazy.tick(false, 12, &slow);
+        }
+        assert!(hot.energy < lazy.energy);
+        assert!(hot.hunger > lazy.hunger);
+        assert!((70.0 - hot.energy - 3.0 * (70.0 - lazy.energy)).abs() < 0.01);
+
+        let (mut hot, mut lazy) = (with_hunger(20.0), with_hunger(20.0));
+        hot.tick(true, 12, &fast);
+        lazy.tick(true, 12, &slow);
+        assert!(hot.energy > lazy.energy);
+    }
+
+    #[test]
+    fn energy_never_goes_past_her_own_ceiling() {
+        let lazy = PersonalityTraits { max_energy: 60.0, ..neutral() };
+        let mut phys = with_hunger(20.0);
+        for _ in 0..50 {
+            phys.tick(true, 3, &lazy);
+        }
+        assert_eq!(phys.energy, 60.0);
+        assert_eq!(with_hunger(95.0).energy_ceiling(&lazy), 36.0);
+    }
+}
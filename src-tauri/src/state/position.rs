@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+/// 最喜欢位置的加权样本上限：超过后新的互动仍能让它慢慢移动
+const MAX_FAVORITE_SAMPLES: u32 = 20;
+
+/// 屏幕上的逻辑位置：0.0 = 最左，1.0 = 最右（像素换算交给前端）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionState {
+    /// 当前位置 0-1
+    pub x: f32,
+    /// 最喜欢的位置：发生正向互动的位置的加权平均
+    pub favorite_x: Option<f32>,
+    /// 参与加权的样本数
+    pub favorite_samples: u32,
+}
+
+impl PositionState {
+    pub fn new() -> Self {
+        Self {
+            x: 0.5,
+            favorite_x: None,
+            favorite_samples: 0,
+        }
+    }
+
+    /// 移动一段距离（left = 朝左）
+    pub fn drift(&mut self, left: bool, distance: f32) {
+        let dx = if left { -distance } else { distance };
+        self.x = (self.x + dx).clamp(0.0, 1.0);
+    }
+
+    /// 位置拉回 0-1；坏掉的最喜欢位置直接丢弃
+    pub fn sanitize(&mut self) {
+        self.x = super::clamp_stat(self.x, 0.0, 1.0, 0.5);
+        if self.favorite_x.is_some_and(|fav| !fav.is_finite()) {
+            self.favorite_x = None;
+            self.favorite_samples = 0;
+        }
+        self.favorite_x = self.favorite_x.map(|fav| fav.clamp(0.0, 1.0));
+    }
+
+    /// 记录一次发生在当前位置的正向互动
+    pub fn on_positive_interaction(&mut self) {
+        let samples = self.favorite_samples.min(MAX_FAVORITE_SAMPLES) as f32;
+        self.favorite_x = Some(match self.favorite_x {
+            Some(fav) => (fav * samples + self.x) / (samples + 1.0),
+            None => self.x,
+        });
+        self.favorite_samples = (self.favorite_samples + 1).min(MAX_FAVORITE_SAMPLES);
+    }
+
+    /// 离最喜欢的位置有多远（没有则为 0）
+    pub fn distance_to_favorite(&self) -> f32 {
+        self.favorite_x.map(|fav| (fav - self.x).abs()).unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_stays_on_screen() {
+        let mut pos = PositionState::new();
+        pos.drift(true, 0.8);
+        assert_eq!(pos.x, 0.0);
+        pos.drift(false, 1.5);
+        assert_eq!(pos.x, 1.0);
+    }
+
+    #[test]
+    fn favorite_spot_follows_positive_interactions() {
+        let mut pos = PositionState::new();
+        assert_eq!(pos.distance_to_favorite(), 0.0);
+
+        pos.x = 0.2;
+        pos.on_positive_interaction();
+        assert_eq!(pos.favorite_x, Some(0.2));
+
+        // 第二次互动在右边：喜欢的位置取两次的平均
+        pos.x = 0.6;
+        pos.on_positive_interaction();
+        assert!((pos.favorite_x.unwrap() - 0.4).abs() < 1e-6);
+        assert!((pos.distance_to_favorite() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn favorite_samples_are_capped() {
+        let mut pos = PositionState::new();
+        pos.x = 0.0;
+        for _ in 0..50 {
+            pos.on_positive_interaction();
+        }
+        assert_eq!(pos.favorite_samples, MAX_FAVORITE_SAMPLES);
+
+        // 样本封顶后，新位置仍能把它拉动一点
+        pos.x = 1.0;
+        pos.on_positive_interaction();
+        let fav = pos.favorite_x.unwrap();
+        assert!(fav > 0.04 && fav < 0.06, "fav = {}", fav);
+    }
+}
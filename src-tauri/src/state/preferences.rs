@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use super::food::FoodType;
+
+/// 最多记住几种玩具的口味
+const MAX_TOY_TASTES: usize = 20;
+/// 分数至少这么高才算"喜欢"（偶尔一次不算）
+const MIN_FAVORITE_SCORE: f32 = 3.0;
+
+/// 对一种东西的口味：累计的正向反应越多，越喜欢
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Taste {
+    pub kind: String,
+    /// 累计的反应强度
+    pub score: f32,
+    pub times: u32,
+}
+
+/// 慢慢形成的口味：哪种食物、哪个玩具让她反应最好
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preferences {
+    pub foods: Vec<Taste>,
+    pub toys: Vec<Taste>,
+}
+
+impl Preferences {
+    pub fn new() -> Self {
+        Self { foods: Vec::new(), toys: Vec::new() }
+    }
+
+    /// 吃了一种食物，`response` 是这次带来的亲密度增长
+    pub fn record_food(&mut self, food: FoodType, response: f32) {
+        record(&mut self.foods, food.as_str(), response, usize::MAX);
+    }
+
+    /// 玩了一个玩具，`response` 是这次有多兴奋（0-1）
+    pub fn record_toy(&mut self, kind: &str, response: f32) {
+        record(&mut self.toys, kind, response, MAX_TOY_TASTES);
+    }
+
+    /// 最喜欢的食物（还没形成口味时为 None）
+    pub fn favorite_food(&self) -> Option<FoodType> {
+        favorite(&self.foods).map(|t| FoodType::parse(&t.kind))
+    }
+
+    /// 最喜欢的玩具
+    pub fn favorite_toy(&self) -> Option<&str> {
+        favorite(&self.toys).map(|t| t.kind.as_str())
+    }
+
+    /// 对这个玩具的偏爱程度 0-1（相对最喜欢的那个）；还没喜欢上的玩具为 0
+    pub fn toy_affinity(&self, kind: &str) -> f32 {
+        let best = self.toys.iter().map(|t| t.score).fold(0.0, f32::max);
+        if best < MIN_FAVORITE_SCORE {
+            return 0.0;
+        }
+        self.toys
+            .iter()
+            .find(|t| t.kind == kind && t.score >= MIN_FAVORITE_SCORE)
+            .map_or(0.0, |t| (t.score / best).clamp(0.0, 1.0))
+    }
+}
+
+/// 累加一次反应；负的或无效的反应不算（不会因此变讨厌）
+fn record(tastes: &mut Vec<Taste>, kind: &str, response: f32, max: usize) {
+    if !response.is_finite() {
+        return;
+    }
+    let response = response.max(0.0);
+    match tastes.iter_mut().find(|t| t.kind == kind) {
+        Some(taste) => {
+            taste.score += response;
+            taste.times += 1;
+        }
+        None => {
+            if tastes.len() >= max {
+                // 挤掉最不喜欢的
+                if let Some(i) = tastes
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| a.1.score.total_cmp(&b.1.score))
+                    .map(|(i, _)| i)
+                {
+                    tastes.remove(i);
+                }
+            }
+            tastes.push(Taste { kind: kind.to_string(), score: response, times: 1 });
+        }
+    }
+}
+
+fn favorite(tastes: &[Taste]) -> Option<&Taste> {
+    tastes
+        .iter()
+        .filter(|t| t.score >= MIN_FAVORITE_SCORE)
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_good_meals_make_a_favorite_food() {
+        let mut prefs = Preferences::new();
+        prefs.record_food(FoodType::Fish, 1.5);
+        assert_eq!(prefs.favorite_food(), None);
+
+        for _ in 0..3 {
+            prefs.record_food(FoodType::Fish, 1.5);
+            prefs.record_food(FoodType::Treat, 0.5);
+            prefs.record_food(FoodType::Meal, -2.0);
+        }
+        assert_eq!(prefs.favorite_food(), Some(FoodType::Fish));
+        let score = |kind: &str| prefs.foods.iter().find(|t| t.kind == kind).unwrap().score;
+        assert!(score("fish") > score("treat"));
+        // 反应不好也不会变成讨厌
+        assert_eq!(score("meal"), 0.0);
+    }
+
+    #[test]
+    fn toy_affinity_is_relative_and_the_least_liked_toy_is_forgotten_first() {
+        let mut prefs = Preferences::new();
+        assert_eq!(prefs.toy_affinity("ball"), 0.0);
+        for i in 0..MAX_TOY_TASTES {
+            prefs.record_toy(&format!("toy{}", i), 0.5);
+        }
+        // 玩得再多，还没喜欢上就没有偏爱
+        assert_eq!(prefs.toy_affinity("toy0"), 0.0);
+        prefs.record_toy("toy0", 4.0);
+        prefs.record_toy("toy1", 2.5);
+        assert_eq!(prefs.toy_affinity("toy0"), 1.0);
+        assert_eq!(prefs.toy_affinity("toy1"), 3.0 / 4.5);
+        assert_eq!(prefs.toy_affinity("toy2"), 0.0);
+        assert_eq!(prefs.favorite_toy(), Some("toy0"));
+
+        prefs.record_toy("ball", 0.1);
+        assert_eq!(prefs.toys.len(), MAX_TOY_TASTES);
+        assert!(prefs.toys.iter().any(|t| t.kind == "ball"));
+        assert!(prefs.toys.iter().any(|t| t.kind == "toy0"));
+    }
+}
@@ -1,5 +1,42 @@
 use serde::{Deserialize, Serialize};
 
+use super::personality::PersonalityTraits;
+use crate::config::TuningConfig;
+
+/// 一句话对关系的最大影响
+const MAX_TONE_DELTA: f32 = 2.0;
+
+/// 了解度分档：越了解主人，说话越放得开
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnderstandingTier {
+    /// < 30：还很戒备
+    Guarded,
+    /// 30-70：熟悉了
+    Familiar,
+    /// > 70：心意相通
+    Attuned,
+}
+
+impl UnderstandingTier {
+    /// 回应时参考的记忆条数
+    pub fn memory_budget(&self) -> usize {
+        match self {
+            UnderstandingTier::Guarded => 3,
+            UnderstandingTier::Familiar => 5,
+            UnderstandingTier::Attuned => 8,
+        }
+    }
+
+    /// 想法气泡的字数上限
+    pub fn thought_limit(&self) -> usize {
+        match self {
+            UnderstandingTier::Guarded => 8,
+            UnderstandingTier::Familiar => 10,
+            UnderstandingTier::Attuned => 15,
+        }
+    }
+}
+
 /// 第三层：关系状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelationshipState {
@@ -20,22 +57,91 @@ impl RelationshipState {
         }
     }
 
-    /// 良好互动后增加关系值
-    pub fn on_positive_interaction(&mut self) {
-        self.trust = (self.trust + 0.5).min(100.0);
+    /// 良好互动后增加关系值（怕生的猫信任涨得慢）
+    pub fn on_positive_interaction(&mut self, traits: &PersonalityTraits) {
+        self.trust = (self.trust + 0.5 * traits.trust_gain()).min(100.0);
         self.intimacy = (self.intimacy + 0.8).min(100.0);
     }
 
+    /// 额外增加亲密度（好吃的零食等）
+    pub fn add_intimacy(&mut self, amount: f32) {
+        self.intimacy = (self.intimacy + amount).clamp(0.0, 100.0);
+    }
+
+    /// 主人说话的语气：被夸亲密度涨得多，被凶先伤信任（delta 截断到 -2..2）
+    pub fn on_speech_tone(&mut self, delta: f32) {
+        if !delta.is_finite() {
+            return;
+        }
+        let delta = delta.clamp(-MAX_TONE_DELTA, MAX_TONE_DELTA);
+        if delta >= 0.0 {
+            self.trust = (self.trust + delta * 0.3).min(100.0);
+            self.intimacy = (self.intimacy + delta * 0.5).min(100.0);
+        } else {
+            self.trust = (self.trust + delta).max(0.0);
+            self.intimacy = (self.intimacy + delta * 0.5).max(0.0);
+        }
+    }
+
+    /// 难过/烦躁时被主人安抚成功：信任明显增加
+    pub fn on_comforted(&mut self, traits: &PersonalityTraits) {
+        self.trust = (self.trust + 1.0 * traits.trust_gain()).min(100.0);
+        self.intimacy = (self.intimacy + 0.5).min(100.0);
+    }
+
+    /// 到了饭点一直没人喂：信任慢慢受损
+    pub fn on_missed_meal(&mut self) {
+        self.trust = (self.trust - 0.5).max(0.0);
+    }
+
+    /// 学到了主人的一件具体的事（名字等）
+    pub fn on_learned_fact(&mut self) {
+        self.understanding = (self.understanding + 2.0).min(100.0);
+    }
+
     /// 对话后增加了解度
     pub fn on_conversation(&mut self) {
         self.understanding = (self.understanding + 1.0).min(100.0);
         self.intimacy = (self.intimacy + 0.3).min(100.0);
     }
 
-    /// 长期忽视导致关系下降
-    pub fn on_neglect(&mut self) {
-        self.trust = (self.trust - 0.1).max(0.0);
-        self.intimacy = (self.intimacy - 0.2).max(0.0);
+    /// 互相慢眨眼：猫表达信任的方式，亲密度明显增加
+    pub fn on_slow_blink(&mut self) {
+        self.trust = (self.trust + 0.3).min(100.0);
+        self.intimacy = (self.intimacy + 1.5).min(100.0);
+    }
+
+    /// 长期忽视导致关系下降（独立的猫不太在意）
+    ///
+    /// 宽限期内不掉，之后冷落越久掉得越快；感情越深亲密度掉得越慢，信任却伤得越重
+    pub fn on_neglect(&mut self, traits: &PersonalityTraits, minutes_since_interaction: u32, tuning: &TuningConfig) {
+        let ramp = tuning.neglect_ramp(minutes_since_interaction);
+        if ramp <= 0.0 {
+            return;
+        }
+        let decay = ramp * traits.neglect_decay();
+        let bond = self.intimacy / 100.0;
+        self.trust = (self.trust - tuning.neglect_trust_rate * decay * (1.0 + bond * 0.5)).max(0.0);
+        self.intimacy = (self.intimacy - tuning.neglect_intimacy_rate * decay * (1.0 - bond * 0.5)).max(0.0);
+    }
+
+    /// 数值拉回 0-100，NaN/无穷恢复成初始值
+    pub fn sanitize(&mut self) {
+        let defaults = Self::new();
+        self.trust = super::clamp_stat(self.trust, 0.0, 100.0, defaults.trust);
+        self.intimacy = super::clamp_stat(self.intimacy, 0.0, 100.0, defaults.intimacy);
+        self.understanding = super::clamp_stat(self.understanding, 0.0, 100.0, defaults.understanding);
+    }
+
+    /// 当前了解度所在的档位
+    pub fn understanding_tier(&self) -> UnderstandingTier {
+        if self.understanding > 70.0 {
+            UnderstandingTier::Attuned
+        } else if self.understanding >= 30.0 {
+            UnderstandingTier::Familiar
+        } else {
+            UnderstandingTier::Guarded
+        }
     }
 
     /// 是否愿意主动靠近（信任度 > 30）
@@ -53,3 +159,115 @@ impl RelationshipState {
         self.trust > 70.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_blink_is_only_returned_above_trust_fifty() {
+        let mut rel = RelationshipState::new();
+        rel.trust = 50.0;
+        assert!(!rel.will_slow_blink());
+        rel.trust = 51.0;
+        assert!(rel.will_slow_blink());
+    }
+
+    #[test]
+    fn returned_slow_blink_deepens_intimacy_up_to_the_cap() {
+        let mut rel = RelationshipState::new();
+        rel.trust = 60.0;
+        rel.intimacy = 20.0;
+        rel.on_slow_blink();
+        assert!((rel.trust - 60.3).abs() < 1e-4);
+        assert!((rel.intimacy - 21.5).abs() < 1e-4);
+
+        rel.trust = 99.9;
+        rel.intimacy = 99.0;
+        rel.on_slow_blink();
+        assert_eq!(rel.trust, 100.0);
+        assert_eq!(rel.intimacy, 100.0);
+    }
+
+    #[test]
+    fn understanding_tiers_widen_memory_and_thought_budgets() {
+        let mut rel = RelationshipState::new();
+        assert_eq!(rel.understanding_tier(), UnderstandingTier::Guarded);
+        rel.understanding = 30.0;
+        assert_eq!(rel.understanding_tier(), UnderstandingTier::Familiar);
+        rel.understanding = 70.0;
+        assert_eq!(rel.understanding_tier(), UnderstandingTier::Familiar);
+        rel.understanding = 70.5;
+        assert_eq!(rel.understanding_tier(), UnderstandingTier::Attuned);
+
+        let tiers = [UnderstandingTier::Guarded, UnderstandingTier::Familiar, UnderstandingTier::Attuned];
+        for pair in tiers.windows(2) {
+            assert!(pair[0].memory_budget() < pair[1].memory_budget());
+            assert!(pair[0].thought_limit() < pair[1].thought_limit());
+        }
+    }
+
+    #[test]
+    fn praise_warms_and_harsh_words_hurt_trust_first() {
+        let mut rel = RelationshipState::new();
+        rel.trust = 50.0;
+        rel.intimacy = 50.0;
+        rel.on_speech_tone(2.0);
+        assert!((rel.trust - 50.6).abs() < 1e-4);
+        assert!((rel.intimacy - 51.0).abs() < 1e-4);
+
+        rel.on_speech_tone(-1.0);
+        assert!((rel.trust - 49.6).abs() < 1e-4);
+        assert!((rel.intimacy - 50.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn speech_tone_is_clamped_and_ignores_garbage() {
+        let mut rel = RelationshipState::new();
+        rel.trust = 50.0;
+        rel.on_speech_tone(-50.0);
+        assert_eq!(rel.trust, 48.0);
+        rel.on_speech_tone(f32::NAN);
+        assert_eq!(rel.trust, 48.0);
+    }
+
+    #[test]
+    fn neglect_waits_out_the_grace_period_then_speeds_up() {
+        let tuning = TuningConfig::new();
+        let traits = PersonalityTraits::neutral();
+        let loss_after = |minutes: u32| {
+            let mut rel = RelationshipState::new();
+            rel.trust = 50.0;
+            rel.intimacy = 50.0;
+            rel.on_neglect(&traits, minutes, &tuning);
+            (50.0 - rel.trust, 50.0 - rel.intimacy)
+        };
+        assert_eq!(loss_after(60), (0.0, 0.0));
+        assert_eq!(loss_after(tuning.neglect_grace_minutes), (0.0, 0.0));
+
+        let early = loss_after(tuning.neglect_grace_minutes + 30);
+        let later = loss_after(tuning.neglect_grace_minutes + 120);
+        let much_later = loss_after(tuning.neglect_grace_minutes + 600);
+        assert!(early.0 > 0.0 && early.1 > 0.0);
+        assert!(later.0 > early.0 && later.1 > early.1);
+        assert!(much_later.0 > later.0);
+        // 加速有上限
+        assert_eq!(much_later, loss_after(tuning.neglect_grace_minutes + 6000));
+    }
+
+    #[test]
+    fn a_close_bond_keeps_intimacy_but_trust_stings_more() {
+        let tuning = TuningConfig::new();
+        let traits = PersonalityTraits::neutral();
+        let mut distant = RelationshipState::new();
+        distant.trust = 50.0;
+        distant.intimacy = 10.0;
+        let mut close = distant.clone();
+        close.intimacy = 90.0;
+
+        distant.on_neglect(&traits, 300, &tuning);
+        close.on_neglect(&traits, 300, &tuning);
+        assert!(50.0 - close.trust > 50.0 - distant.trust);
+        assert!(90.0 - close.intimacy < 10.0 - distant.intimacy);
+    }
+}
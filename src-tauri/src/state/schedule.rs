@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+/// 某个钟点至少被喂过这么多次，才算"平时这个点吃饭"
+const MIN_HABIT_FEEDS: u32 = 3;
+/// 某个钟点的次数至少要达到最常喂的钟点的这个比例
+const MIN_HABIT_SHARE: f32 = 0.3;
+/// 任何钟点的次数超过这个值就全部减半，让习惯能慢慢改变
+const MAX_HOUR_COUNT: u32 = 30;
+/// 饭点开始后多久（秒）还没吃就算错过了
+const MISSED_AFTER_SECS: u64 = 2 * 3600;
+/// 连续错过这么多顿才开始影响信任
+const MISSED_TRUST_STREAK: u32 = 2;
+
+/// 学到的喂食时间表：每个本地钟点被喂过几次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedingSchedule {
+    pub hour_counts: [u32; 24],
+    pub last_feed_ts: Option<u64>,
+    /// 连续错过的饭点数（按时喂一次就清零）
+    pub missed_streak: u32,
+    /// 最近一次判定过的饭点开始时间，避免同一顿重复判定
+    pub last_checked_meal_ts: Option<u64>,
+}
+
+impl FeedingSchedule {
+    pub fn new() -> Self {
+        Self {
+            hour_counts: [0; 24],
+            last_feed_ts: None,
+            missed_streak: 0,
+            last_checked_meal_ts: None,
+        }
+    }
+
+    /// 记一次喂食；`hour` 是本地钟点
+    pub fn record_feed(&mut self, now: u64, hour: u32) {
+        let slot = &mut self.hour_counts[(hour % 24) as usize];
+        *slot += 1;
+        if *slot > MAX_HOUR_COUNT {
+            for count in self.hour_counts.iter_mut() {
+                *count /= 2;
+            }
+        }
+        self.last_feed_ts = Some(now);
+        self.missed_streak = 0;
+    }
+
+    /// 平时的饭点（本地钟点，从小到大）
+    pub fn usual_hours(&self) -> Vec<u32> {
+        let max = self.hour_counts.iter().copied().max().unwrap_or(0);
+        (0..24u32)
+            .filter(|&h| {
+                let count = self.hour_counts[h as usize];
+                count >= MIN_HABIT_FEEDS && count as f32 >= max as f32 * MIN_HABIT_SHARE
+            })
+            .collect()
+    }
+
+    /// 当前所在饭点的开始时间：本地钟点是饭点或刚过一个钟头，且这顿还没吃
+    ///
+    /// `hour_start_ts` 是当前本地钟点开始的时间戳
+    pub fn pending_meal(&self, hour: u32, hour_start_ts: u64) -> Option<u64> {
+        let usual = self.usual_hours();
+        let meal_start = if usual.contains(&(hour % 24)) {
+            hour_start_ts
+        } else if usual.contains(&((hour + 23) % 24)) {
+            hour_start_ts.saturating_sub(3600)
+        } else {
+            return None;
+        };
+        let fed = self.last_feed_ts.is_some_and(|ts| ts >= meal_start);
+        (!fed).then_some(meal_start)
+    }
+
+    /// 饭点过了还没吃：每顿只判定一次，返回是否该影响信任（连续错过才会）
+    pub fn check_missed(&mut self, hour: u32, hour_start_ts: u64) -> bool {
+        // 饭点开始后两个钟头还没吃才算错过
+        let meal_hour = (hour + 22) % 24;
+        if !self.usual_hours().contains(&meal_hour) {
+            return false;
+        }
+        let meal_start = hour_start_ts.saturating_sub(MISSED_AFTER_SECS);
+        if self.last_checked_meal_ts == Some(meal_start) {
+            return false;
+        }
+        self.last_checked_meal_ts = Some(meal_start);
+        if self.last_feed_ts.is_some_and(|ts| ts >= meal_start) {
+            return false;
+        }
+        self.missed_streak += 1;
+        self.missed_streak >= MISSED_TRUST_STREAK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400;
+    /// 某天本地 18 点开始的时间戳（测试里直接当 UTC 用）
+    const SIX_PM: u64 = 100 * DAY + 18 * 3600;
+
+    fn fed_at_six_for(days: u64) -> FeedingSchedule {
+        let mut schedule = FeedingSchedule::new();
+        for d in 0..days {
+            schedule.record_feed(SIX_PM - (d + 1) * DAY + 600, 18);
+        }
+        schedule
+    }
+
+    #[test]
+    fn repeated_feeds_at_one_hour_become_a_meal_time() {
+        assert!(fed_at_six_for(2).usual_hours().is_empty());
+        let schedule = fed_at_six_for(3);
+        assert_eq!(schedule.usual_hours(), vec![18]);
+
+        assert_eq!(schedule.pending_meal(18, SIX_PM), Some(SIX_PM));
+        // 饭点后一个钟头还在等
+        assert_eq!(schedule.pending_meal(19, SIX_PM + 3600), Some(SIX_PM));
+        assert_eq!(schedule.pending_meal(17, SIX_PM - 3600), None);
+        assert_eq!(schedule.pending_meal(20, SIX_PM + 2 * 3600), None);
+
+        // 这顿吃过了就不等了
+        let mut fed = schedule.clone();
+        fed.record_feed(SIX_PM + 300, 18);
+        assert_eq!(fed.pending_meal(19, SIX_PM + 3600), None);
+    }
+
+    #[test]
+    fn rare_feeding_hours_do_not_count_and_old_habits_fade() {
+        let mut schedule = fed_at_six_for(11);
+        for _ in 0..3 {
+            schedule.record_feed(SIX_PM, 8);
+        }
+        // 8 点只有 18 点的三成不到
+        assert_eq!(schedule.usual_hours(), vec![18]);
+
+        for _ in 0..MAX_HOUR_COUNT {
+            schedule.record_feed(SIX_PM, 18);
+        }
+        assert!(schedule.hour_counts[18] <= MAX_HOUR_COUNT);
+        assert!(schedule.hour_counts[8] < 3);
+    }
+
+    #[test]
+    fn missed_meals_are_judged_once_and_only_a_streak_hurts() {
+        let mut schedule = fed_at_six_for(3);
+        let eight_pm = SIX_PM + 2 * 3600;
+        assert!(!schedule.check_missed(20, eight_pm));
+        assert_eq!(schedule.missed_streak, 1);
+        // 同一顿不重复算
+        assert!(!schedule.check_missed(20, eight_pm));
+        assert_eq!(schedule.missed_streak, 1);
+
+        assert!(schedule.check_missed(20, eight_pm + DAY));
+        assert_eq!(schedule.missed_streak, 2);
+
+        // 按时喂了就清零
+        schedule.record_feed(SIX_PM + 2 * DAY + 60, 18);
+        assert_eq!(schedule.missed_streak, 0);
+        assert!(!schedule.check_missed(20, eight_pm + 2 * DAY));
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+/// 互动统计（随状态一起持久化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionStats {
+    /// 累计喂食次数
+    pub feeds: u64,
+    /// 累计点击次数
+    pub clicks: u64,
+    /// 累计对话次数
+    pub conversations: u64,
+    /// 诞生时间戳（秒）
+    pub born_ts: u64,
+    /// 今天的喂食 / 点击 / 对话 / 玩耍次数（每天零点清零）
+    #[serde(default)]
+    pub feeds_today: u32,
+    #[serde(default)]
+    pub clicks_today: u32,
+    #[serde(default)]
+    pub conversations_today: u32,
+    #[serde(default)]
+    pub plays_today: u32,
+}
+
+impl InteractionStats {
+    pub fn new() -> Self {
+        Self {
+            feeds: 0,
+            clicks: 0,
+            conversations: 0,
+            born_ts: super::unix_now(),
+            feeds_today: 0,
+            clicks_today: 0,
+            conversations_today: 0,
+            plays_today: 0,
+        }
+    }
+
+    pub fn record_feed(&mut self) {
+        self.feeds += 1;
+        self.feeds_today += 1;
+    }
+
+    pub fn record_click(&mut self) {
+        self.clicks += 1;
+        self.clicks_today += 1;
+    }
+
+    pub fn record_conversation(&mut self) {
+        self.conversations += 1;
+        self.conversations_today += 1;
+    }
+
+    pub fn record_play(&mut self) {
+        self.plays_today += 1;
+    }
+
+    /// 今天一共互动了几次
+    pub fn interactions_today(&self) -> u32 {
+        self.feeds_today + self.clicks_today + self.conversations_today + self.plays_today
+    }
+
+    /// 新的一天：清零今天的计数，返回昨天的互动次数
+    pub fn start_new_day(&mut self) -> u32 {
+        let yesterday = self.interactions_today();
+        self.feeds_today = 0;
+        self.clicks_today = 0;
+        self.conversations_today = 0;
+        self.plays_today = 0;
+        yesterday
+    }
+
+    /// 活了几天（诞生当天算第 1 天）
+    pub fn days_alive(&self, now: u64) -> u64 {
+        now.saturating_sub(self.born_ts) / 86400 + 1
+    }
+}
+
+/// 根据有互动的日期计算（当前连续天数, 最长连续天数）
+///
+/// `days` 是本地日期编号（天数），顺序不限、可重复；`today` 同样是日期编号。
+/// 今天还没互动时，连续天数从昨天算起，不算中断。
+pub fn compute_streak(days: &[i64], today: i64) -> (u32, u32) {
+    let mut days = days.to_vec();
+    days.sort_unstable();
+    days.dedup();
+
+    let mut longest = 0u32;
+    let mut run = 0u32;
+    let mut prev: Option<i64> = None;
+    for &day in &days {
+        run = match prev {
+            Some(p) if day == p + 1 => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(day);
+    }
+
+    let current = match days.last() {
+        Some(&last) if last == today || last == today - 1 => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streak_counts_consecutive_days() {
+        assert_eq!(compute_streak(&[], 100), (0, 0));
+        assert_eq!(compute_streak(&[98, 99, 100], 100), (3, 3));
+        // 顺序和重复不影响
+        assert_eq!(compute_streak(&[100, 98, 99, 99], 100), (3, 3));
+    }
+
+    #[test]
+    fn streak_survives_until_today_is_over() {
+        // 今天还没互动，从昨天算起
+        assert_eq!(compute_streak(&[97, 98, 99], 100), (3, 3));
+        // 昨天也没有，就断了
+        assert_eq!(compute_streak(&[96, 97, 98], 100), (0, 3));
+    }
+
+    #[test]
+    fn days_alive_counts_the_first_day() {
+        let stats = InteractionStats { born_ts: 1_000_000, ..InteractionStats::new() };
+        assert_eq!(stats.days_alive(1_000_000), 1);
+        assert_eq!(stats.days_alive(1_000_000 + 86_399), 1);
+        assert_eq!(stats.days_alive(1_000_000 + 86_400 * 3), 4);
+        // 时钟倒退也不会下溢
+        assert_eq!(stats.days_alive(0), 1);
+    }
+
+    #[test]
+    fn longest_streak_remembers_older_runs() {
+        assert_eq!(compute_streak(&[1, 2, 3, 4, 10, 11], 11), (2, 4));
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+/// 同一个玩具隔这么久（秒）没见，又会重新觉得新鲜
+const NOVELTY_RESET_SECS: u64 = 3600;
+/// 最多记住几种最近见过的玩具
+const MAX_TOYS: usize = 10;
+/// 玩具名最长几个字
+const MAX_KIND_CHARS: usize = 20;
+
+/// 最近见过的一种玩具
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenToy {
+    pub kind: String,
+    /// 这段时间里看了几次
+    pub times: u32,
+    pub last_seen_ts: u64,
+}
+
+/// 最近见过的玩具：同一个玩具反复拿出来，新鲜感越来越低
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToyMemory {
+    pub seen: Vec<SeenToy>,
+}
+
+impl ToyMemory {
+    pub fn new() -> Self {
+        Self { seen: Vec::new() }
+    }
+
+    /// 给她看一个玩具，返回新鲜感 0-1（第一次 1.0，之后 1/2、1/3 ...）
+    pub fn present(&mut self, kind: &str, now: u64) -> f32 {
+        let kind = normalize_kind(kind);
+        self.seen
+            .retain(|t| now.saturating_sub(t.last_seen_ts) < NOVELTY_RESET_SECS);
+        let times = match self.seen.iter_mut().find(|t| t.kind == kind) {
+            Some(toy) => {
+                toy.times += 1;
+                toy.last_seen_ts = now;
+                toy.times
+            }
+            None => {
+                if self.seen.len() >= MAX_TOYS {
+                    self.seen.remove(0);
+                }
+                self.seen.push(SeenToy { kind, times: 1, last_seen_ts: now });
+                1
+            }
+        };
+        1.0 / times as f32
+    }
+}
+
+/// 统一玩具名：去空白、小写、截断；没说是什么就叫"玩具"
+pub fn normalize_kind(kind: &str) -> String {
+    let kind: String = kind.trim().to_lowercase().chars().take(MAX_KIND_CHARS).collect();
+    if kind.is_empty() { "玩具".to_string() } else { kind }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_toy_gets_boring_but_a_new_one_is_exciting() {
+        let mut toys = ToyMemory::new();
+        assert_eq!(toys.present("逗猫棒", 100), 1.0);
+        assert_eq!(toys.present(" 逗猫棒 ", 110), 0.5);
+        assert!(toys.present("逗猫棒", 120) < 0.5);
+        assert_eq!(toys.present("毛线球", 130), 1.0);
+
+        // 隔了很久再拿出来又新鲜了
+        assert_eq!(toys.present("逗猫棒", 120 + NOVELTY_RESET_SECS), 1.0);
+    }
+
+    #[test]
+    fn toy_names_are_normalized() {
+        assert_eq!(normalize_kind("  Ball "), "ball");
+        assert_eq!(normalize_kind(""), "玩具");
+        assert_eq!(normalize_kind(&"鱼".repeat(50)).chars().count(), MAX_KIND_CHARS);
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+/// 信任至少这么高她才会理主人的口令
+const MIN_TRICK_TRUST: f32 = 40.0;
+/// 理解至少这么高她才听得懂口令
+const MIN_TRICK_UNDERSTANDING: f32 = 20.0;
+/// 每成功一次，下次照做的概率多这么多
+const TRAINING_STEP: f32 = 0.08;
+/// 训练得再好也有不想理你的时候
+const MAX_SUCCESS_CHANCE: f32 = 0.9;
+/// 训练进度的上限（之后再成功也不再涨）
+const MAX_TRAINED: u32 = 20;
+
+/// 能学会的把戏
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trick {
+    Sit,
+    Come,
+    PlayDead,
+}
+
+impl Trick {
+    pub const ALL: [Trick; 3] = [Trick::Sit, Trick::Come, Trick::PlayDead];
+
+    /// 解析口令（中英文都认，忽略大小写和首尾空白），不认识的返回 None
+    pub fn parse(cmd: &str) -> Option<Trick> {
+        match cmd.trim().to_lowercase().as_str() {
+            "sit" | "坐" | "坐下" => Some(Trick::Sit),
+            "come" | "来" | "过来" => Some(Trick::Come),
+            "play dead" | "play_dead" | "装死" => Some(Trick::PlayDead),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Trick::Sit => "sit",
+            Trick::Come => "come",
+            Trick::PlayDead => "play_dead",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trick::Sit => "坐下",
+            Trick::Come => "过来",
+            Trick::PlayDead => "装死",
+        }
+    }
+
+    /// 没训练过时照做的概率：越难的把戏越低
+    fn base_chance(&self) -> f32 {
+        match self {
+            Trick::Sit => 0.3,
+            Trick::Come => 0.25,
+            Trick::PlayDead => 0.1,
+        }
+    }
+}
+
+/// 每种把戏成功过几次（训练进度，持久化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrickTraining {
+    #[serde(default)]
+    pub sit: u32,
+    #[serde(default)]
+    pub come: u32,
+    #[serde(default)]
+    pub play_dead: u32,
+}
+
+impl TrickTraining {
+    pub fn new() -> Self {
+        Self { sit: 0, come: 0, play_dead: 0 }
+    }
+
+    pub fn trained(&self, trick: Trick) -> u32 {
+        match trick {
+            Trick::Sit => self.sit,
+            Trick::Come => self.come,
+            Trick::PlayDead => self.play_dead,
+        }
+    }
+
+    /// 信任和理解够了时照做的概率，随训练进度上升
+    pub fn success_chance(&self, trick: Trick) -> f32 {
+        (trick.base_chance() + TRAINING_STEP * self.trained(trick) as f32).min(MAX_SUCCESS_CHANCE)
+    }
+
+    /// 照做了一次：训练进度加一
+    pub fn record_success(&mut self, trick: Trick) {
+        let count = match trick {
+            Trick::Sit => &mut self.sit,
+            Trick::Come => &mut self.come,
+            Trick::PlayDead => &mut self.play_dead,
+        };
+        *count = (*count + 1).min(MAX_TRAINED);
+    }
+}
+
+/// 关系够不够她理口令：不信任或听不懂的猫一律当没听见
+pub fn will_listen(trust: f32, understanding: f32) -> bool {
+    trust >= MIN_TRICK_TRUST && understanding >= MIN_TRICK_UNDERSTANDING
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SophieState;
+
+    fn cat(trust: f32, understanding: f32) -> SophieState {
+        let mut sophie = SophieState::new();
+        sophie.is_sleeping = false;
+        sophie.woke_at_ts = None;
+        sophie.relationship.trust = trust;
+        sophie.relationship.understanding = understanding;
+        sophie
+    }
+
+    #[test]
+    fn every_trick_parses_from_its_id_and_label() {
+        for trick in Trick::ALL {
+            assert_eq!(Trick::parse(trick.as_str()), Some(trick));
+            assert_eq!(Trick::parse(trick.label()), Some(trick));
+        }
+        assert_eq!(Trick::parse("  Play Dead "), Some(Trick::PlayDead));
+        assert_eq!(Trick::parse("roll over"), None);
+    }
+
+    #[test]
+    fn low_trust_ignores_every_trick() {
+        let mut sophie = cat(10.0, 80.0);
+        for trick in Trick::ALL {
+            assert!(!sophie.give_command(trick, 0.0));
+            assert_eq!(sophie.tricks.trained(trick), 0);
+        }
+        // 信任够但听不懂也不行
+        let mut sophie = cat(80.0, 5.0);
+        assert!(!sophie.give_command(Trick::Sit, 0.0));
+    }
+
+    #[test]
+    fn training_makes_tricks_succeed() {
+        let mut sophie = cat(80.0, 50.0);
+        for trick in Trick::ALL {
+            // 同一个随机数，没训练时不理，训练几次后就照做了
+            let r = trick.base_chance() + 0.05;
+            assert!(!sophie.give_command(trick, r));
+            for _ in 0..3 {
+                assert!(sophie.give_command(trick, 0.0));
+            }
+            assert_eq!(sophie.tricks.trained(trick), 3);
+            assert!(sophie.give_command(trick, r));
+        }
+    }
+
+    #[test]
+    fn success_chance_is_capped() {
+        let mut training = TrickTraining::new();
+        for _ in 0..100 {
+            training.record_success(Trick::PlayDead);
+        }
+        assert_eq!(training.trained(Trick::PlayDead), MAX_TRAINED);
+        assert_eq!(training.success_chance(Trick::PlayDead), MAX_SUCCESS_CHANCE);
+    }
+}
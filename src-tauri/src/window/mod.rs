@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+use crate::memory::MemoryStore;
+
+/// 窗口偏好在 sophie_state 表里的 key
+const PREFS_KEY: &str = "window_prefs";
+/// 桌宠窗口的 label（见 tauri.conf.json）
+const WINDOW_LABEL: &str = "sophie";
+/// 透明度下限：太低就找不到她了
+const MIN_OPACITY: f32 = 0.2;
+
+/// 桌宠窗口的偏好设置（持久化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowPrefs {
+    pub always_on_top: bool,
+    /// 点击穿透：整个窗口不接收鼠标事件，可以从托盘菜单恢复
+    pub click_through: bool,
+    /// 透明度 0.2-1.0（Tauri 没有窗口级透明度，由前端用 CSS 应用）
+    pub opacity: f32,
+    /// 从托盘重新显示时她打个招呼
+    #[serde(default = "default_greet_on_show")]
+    pub greet_on_show: bool,
+}
+
+fn default_greet_on_show() -> bool {
+    true
+}
+
+impl WindowPrefs {
+    pub fn new() -> Self {
+        Self {
+            always_on_top: true,
+            click_through: false,
+            opacity: 1.0,
+            greet_on_show: true,
+        }
+    }
+
+    /// 从数据库读取，没有或损坏时用默认值
+    pub fn load(mem: &MemoryStore) -> Self {
+        mem.load_state(PREFS_KEY)
+            .and_then(|json| serde_json::from_str::<WindowPrefs>(&json).ok())
+            .map(|mut prefs| {
+                prefs.opacity = clamp_opacity(prefs.opacity);
+                prefs
+            })
+            .unwrap_or_else(WindowPrefs::new)
+    }
+
+    pub fn save(&self, mem: &MemoryStore) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| format!("Serialize error: {}", e))?;
+        mem.save_state(PREFS_KEY, &json)
+    }
+
+    /// 把全部偏好应用到窗口上（启动时调用）
+    pub fn apply(&self, app: &AppHandle) -> Result<(), String> {
+        let window = sophie_window(app)?;
+        window
+            .set_always_on_top(self.always_on_top)
+            .map_err(|e| format!("Failed to set always-on-top: {}", e))?;
+        window
+            .set_ignore_cursor_events(self.click_through)
+            .map_err(|e| format!("Failed to set click-through: {}", e))?;
+        let _ = app.emit("sophie-opacity", self.opacity);
+        Ok(())
+    }
+}
+
+pub fn clamp_opacity(opacity: f32) -> f32 {
+    if opacity.is_finite() {
+        opacity.clamp(MIN_OPACITY, 1.0)
+    } else {
+        1.0
+    }
+}
+
+/// 获取桌宠窗口，不存在时返回错误而不是 panic
+pub fn sophie_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    app.get_webview_window(WINDOW_LABEL)
+        .ok_or_else(|| format!("Window '{}' not found", WINDOW_LABEL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opacity_is_clamped_to_a_visible_range() {
+        assert_eq!(clamp_opacity(0.0), MIN_OPACITY);
+        assert_eq!(clamp_opacity(0.6), 0.6);
+        assert_eq!(clamp_opacity(3.0), 1.0);
+        assert_eq!(clamp_opacity(f32::NAN), 1.0);
+    }
+
+    #[test]
+    fn prefs_round_trip_and_fall_back_to_defaults() {
+        let mem = MemoryStore::open_in_memory().unwrap();
+        assert!(WindowPrefs::load(&mem).always_on_top);
+
+        let prefs = WindowPrefs { always_on_top: false, click_through: true, opacity: 0.5, greet_on_show: false };
+        prefs.save(&mem).unwrap();
+        let loaded = WindowPrefs::load(&mem);
+        assert!(!loaded.always_on_top);
+        assert!(loaded.click_through);
+        assert_eq!(loaded.opacity, 0.5);
+        assert!(!loaded.greet_on_show);
+
+        // 手改过的越界值读回来时会被夹住；坏 JSON 用默认值
+        mem.save_state(PREFS_KEY, r#"{"alwaysOnTop":true,"clickThrough":false,"opacity":0.01}"#).unwrap();
+        assert_eq!(WindowPrefs::load(&mem).opacity, MIN_OPACITY);
+        // 加这项之前存的偏好没有 greetOnShow：默认打招呼
+        assert!(WindowPrefs::load(&mem).greet_on_show);
+        mem.save_state(PREFS_KEY, "not json").unwrap();
+        assert_eq!(WindowPrefs::load(&mem).opacity, 1.0);
+    }
+}